@@ -17,7 +17,8 @@ pub struct Tick;
 /// The default micro:bit runner: runs `Startup` once, then loops driving the
 /// `Tick` schedule every iteration (1 ms LED row scan) and the
 /// `PreUpdate`/`Update`/`PostUpdate` schedules once every [`ROW_COUNT`] ticks
-/// (one frame).
+/// (one frame). Event buffers are swapped at the start of every frame, so an
+/// event stays readable through the frame after the one it was sent in.
 ///
 /// Pass this to [`App::run`] after all plugins are registered:
 ///
@@ -37,7 +38,9 @@ pub fn microbit_runner<W: WorldApi>(world: &mut W) -> AppExit {
 
         tick = tick.wrapping_add(1);
         if tick.is_multiple_of(ROW_COUNT) {
-            // Refresh inputs and the clock, then run the game logic.
+            // Retire last frame's events, refresh inputs and the clock, then
+            // run the game logic.
+            world.update_events();
             world.run_schedule(PreUpdate);
             world.run_schedule(Update);
             world.run_schedule(PostUpdate);
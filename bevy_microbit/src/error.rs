@@ -0,0 +1,14 @@
+//! An error handler logging failed systems over RTT.
+//!
+//! Install it with `app.set_error_handler(bevy_microbit::error::log)`; the
+//! other stock handlers live in [`tiny_ecs::error`].
+
+use rtt_target::rprintln;
+use tiny_ecs::error::SystemFailed;
+
+/// Prints the failing system's name and error over RTT, then carries on.
+///
+/// Prints nothing until the application has initialised RTT.
+pub fn log<W>(_world: &mut W, failed: SystemFailed<'_>) {
+    rprintln!("system {} failed: {:?}", failed.system, failed.error);
+}
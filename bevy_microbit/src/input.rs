@@ -114,7 +114,7 @@ impl ButtonKey for GameButton {
 pub struct MicrobitInputPlugin;
 impl<W: WorldApi> Plugin<W> for MicrobitInputPlugin where W: HasResource<Device> + HasResource<ButtonInput<GameButton>> {
     fn build(&self, app: &mut App<W>) {
-        app.insert_resource(ButtonInput::<GameButton>::default());
+        app.init_resource::<ButtonInput<GameButton>>();
         app.add_system(tiny_ecs::schedule::PreUpdate, read_buttons);
     }
 }
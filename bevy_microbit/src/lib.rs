@@ -16,6 +16,7 @@
 
 pub mod app;
 pub mod device;
+pub mod error;
 pub mod framebuffer;
 pub mod input;
 pub mod render;
@@ -75,8 +75,8 @@ where
         + HasResource<RenderState>
 {
     fn build(&self, app: &mut App<W>) {
-        app.insert_resource(RenderState::new());
-        app.insert_resource(FrameBuffer::new());
+        app.init_resource::<RenderState>();
+        app.init_resource::<FrameBuffer>();
         app.add_system(crate::app::Tick, render_row);
     }
 }
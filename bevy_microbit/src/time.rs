@@ -13,7 +13,7 @@ use crate::app::FRAME_MILLIS;
 pub struct MicrobitTimePlugin;
 impl<W: WorldApi> Plugin<W> for MicrobitTimePlugin where W: HasResource<tiny_ecs::Time> {
     fn build(&self, app: &mut App<W>) {
-        app.insert_resource(tiny_ecs::Time::new());
+        app.init_resource::<tiny_ecs::Time>();
         app.add_system(tiny_ecs::schedule::PreUpdate, advance_time);
     }
 }
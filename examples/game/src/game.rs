@@ -31,6 +31,7 @@ const OBSTACLES: [[bool; WIDTH]; 11] = [
 
 /// The player's paddle, represented as a single entity.
 #[derive(Component, Debug)]
+#[component(requires(MoveCooldown = default_cooldown))]
 pub struct Player {
     /// Column the player currently occupies (0-4).
     pub x: usize,
@@ -49,6 +50,11 @@ pub struct Debris {
 #[derive(Component, Debug)]
 pub struct MoveCooldown(pub Timer);
 
+/// Builds the cooldown every [`Player`] requires.
+fn default_cooldown() -> MoveCooldown {
+    MoveCooldown(Timer::from_seconds(MOVE_COOLDOWN_SECS, TimerMode::Once))
+}
+
 /// The player's running score.
 #[derive(Resource, Debug, Default)]
 pub struct Score(pub usize);
@@ -90,30 +96,34 @@ impl GameTimers {
     }
 }
 
+/// The gameplay systems, which only run while a round is in progress.
+#[derive(SystemSet)]
+struct Playing;
+
+/// Holds while the round is in progress.
+#[system]
+fn not_game_over(state: Res<GameState>) -> bool {
+    !state.game_over
+}
+
 /// Registers the game's systems and initial world state (resources + player).
 pub struct GamePlugin;
 impl Plugin<crate::World> for GamePlugin {
     fn build(&self, app: &mut App<crate::World>) {
         // Round-level resources.
-        app.world_mut().insert_resource(Score(0));
-        app.world_mut().insert_resource(GameState::default());
+        app.init_resource::<Score>();
+        app.init_resource::<GameState>();
         app.world_mut().insert_resource(GameTimers::new());
 
-        // Spawn the player entity with its move cooldown.
-        if let Some(entity) = app.world_mut().spawn_empty() {
-            app.world_mut().set_component(entity, Player { x: 2 });
-            app.world_mut().set_component(
-                entity,
-                MoveCooldown(Timer::from_seconds(MOVE_COOLDOWN_SECS, TimerMode::Once)),
-            );
-        }
+        // Spawn the player entity; its move cooldown is a required component.
+        app.world_mut().spawn(Player { x: 2 });
 
-        app.add_system(Update, player_input);
-        app.add_system(Update, spawn_debris);
-        app.add_system(Update, fall_debris);
-        app.add_system(Update, collision);
-        app.add_system(Update, reset);
-        app.add_system(Update, draw);
+        // A round moves the player, then the debris, then checks for a hit;
+        // the board is reset and drawn once the round's systems are done.
+        app.configure_set(Update, Playing.run_if(not_game_over));
+        app.add_systems(Update, (player_input, spawn_debris, fall_debris, collision).chain().in_set(Playing));
+        app.add_system(Update, reset.after(Playing));
+        app.add_system(Update, draw.after(reset));
     }
 }
 
@@ -122,15 +132,9 @@ impl Plugin<crate::World> for GamePlugin {
 fn player_input(
     input: Res<ButtonInput<GameButton>>,
     time: Res<Time>,
-    state: Res<GameState>,
-    mut player: Query<(&mut Player, &mut MoveCooldown)>,
+    player: Single<(&mut Player, &mut MoveCooldown)>,
 ) {
-    if state.game_over {
-        return;
-    }
-    let Ok((player, cooldown)) = player.single_mut() else {
-        return;
-    };
+    let (mut player, mut cooldown) = player.into_inner();
 
     cooldown.0.tick(time.delta());
     if !cooldown.0.is_finished() {
@@ -150,7 +154,6 @@ fn player_input(
 #[system]
 fn spawn_debris(
     time: Res<Time>,
-    state: Res<GameState>,
     mut timers: ResMut<GameTimers>,
     mut entropy: ResMut<Entropy>,
     mut commands: Commands,
@@ -158,7 +161,7 @@ fn spawn_debris(
 ) {
     timers.spawn.tick(time.delta());
 
-    if state.game_over || !timers.spawn.just_finished() {
+    if !timers.spawn.just_finished() {
         return;
     }
 
@@ -176,7 +179,6 @@ fn spawn_debris(
 #[system]
 fn fall_debris(
     time: Res<Time>,
-    state: Res<GameState>,
     mut timers: ResMut<GameTimers>,
     mut score: ResMut<Score>,
     mut commands: Commands,
@@ -184,11 +186,11 @@ fn fall_debris(
 ) {
     timers.fall.tick(time.delta());
 
-    if state.game_over || !timers.fall.just_finished() {
+    if !timers.fall.just_finished() {
         return;
     }
 
-    for (entity, piece) in &mut debris {
+    for (entity, mut piece) in &mut debris {
         if piece.y >= HEIGHT - 1 {
             // The piece reached the bottom and is removed for a point.
             commands.entity(entity).despawn();
@@ -201,63 +203,77 @@ fn fall_debris(
 
 /// Ends the round when the falling debris reaches the player.
 #[system]
-fn collision(mut state: ResMut<GameState>, player: Query<&Player>, debris: Query<&Debris>) {
-    if state.game_over {
-        return;
-    }
-    let Ok(player) = player.single() else {
-        return;
-    };
+fn collision(mut state: ResMut<GameState>, player: Single<&Player>, debris: Query<&Debris>) {
     let hit = debris
         .iter()
         .any(|piece| piece.x == player.x && piece.y == HEIGHT - 1);
-    state.game_over = hit;
+    // Only write on a hit, so `GameState` stays unchanged for `draw`.
+    if hit {
+        state.game_over = true;
+    }
 }
 
 /// Starts a fresh round when A is pressed after a game over.
+///
+/// Exclusive, so the whole board is reset in place before `draw` runs.
 #[system]
-fn reset(
-    input: Res<ButtonInput<GameButton>>,
-    mut state: ResMut<GameState>,
-    mut score: ResMut<Score>,
-    mut timers: ResMut<GameTimers>,
-    mut commands: Commands,
-    mut player: Query<(&mut Player, &mut MoveCooldown)>,
-    debris: Query<(Entity, &Debris)>,
-) {
-    if !state.game_over || !input.just_pressed(GameButton::A) {
+fn reset(world: &mut crate::World) {
+    let game_over = world.get_resource::<GameState>().is_some_and(|state| state.game_over);
+    let pressed = world
+        .get_resource::<ButtonInput<GameButton>>()
+        .is_some_and(|input| input.just_pressed(GameButton::A));
+    if !game_over || !pressed {
         return;
     }
 
-    state.game_over = false;
-    score.0 = 0;
-    timers.reset();
-    for (entity, _) in &debris {
-        commands.entity(entity).despawn();
+    world.insert_resource(GameState::default());
+    world.insert_resource(Score(0));
+    if let Some(mut timers) = world.get_resource_mut::<GameTimers>() {
+        timers.reset();
     }
-    if let Ok((player, cooldown)) = player.single_mut() {
+
+    // Clear the board one piece at a time; despawning invalidates the query.
+    while let Some(entity) = world.query_filtered::<Entity, With<Debris>>().iter().next() {
+        world.despawn(entity);
+    }
+    if let Ok((mut player, mut cooldown)) = world.query::<(&mut Player, &mut MoveCooldown)>().single_mut() {
         player.x = 2;
         cooldown.0.reset();
     }
 }
 
-/// Renders the player and all debris into the shared frame buffer.
+/// Renders the player and all debris into the shared frame buffer, skipping
+/// frames in which nothing visible changed. Fails when the player is missing.
 #[system]
-fn draw(mut frame: ResMut<FrameBuffer>, state: Res<GameState>, player: Query<&Player>, debris: Query<&Debris>, time: Res<Time>) {
+fn draw(
+    mut frame: ResMut<FrameBuffer>,
+    state: Res<GameState>,
+    player: Query<&Player>,
+    debris: Query<&Debris>,
+    moved: Query<Entity, Or<(Changed<Player>, Changed<Debris>)>>,
+    removed: RemovedComponents<Debris>,
+    time: Res<Time>,
+) -> Result<(), QuerySingleError> {
+    // The game-over pulse animates every frame; otherwise only repaint when a
+    // piece moved, appeared or disappeared.
+    if !state.game_over && !state.is_changed() && moved.is_empty() && removed.is_empty() {
+        return Ok(());
+    }
+
     frame.clear();
 
     if state.game_over {
         frame.fill_rect(0, 0, WIDTH, HEIGHT, (time.elapsed().as_secs_f32().mul(5.0).sin() * 128.0 + 128.0) as u8);
-        return;
-    }
-
-    // Draw player
-    if let Ok(player) = player.single() {
-        frame.set(player.x, HEIGHT - 1, 255);
+        return Ok(());
     }
 
     // Draw debris
     for piece in &debris {
         frame.set(piece.x, piece.y, 10);
     }
+
+    // Draw player
+    let player = player.single()?;
+    frame.set(player.x, HEIGHT - 1, 255);
+    Ok(())
 }
\ No newline at end of file
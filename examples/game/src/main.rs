@@ -18,9 +18,9 @@ define_world! {
         entities: 64,
         schedules: 8,
         components {
-            player: game::Player [4],
-            debris: game::Debris [64],
-            move_cooldown: game::MoveCooldown [4],
+            player: game::Player [4, sparse],
+            debris: game::Debris [64, removed: 8],
+            move_cooldown: game::MoveCooldown [4, sparse],
         }
         resources {
             device: bevy_microbit::device::Device,
@@ -47,6 +47,8 @@ fn main() -> ! {
 
     // Create the app and run it
     App::new(world)
+        .set_skip_handler(|skipped| rprintln!("skipped {}: {} {:?}", skipped.system, skipped.param, skipped.error))
+        .set_error_handler(bevy_microbit::error::log)
         .add_plugin(MicrobitPlugins)
         .add_plugin(game::GamePlugin)
         .run(microbit_runner);
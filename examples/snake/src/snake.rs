@@ -149,10 +149,7 @@ impl Plugin<crate::World> for SnakePlugin {
         app.insert_resource(GameState::default());
         app.insert_resource(MoveTimer::new());
 
-        app.add_system(Update, steer);
-        app.add_system(Update, advance);
-        app.add_system(Update, restart);
-        app.add_system(Update, draw);
+        app.add_systems(Update, (steer, advance.run_if(alive), restart, draw).chain());
     }
 }
 
@@ -173,6 +170,12 @@ fn steer(input: Res<ButtonInput<GameButton>>, mut snake: ResMut<Snake>) {
     }
 }
 
+/// Holds while the snake is alive.
+#[system]
+fn alive(state: Res<GameState>) -> bool {
+    !state.dead
+}
+
 /// Moves the snake forward on each timer tick, handling eating, growth,
 /// collision, and food relocation.
 #[system]
@@ -184,10 +187,6 @@ fn advance(
     mut food: ResMut<Food>,
     mut entropy: ResMut<Entropy>,
 ) {
-    if state.dead {
-        return;
-    }
-
     timer.0.tick(time.delta());
     if !timer.0.just_finished() {
         return;
@@ -9,9 +9,11 @@
 //! fully programmatic and hardware-agnostic.
 
 use crate::commands_buffer::CommandBuffer;
-use crate::schedule::ScheduleLabel;
-use crate::system::{IntoSystem, ResourceInsRef};
-use crate::world::WorldApi;
+use crate::error::SystemFailed;
+use crate::observer::ComponentEvent;
+use crate::schedule::{IntoSetConfig, IntoSystemConfig, IntoSystemConfigs, ScheduleLabel};
+use crate::system::{HasResource, IntoSystem, ResourceInsRef, SystemSkipped};
+use crate::world::{FromWorld, WorldApi};
 
 /// Extension point mirroring Bevy's `Plugin`.
 ///
@@ -73,15 +75,68 @@ impl<W: WorldApi> App<W> {
         self
     }
 
+    /// Inserts `R` built by [`FromWorld`] (e.g. its `Default`) unless the
+    /// resource is already present, mirroring Bevy's `App::init_resource`.
+    pub fn init_resource<R: FromWorld<W> + 'static>(&mut self) -> &mut Self
+    where
+        W: HasResource<R>,
+    {
+        self.world.init_resource::<R>();
+        self
+    }
+
     /// Adds a schedule under label `L` if it does not already exist.
     pub fn add_schedule<L: ScheduleLabel>(&mut self, label: L) -> &mut Self {
         self.world.add_schedule(label);
         self
     }
 
-    /// Adds a system to the schedule identified by label `L`.
-    pub fn add_system<L: ScheduleLabel, S: IntoSystem<W>>(&mut self, label: L, system: S) -> &mut Self {
-        self.world.add_system(label, system.into_system());
+    /// Adds a system to the schedule identified by label `L`, optionally with
+    /// run conditions, a set and ordering:
+    /// `add_system(Update, fall_debris.run_if(playing).before(collision))`.
+    pub fn add_system<L: ScheduleLabel, M, S: IntoSystemConfig<W, M>>(&mut self, label: L, system: S) -> &mut Self {
+        self.world.add_system(label, system);
+        self
+    }
+
+    /// Adds a tuple of systems to the schedule identified by label `L`, e.g.
+    /// `add_systems(Update, (player_input, fall_debris, collision).chain())` to
+    /// run them in that order.
+    pub fn add_systems<L: ScheduleLabel, M, S: IntoSystemConfigs<W, M>>(&mut self, label: L, systems: S) -> &mut Self {
+        self.world.add_systems(label, systems);
+        self
+    }
+
+    /// Gives the system set `S` run conditions shared by all its systems in
+    /// the schedule identified by label `L`:
+    /// `configure_set(Update, Playing.run_if(not_game_over))`.
+    pub fn configure_set<L: ScheduleLabel, S: IntoSetConfig<W>>(&mut self, label: L, set: S) -> &mut Self {
+        self.world.configure_set(label, set);
+        self
+    }
+
+    /// Registers `system` as an observer of the lifecycle event `E`, e.g.
+    /// `app.observe::<OnRemove<Debris>>(crash_sound)`. The system receives the
+    /// entity through a `Trigger<E>` param.
+    pub fn observe<E: ComponentEvent>(&mut self, system: impl IntoSystem<W>) -> &mut Self {
+        self.world.add_observer::<E>(system.into_system());
+        self
+    }
+
+    /// Installs the handler receiving a diagnostic for every system skipped
+    /// because a `Single` param found no unique match (e.g. to log it over
+    /// RTT).
+    pub fn set_skip_handler(&mut self, handler: fn(SystemSkipped)) -> &mut Self {
+        self.world.set_skip_handler(handler);
+        self
+    }
+
+    /// Installs the handler receiving the name and error of every fallible
+    /// system that fails: one of the stock handlers in
+    /// [`error`](crate::error) (panic, ignore, count into a resource) or
+    /// `bevy_microbit::error::log` to log over RTT.
+    pub fn set_error_handler(&mut self, handler: fn(&mut W, SystemFailed<'_>)) -> &mut Self {
+        self.world.set_error_handler(handler);
         self
     }
 
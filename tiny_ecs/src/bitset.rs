@@ -0,0 +1,141 @@
+//! Fixed-size bitsets tracking which entity ids a column occupies.
+//!
+//! Every component column keeps a [`BitSet`] alongside its slots, one bit per
+//! addressable entity id. Queries AND the bitsets of their columns one `u32`
+//! word at a time, so whole runs of empty slots are skipped without probing a
+//! single component, and counting matches is a popcount per word.
+
+/// Number of bits stored per bitset word.
+pub const WORD_BITS: usize = u32::BITS as usize;
+
+/// Returns the number of `u32` words needed to hold `bits` bits.
+///
+/// `define_world!` uses it to size the `W` const generic of every column.
+pub const fn words_for(bits: usize) -> usize {
+    bits.div_ceil(WORD_BITS)
+}
+
+/// A bitset of `W * 32` bits, stored as `W` `u32` words.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitSet<const W: usize> {
+    /// The packed bits; bit `i` lives in `words[i / 32]` at position `i % 32`.
+    words: [u32; W],
+}
+
+impl<const W: usize> BitSet<W> {
+    /// Creates a bitset with every bit cleared.
+    pub const fn new() -> Self {
+        Self { words: [0; W] }
+    }
+
+    /// Sets bit `index`; out-of-range indices are ignored.
+    pub fn insert(&mut self, index: usize) {
+        if let Some(word) = self.words.get_mut(index / WORD_BITS) {
+            *word |= 1 << (index % WORD_BITS);
+        }
+    }
+
+    /// Clears bit `index`; out-of-range indices are ignored.
+    pub fn remove(&mut self, index: usize) {
+        if let Some(word) = self.words.get_mut(index / WORD_BITS) {
+            *word &= !(1 << (index % WORD_BITS));
+        }
+    }
+
+    /// Returns `true` when bit `index` is set.
+    pub fn contains(&self, index: usize) -> bool {
+        self.words
+            .get(index / WORD_BITS)
+            .is_some_and(|word| word & (1 << (index % WORD_BITS)) != 0)
+    }
+
+    /// Returns the number of set bits.
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Returns the raw words.
+    pub const fn words(&self) -> &[u32] {
+        &self.words
+    }
+}
+
+impl<const W: usize> Default for BitSet<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Iterates the indices of the set bits of a single word, offset by `base`.
+///
+/// Used by query iteration to visit the candidates of one ANDed word.
+#[derive(Clone, Debug)]
+pub struct Ones {
+    /// Remaining set bits; the lowest one is yielded next.
+    mask: u32,
+    /// Index of bit 0 of `mask`.
+    base: usize,
+}
+
+impl Ones {
+    /// Creates an iterator over the set bits of `mask`, word `word` of a set.
+    pub const fn new(mask: u32, word: usize) -> Self {
+        Self {
+            mask,
+            base: word * WORD_BITS,
+        }
+    }
+}
+
+impl Iterator for Ones {
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        if self.mask == 0 {
+            return None;
+        }
+
+        // Take the lowest set bit, then clear it.
+        let bit = self.mask.trailing_zeros() as usize;
+        self.mask &= self.mask - 1;
+        Some(self.base + bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_cover_partial_last_word() {
+        assert_eq!(words_for(0), 0);
+        assert_eq!(words_for(32), 1);
+        assert_eq!(words_for(33), 2);
+    }
+
+    #[test]
+    fn bits_at_word_edges_are_independent() {
+        let mut set = BitSet::<2>::new();
+        for index in [0, 31, 32, 63] {
+            set.insert(index);
+        }
+        assert_eq!(set.words(), &[1 | 1 << 31, 1 | 1 << 31]);
+        assert_eq!(set.count(), 4);
+
+        set.remove(31);
+        assert!(!set.contains(31));
+        assert!(set.contains(32));
+
+        // Indices past the last word are ignored.
+        set.insert(64);
+        assert!(!set.contains(64));
+        assert_eq!(set.count(), 3);
+    }
+
+    #[test]
+    fn ones_yields_every_bit_offset_by_its_word() {
+        let ones: [usize; 3] = [33, 34, 63];
+        assert!(Ones::new(0b11 << 1 | 1 << 31, 1).eq(ones));
+        assert!(Ones::new(u32::MAX, 0).eq(0..32));
+        assert_eq!(Ones::new(0, 3).next(), None);
+    }
+}
@@ -0,0 +1,114 @@
+//! Component bundles: groups of components spawned or inserted together,
+//! mirroring `bevy_ecs::bundle`.
+//!
+//! Every component is a bundle of one, tuples of bundles (up to arity 8) are
+//! bundles, and `#[derive(Bundle)]` makes a struct whose fields are bundles a
+//! bundle too. Inserting a bundle enqueues each component on its typed pending
+//! queue, so they all land in their columns on the same
+//! [`WorldApi::flush_commands`](crate::world::WorldApi::flush_commands).
+
+use crate::entity::Entity;
+use crate::system::EntityCommandsRef;
+use crate::Component;
+
+/// A set of components inserted onto an entity as a unit.
+///
+/// Generic over the concrete world `W` so every component of the bundle is
+/// checked for a registered column at compile time.
+pub trait Bundle<W>: Sized {
+    /// Enqueues every component of the bundle for insertion on `entity`.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut W`.
+    unsafe fn enqueue_insert(self, world: *mut W, entity: Entity);
+}
+
+impl<W: EntityCommandsRef<C>, C: Component> Bundle<W> for C {
+    unsafe fn enqueue_insert(self, world: *mut W, entity: Entity) {
+        // SAFETY: the caller guarantees `world` is valid.
+        unsafe { W::enqueue_insert(world, entity, self) }
+    }
+}
+
+// =====================================================================
+// --- Tuple impls ------------------------------------------------------
+
+/// Implements [`Bundle`] for a tuple of bundles, enqueuing each in order.
+macro_rules! impl_bundle_tuple {
+    ($($b:ident),*) => {
+        impl<W, $($b: Bundle<W>),*> Bundle<W> for ($($b,)*) {
+            #[allow(non_snake_case, unused_variables)]
+            unsafe fn enqueue_insert(self, world: *mut W, entity: Entity) {
+                let ($($b,)*) = self;
+                // SAFETY: the caller guarantees `world` is valid.
+                $( unsafe { $b.enqueue_insert(world, entity) }; )*
+            }
+        }
+    };
+}
+
+impl_bundle_tuple!();
+impl_bundle_tuple!(B0);
+impl_bundle_tuple!(B0, B1);
+impl_bundle_tuple!(B0, B1, B2);
+impl_bundle_tuple!(B0, B1, B2, B3);
+impl_bundle_tuple!(B0, B1, B2, B3, B4);
+impl_bundle_tuple!(B0, B1, B2, B3, B4, B5);
+impl_bundle_tuple!(B0, B1, B2, B3, B4, B5, B6);
+impl_bundle_tuple!(B0, B1, B2, B3, B4, B5, B6, B7);
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Pos(u32);
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Vel(i8);
+
+    #[derive(Component)]
+    struct Tag;
+
+    #[derive(Bundle)]
+    struct Body {
+        pos: Pos,
+        vel: Vel,
+    }
+
+    #[derive(Bundle)]
+    struct TaggedBody {
+        body: Body,
+        tag: Tag,
+    }
+
+    define_world! {
+        struct World {
+            entities: 4,
+            schedules: 1,
+            components { pos: Pos [4], vel: Vel [4], tag: Tag [4, sparse], }
+            resources {}
+        }
+    }
+
+    #[test]
+    fn tuples_and_derived_bundles_land_together() {
+        let mut world = World::new();
+        let tuple = world.spawn((Pos(1), (Vel(-1), Tag))).expect("budget");
+        let nested = world.spawn(TaggedBody { body: Body { pos: Pos(2), vel: Vel(2) }, tag: Tag }).expect("budget");
+        let plain = world.spawn(Body { pos: Pos(3), vel: Vel(3) }).expect("budget");
+
+        let query = world.query::<(&Pos, &Vel, Option<&Tag>)>();
+        let read = |entity| query.get(entity).map(|(pos, vel, tag)| (pos.0, vel.0, tag.is_some()));
+        assert_eq!(read(tuple), Ok((1, -1, true)));
+        assert_eq!(read(nested), Ok((2, 2, true)));
+        assert_eq!(read(plain), Ok((3, 3, false)));
+    }
+
+    #[test]
+    fn later_bundle_members_replace_earlier_ones() {
+        let mut world = World::new();
+        let entity = world.spawn((Pos(1), Pos(2))).expect("budget");
+        assert_eq!(world.query::<&Pos>().get(entity), Ok(&Pos(2)));
+    }
+}
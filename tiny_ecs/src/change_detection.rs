@@ -0,0 +1,340 @@
+//! Change detection: ticks, [`Mut`], and the [`DetectChanges`] traits,
+//! mirroring `bevy_ecs::change_detection`.
+//!
+//! The `World` keeps a change tick that advances once per system run inside
+//! `run_schedule`. Every component slot and every resource records the tick it
+//! was added at and the tick it was last mutably dereferenced at
+//! ([`ComponentTicks`]); every system remembers the tick of its previous run.
+//! A value counts as added / changed for a system when its tick is newer than
+//! that previous run, which is what the [`Added`](crate::filter::Added) and
+//! [`Changed`](crate::filter::Changed) filters and [`DetectChanges`] report.
+//!
+//! Writes only count when they go through `DerefMut`: `&mut T` query items are
+//! [`Mut`] wrappers and [`ResMut`](crate::system::ResMut) marks its resource
+//! the same way, so a system that merely reads through them leaves no trace.
+//!
+//! The `u32` counter wraps after 2^32 system runs, and a tick that old would
+//! read as newer than the system's last run. Mirroring Bevy's
+//! `check_change_ticks`, `flush_commands` therefore clamps every stored tick
+//! to at most [`MAX_CHANGE_AGE`] ticks in the past once the change tick has
+//! advanced [`CHECK_TICK_THRESHOLD`] since the previous pass, so values left
+//! untouched for days keep reading as unchanged.
+
+use core::ops::{Deref, DerefMut};
+
+/// How far the change tick may advance between two passes of
+/// [`WorldApi::check_change_ticks`](crate::world::WorldApi::check_change_ticks).
+pub const CHECK_TICK_THRESHOLD: u32 = 518_400_000;
+
+/// The largest age a stored tick is clamped to. Leaves room for a full
+/// [`CHECK_TICK_THRESHOLD`] between passes, plus the one a value may have
+/// aged before the pass that first sees it.
+pub const MAX_CHANGE_AGE: u32 = u32::MAX - (2 * CHECK_TICK_THRESHOLD - 1);
+
+/// A point in the world's change-tick timeline.
+///
+/// Compared relative to a system's run (see [`Tick::is_newer_than`]); the
+/// `u32` counter may wrap around as long as stored ticks are clamped with
+/// [`Tick::check_tick`] (see the module docs).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Tick {
+    /// The raw tick counter.
+    tick: u32,
+}
+
+impl Tick {
+    /// Creates a tick from a raw counter value.
+    pub const fn new(tick: u32) -> Self {
+        Self { tick }
+    }
+
+    /// Returns the raw counter value.
+    pub const fn get(self) -> u32 {
+        self.tick
+    }
+
+    /// Returns the tick following this one.
+    pub const fn next(self) -> Self {
+        Self::new(self.tick.wrapping_add(1))
+    }
+
+    /// Returns `true` when `self` happened after `last_run`, as seen from a
+    /// system running at `this_run`.
+    pub const fn is_newer_than(self, last_run: Tick, this_run: Tick) -> bool {
+        // Measure both distances backwards from `this_run`, so wrapping
+        // counters still order correctly.
+        // A system that has not run for longer than any stored tick may be
+        // old is capped like those ticks are.
+        let since_insert = this_run.tick.wrapping_sub(self.tick);
+        let since_system = this_run.tick.wrapping_sub(last_run.tick);
+        let since_system = if since_system > MAX_CHANGE_AGE { MAX_CHANGE_AGE } else { since_system };
+        since_system > since_insert
+    }
+
+    /// Clamps `self` to at most [`MAX_CHANGE_AGE`] ticks before `this_run`,
+    /// so it cannot wrap around to look recent. Returns `true` when clamped.
+    pub fn check_tick(&mut self, this_run: Tick) -> bool {
+        if this_run.tick.wrapping_sub(self.tick) <= MAX_CHANGE_AGE {
+            return false;
+        }
+        self.tick = this_run.tick.wrapping_sub(MAX_CHANGE_AGE);
+        true
+    }
+}
+
+/// The added / last-changed ticks of a component slot or resource.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComponentTicks {
+    /// The tick the value was inserted at.
+    pub added: Tick,
+    /// The tick the value was last mutably dereferenced (or inserted) at.
+    pub changed: Tick,
+}
+
+impl ComponentTicks {
+    /// Creates the ticks of a value inserted at `tick`.
+    pub const fn new(tick: Tick) -> Self {
+        Self {
+            added: tick,
+            changed: tick,
+        }
+    }
+
+    /// Returns `true` when the value was added since `last_run`.
+    pub const fn is_added(&self, last_run: Tick, this_run: Tick) -> bool {
+        self.added.is_newer_than(last_run, this_run)
+    }
+
+    /// Returns `true` when the value was added or changed since `last_run`.
+    pub const fn is_changed(&self, last_run: Tick, this_run: Tick) -> bool {
+        self.changed.is_newer_than(last_run, this_run)
+    }
+
+    /// Clamps both ticks relative to `this_run`, see [`Tick::check_tick`].
+    pub fn check_ticks(&mut self, this_run: Tick) {
+        self.added.check_tick(this_run);
+        self.changed.check_tick(this_run);
+    }
+}
+
+/// The ticks of the system currently running: its previous run and this one.
+///
+/// The `World` keeps one `SystemTicks`, rewritten by the schedule before each
+/// system; outside systems `this_run` is the tick of the latest system run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemTicks {
+    /// The tick the running system last ran at (`0` before its first run).
+    pub last_run: Tick,
+    /// The tick the running system runs at, i.e. the world's change tick.
+    pub this_run: Tick,
+}
+
+impl SystemTicks {
+    /// Advances the change tick for a system that last ran at `last_run` and
+    /// returns the tick it now runs at.
+    pub fn begin(&mut self, last_run: Tick) -> Tick {
+        self.this_run = self.this_run.next();
+        self.last_run = last_run;
+        self.this_run
+    }
+
+    /// Returns the tick recorded by writes made outside systems (spawns,
+    /// command flushes, `insert_resource`): the tick the next system runs at,
+    /// so every system observes them.
+    pub const fn write_tick(&self) -> Tick {
+        self.this_run.next()
+    }
+}
+
+// =====================================================================
+// --- Detection traits -------------------------------------------------
+
+/// Change queries on [`Res`](crate::system::Res),
+/// [`ResMut`](crate::system::ResMut) and [`Mut`].
+pub trait DetectChanges {
+    /// Returns `true` when the value was added since the system last ran.
+    fn is_added(&self) -> bool;
+    /// Returns `true` when the value was added or mutated since the system
+    /// last ran.
+    fn is_changed(&self) -> bool;
+    /// Returns the tick the value was last changed at.
+    fn last_changed(&self) -> Tick;
+}
+
+/// Change marking on [`ResMut`](crate::system::ResMut) and [`Mut`].
+pub trait DetectChangesMut: DetectChanges {
+    /// The wrapped value type.
+    type Inner: ?Sized;
+    /// Marks the value as changed without touching it.
+    fn set_changed(&mut self);
+    /// Returns the value without marking it as changed.
+    fn bypass_change_detection(&mut self) -> &mut Self::Inner;
+}
+
+/// A mutable borrow that records a change when it is mutably dereferenced,
+/// mirroring Bevy's `Mut`. Yielded by `&mut T` query fetches.
+pub struct Mut<'w, T: ?Sized> {
+    /// The borrowed value.
+    value: &'w mut T,
+    /// The value's ticks, stamped on `DerefMut`.
+    ticks: &'w mut ComponentTicks,
+    /// The running system's ticks.
+    system: SystemTicks,
+}
+
+impl<'w, T: ?Sized> Mut<'w, T> {
+    /// Wraps `value` and its `ticks` for a system running with `system`.
+    pub fn new(value: &'w mut T, ticks: &'w mut ComponentTicks, system: SystemTicks) -> Self {
+        Self {
+            value,
+            ticks,
+            system,
+        }
+    }
+
+    /// Returns the borrow, marking the value as changed.
+    pub fn into_inner(self) -> &'w mut T {
+        self.ticks.changed = self.system.this_run;
+        self.value
+    }
+}
+
+impl<T: ?Sized> DetectChanges for Mut<'_, T> {
+    fn is_added(&self) -> bool {
+        self.ticks.is_added(self.system.last_run, self.system.this_run)
+    }
+
+    fn is_changed(&self) -> bool {
+        self.ticks.is_changed(self.system.last_run, self.system.this_run)
+    }
+
+    fn last_changed(&self) -> Tick {
+        self.ticks.changed
+    }
+}
+
+impl<T: ?Sized> DetectChangesMut for Mut<'_, T> {
+    type Inner = T;
+
+    fn set_changed(&mut self) {
+        self.ticks.changed = self.system.this_run;
+    }
+
+    fn bypass_change_detection(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+impl<T: ?Sized> Deref for Mut<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T: ?Sized> DerefMut for Mut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.set_changed();
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Pos(u32);
+
+    #[derive(Resource, Default)]
+    struct Seen(usize);
+
+    define_world! {
+        struct World {
+            entities: 4,
+            schedules: 1,
+            components { pos: Pos [4], }
+            resources { seen: Seen, }
+        }
+    }
+
+    #[system]
+    fn count_changed(pos: Query<&Pos, Changed<Pos>>, mut seen: ResMut<Seen>) {
+        seen.0 = pos.count();
+    }
+
+    #[system]
+    fn read_through_mut(mut pos: Query<&mut Pos>) {
+        for pos in pos.iter_mut() {
+            let _ = pos.0;
+        }
+    }
+
+    #[system]
+    fn write_through_mut(mut pos: Query<&mut Pos>) {
+        for mut pos in pos.iter_mut() {
+            pos.0 += 1;
+        }
+    }
+
+    #[test]
+    fn ticks_compare_across_the_wrap() {
+        let (last_run, this_run) = (Tick::new(u32::MAX - 1), Tick::new(3));
+        assert!(Tick::new(1).is_newer_than(last_run, this_run));
+        assert!(Tick::new(u32::MAX).is_newer_than(last_run, this_run));
+        assert!(!Tick::new(u32::MAX - 2).is_newer_than(last_run, this_run));
+    }
+
+    #[test]
+    fn check_tick_clamps_only_old_ticks() {
+        let this_run = Tick::new(5);
+        let mut recent = Tick::new(5u32.wrapping_sub(MAX_CHANGE_AGE));
+        assert!(!recent.check_tick(this_run));
+        let mut old = Tick::new(recent.get() - 1);
+        assert!(old.check_tick(this_run));
+        assert_eq!(old, recent);
+        assert!(!recent.check_tick(this_run));
+    }
+
+    #[test]
+    fn writes_count_only_through_deref_mut() {
+        let mut world = World::new();
+        world.init_resource::<Seen>();
+        world.spawn(Pos(0));
+        world.add_system(Update, count_changed);
+        world.run_schedule(Update);
+        assert_eq!(world.get_resource::<Seen>().map(|seen| seen.0), Some(1));
+        world.run_schedule(Update);
+        assert_eq!(world.get_resource::<Seen>().map(|seen| seen.0), Some(0));
+
+        world.add_system(Update, read_through_mut.before(count_changed));
+        world.run_schedule(Update);
+        assert_eq!(world.get_resource::<Seen>().map(|seen| seen.0), Some(0));
+
+        world.add_system(Update, write_through_mut.before(count_changed));
+        world.run_schedule(Update);
+        assert_eq!(world.get_resource::<Seen>().map(|seen| seen.0), Some(1));
+    }
+
+    #[test]
+    fn untouched_values_stay_unchanged_across_the_wrap() {
+        let mut world = World::new();
+        world.spawn(Pos(0));
+
+        // Advance the change tick almost a full turn, flushing on the way
+        // like a runner does.
+        for _ in 0..8 {
+            world.ticks.this_run = Tick::new(world.ticks.this_run.get() + CHECK_TICK_THRESHOLD);
+            world.flush_commands();
+        }
+
+        // A system that last ran just before the wrap, running just after
+        // it, would see the spawn tick as brand new without the clamp.
+        let (last_run, this_run) = (Tick::new(u32::MAX - 9), Tick::new(10));
+        assert!(ComponentTicks::new(Tick::new(1)).is_added(last_run, this_run));
+        world.ticks = SystemTicks { last_run, this_run };
+        assert_eq!(world.query_filtered::<&Pos, Added<Pos>>().count(), 0);
+        assert_eq!(world.query_filtered::<&Pos, Changed<Pos>>().count(), 0);
+    }
+}
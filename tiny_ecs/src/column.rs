@@ -1,27 +1,63 @@
 //! Bounded, entity-indexed component storage.
 //!
-//! Each `Component` type owns exactly one [`Column<T, N>`]: a fixed
-//! `[Option<T>; N]` indexed by entity id, with a free-list managed centrally by
-//! the `World`. There are no archetypes and no sparse sets — iteration simply
-//! walks the slots and yields the populated ones.
+//! Each `Component` type owns exactly one column, in one of two layouts:
+//!
+//! * [`Column<T, N, W>`] — a fixed `[Option<T>; N]` indexed directly by entity id.
+//!   The default; O(1) access, but only entities with an id below `N` can
+//!   hold the component.
+//! * [`SparseColumn<T, N, E, W>`] — a sparse set holding up to `N` components for
+//!   any entity id below `E` (the world's entity budget). Meant for rare
+//!   components, so they neither cost an entity-sized `[Option<T>; E]` nor
+//!   lose data when their entity's id is large.
+//!
+//! Entity ids come from a free-list managed centrally by the `World`. There
+//! are no archetypes. Instead, both layouts keep an occupancy [`BitSet`] with
+//! one bit per addressable entity id (the `W` const generic is its word count,
+//! see [`words_for`](crate::bitset::words_for)); queries AND those bitsets word
+//! by word and only probe the slots that survive.
+//!
+//! Next to every stored component, both layouts keep its [`ComponentTicks`]
+//! for change detection: the tick it was inserted at and the tick it was last
+//! mutably dereferenced at.
+
+use crate::bitset::{words_for, BitSet, WORD_BITS};
+use crate::change_detection::{ComponentTicks, Tick};
 
 /// A single component column: `N` slots, each `Option<T>`, indexed by entity id.
 ///
 /// `N` is a per-component compile-time capacity exported via the const generic,
-/// so each component type can be given just the budget it needs.
-pub struct Column<T, const N: usize> {
+/// so each component type can be given just the budget it needs. `W` must be
+/// `words_for(N)`.
+pub struct Column<T, const N: usize, const W: usize> {
     /// The per-entity slots; `slots[i]` is `Some` when entity `i` has `T`.
     slots: [Option<T>; N],
+    /// The change ticks of each occupied slot.
+    ticks: [ComponentTicks; N],
+    /// Bit `i` is set when `slots[i]` is `Some`.
+    occupied: BitSet<W>,
 }
 
-impl<T, const N: usize> Column<T, N> {
+impl<T, const N: usize, const W: usize> Column<T, N, W> {
     /// Creates a column where every slot is empty.
     pub const fn new() -> Self {
+        const { assert!(W == words_for(N), "column word count must be `words_for(N)`") };
         Self {
             slots: [const { None }; N],
+            ticks: [ComponentTicks::new(Tick::new(0)); N],
+            occupied: BitSet::new(),
         }
     }
 
+    /// Returns the number of stored components.
+    pub fn len(&self) -> usize {
+        self.occupied.count()
+    }
+
+    /// Returns `true` when no component is stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// Returns the compile-time capacity.
     pub const fn capacity(&self) -> usize {
         N
@@ -45,18 +81,45 @@ impl<T, const N: usize> Column<T, N> {
         }
     }
 
+    /// Returns the component at `index` together with its change ticks, if
+    /// present.
+    pub fn get_mut_with_ticks(&mut self, index: usize) -> Option<(&mut T, &mut ComponentTicks)> {
+        let value = self.slots.get_mut(index)?.as_mut()?;
+        Some((value, &mut self.ticks[index]))
+    }
+
+    /// Returns the change ticks of the component at `index`, if present.
+    pub fn ticks(&self, index: usize) -> Option<ComponentTicks> {
+        self.slots.get(index)?.as_ref().map(|_| self.ticks[index])
+    }
+
     /// Inserts `value` at `index`, replacing any existing component and
     /// returning the previous one.
-    pub fn insert(&mut self, index: usize, value: T) -> Option<T> {
+    ///
+    /// A new component is stamped as added at `tick`; a replaced one only as
+    /// changed. Hands `value` back as `Err` when `index >= N`.
+    pub fn insert(&mut self, index: usize, value: T, tick: Tick) -> Result<Option<T>, T> {
         if index >= N {
-            return None;
+            return Err(value);
         }
-        self.slots[index].replace(value)
+        self.occupied.insert(index);
+        let previous = self.slots[index].replace(value);
+        match previous {
+            Some(_) => self.ticks[index].changed = tick,
+            None => self.ticks[index] = ComponentTicks::new(tick),
+        }
+        Ok(previous)
+    }
+
+    /// Clamps the change ticks of every slot relative to `this_run`.
+    pub fn check_change_ticks(&mut self, this_run: Tick) {
+        self.ticks.iter_mut().for_each(|ticks| ticks.check_ticks(this_run));
     }
 
     /// Removes the component at `index`, if present.
     pub fn remove(&mut self, index: usize) -> Option<T> {
         if index < N {
+            self.occupied.remove(index);
             self.slots[index].take()
         } else {
             None
@@ -64,7 +127,142 @@ impl<T, const N: usize> Column<T, N> {
     }
 }
 
-impl<T, const N: usize> Default for Column<T, N> {
+impl<T, const N: usize, const W: usize> Default for Column<T, N, W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A sparse-set component column: up to `N` components for any entity id
+/// below `E`.
+///
+/// `sparse[i]` maps entity `i` onto its position in the packed `dense` list;
+/// removal swaps the last dense entry into the hole, so the dense list never
+/// fragments. The sparse array costs two bytes per entity, regardless of `T`.
+/// `W` must be `words_for(E)`.
+pub struct SparseColumn<T, const N: usize, const E: usize, const W: usize> {
+    /// Dense position of each entity's component, or [`SparseColumn::EMPTY`].
+    sparse: [u16; E],
+    /// The packed `(entity index, component, change ticks)` entries.
+    dense: heapless::Vec<(u32, T, ComponentTicks), N>,
+    /// Bit `i` is set when entity `i` holds a component.
+    occupied: BitSet<W>,
+}
+
+impl<T, const N: usize, const E: usize, const W: usize> SparseColumn<T, N, E, W> {
+    /// Marks an entity without a dense entry.
+    const EMPTY: u16 = u16::MAX;
+
+    /// Creates a column holding no components.
+    pub const fn new() -> Self {
+        // Dense positions must fit the `u16` sparse entries (minus the marker).
+        const { assert!(N < u16::MAX as usize, "sparse column capacity must be below u16::MAX") };
+        const { assert!(W == words_for(E), "sparse column word count must be `words_for(E)`") };
+        Self {
+            sparse: [Self::EMPTY; E],
+            dense: heapless::Vec::new(),
+            occupied: BitSet::new(),
+        }
+    }
+
+    /// Returns the maximum number of components the column can hold.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of stored components.
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    /// Returns `true` when no component is stored.
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    /// Returns the dense position of entity `index`, if it holds a component.
+    fn position(&self, index: usize) -> Option<usize> {
+        self.sparse
+            .get(index)
+            .filter(|&&slot| slot != Self::EMPTY)
+            .map(|&slot| slot as usize)
+    }
+
+    /// Returns an immutable reference to the component of entity `index`, if
+    /// present.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.position(index).map(|pos| &self.dense[pos].1)
+    }
+
+    /// Returns a mutable reference to the component of entity `index`, if
+    /// present.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.position(index).map(|pos| &mut self.dense[pos].1)
+    }
+
+    /// Returns the component of entity `index` together with its change
+    /// ticks, if present.
+    pub fn get_mut_with_ticks(&mut self, index: usize) -> Option<(&mut T, &mut ComponentTicks)> {
+        let pos = self.position(index)?;
+        let (_, value, ticks) = &mut self.dense[pos];
+        Some((value, ticks))
+    }
+
+    /// Returns the change ticks of the component of entity `index`, if
+    /// present.
+    pub fn ticks(&self, index: usize) -> Option<ComponentTicks> {
+        self.position(index).map(|pos| self.dense[pos].2)
+    }
+
+    /// Inserts `value` for entity `index`, replacing any existing component and
+    /// returning the previous one.
+    ///
+    /// A new component is stamped as added at `tick`; a replaced one only as
+    /// changed. Hands `value` back as `Err` when `index >= E` or all `N`
+    /// entries are taken.
+    pub fn insert(&mut self, index: usize, value: T, tick: Tick) -> Result<Option<T>, T> {
+        // Replace in place when the entity already holds a component.
+        if let Some(pos) = self.position(index) {
+            let (_, slot, ticks) = &mut self.dense[pos];
+            ticks.changed = tick;
+            return Ok(Some(core::mem::replace(slot, value)));
+        }
+        if index >= E {
+            return Err(value);
+        }
+
+        // Otherwise append a dense entry and point the sparse slot at it.
+        let pos = self.dense.len();
+        self.dense
+            .push((index as u32, value, ComponentTicks::new(tick)))
+            .map_err(|(_, value, _)| value)?;
+        self.sparse[index] = pos as u16;
+        self.occupied.insert(index);
+        Ok(None)
+    }
+
+    /// Clamps the change ticks of every stored component relative to
+    /// `this_run`.
+    pub fn check_change_ticks(&mut self, this_run: Tick) {
+        self.dense.iter_mut().for_each(|(_, _, ticks)| ticks.check_ticks(this_run));
+    }
+
+    /// Removes the component of entity `index`, if present.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let pos = self.position(index)?;
+        self.sparse[index] = Self::EMPTY;
+        self.occupied.remove(index);
+
+        // Fill the hole with the last dense entry and re-point its sparse slot.
+        let (_, value, _) = self.dense.swap_remove(pos);
+        if let Some(&(moved, _, _)) = self.dense.get(pos) {
+            self.sparse[moved as usize] = pos as u16;
+        }
+        Some(value)
+    }
+}
+
+impl<T, const N: usize, const E: usize, const W: usize> Default for SparseColumn<T, N, E, W> {
     fn default() -> Self {
         Self::new()
     }
@@ -72,25 +270,114 @@ impl<T, const N: usize> Default for Column<T, N> {
 
 /// Object-safe operations shared by every component column, used so queries
 /// can hold a single `*const dyn ColumnOps<T>` regardless of the column's
-/// concrete capacity `N`.
+/// concrete layout and capacity.
 pub trait ColumnOps<T> {
-    /// Returns the column's capacity.
+    /// Returns the column's capacity (the maximum number of components).
     fn capacity(&self) -> usize;
+    /// Returns the exclusive upper bound of the entity ids the column can
+    /// address; queries iterate `0..index_bound()`.
+    fn index_bound(&self) -> usize;
+    /// Returns the number of stored components.
+    fn len(&self) -> usize;
+    /// Returns `true` when no component is stored.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Returns the occupancy bitset words: bit `i % 32` of word `i / 32` is set
+    /// when entity `i` holds a component.
+    fn occupancy(&self) -> &[u32];
+    /// Returns occupancy word `word`, or `0` past the end of the bitset.
+    fn occupancy_word(&self, word: usize) -> u32 {
+        self.occupancy().get(word).copied().unwrap_or(0)
+    }
+    /// Returns `true` when entity `index` holds a component.
+    fn contains(&self, index: usize) -> bool {
+        self.occupancy_word(index / WORD_BITS) & (1 << (index % WORD_BITS)) != 0
+    }
     /// Borrows the component at `index`, if present.
     fn get(&self, index: usize) -> Option<&T>;
-    /// Mutably borrows the component at `index`, if present.
+    /// Mutably borrows the component at `index`, if present, without marking
+    /// it as changed.
     fn get_mut(&mut self, index: usize) -> Option<&mut T>;
-    /// Inserts `value` at `index`, returning any previous component.
-    fn insert(&mut self, index: usize, value: T) -> Option<T>;
+    /// Mutably borrows the component at `index` together with its change
+    /// ticks, if present.
+    fn get_mut_with_ticks(&mut self, index: usize) -> Option<(&mut T, &mut ComponentTicks)>;
+    /// Returns the change ticks of the component at `index`, if present.
+    fn ticks(&self, index: usize) -> Option<ComponentTicks>;
+    /// Inserts `value` at `index` (stamped with `tick`), returning any
+    /// previous component, or handing `value` back as `Err` when the column
+    /// cannot hold it.
+    fn insert(&mut self, index: usize, value: T, tick: Tick) -> Result<Option<T>, T>;
     /// Removes the component at `index`, if present.
     fn remove(&mut self, index: usize) -> Option<T>;
+    /// Clamps every stored change tick relative to `this_run`, see
+    /// [`Tick::check_tick`].
+    fn check_change_ticks(&mut self, this_run: Tick);
+}
+
+impl<T, const N: usize, const W: usize> ColumnOps<T> for Column<T, N, W> {
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn index_bound(&self) -> usize {
+        N
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn occupancy(&self) -> &[u32] {
+        self.occupied.words()
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        self.get(index)
+    }
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.get_mut(index)
+    }
+
+    fn get_mut_with_ticks(&mut self, index: usize) -> Option<(&mut T, &mut ComponentTicks)> {
+        self.get_mut_with_ticks(index)
+    }
+
+    fn ticks(&self, index: usize) -> Option<ComponentTicks> {
+        self.ticks(index)
+    }
+
+    fn insert(&mut self, index: usize, value: T, tick: Tick) -> Result<Option<T>, T> {
+        self.insert(index, value, tick)
+    }
+
+    fn remove(&mut self, index: usize) -> Option<T> {
+        self.remove(index)
+    }
+
+    fn check_change_ticks(&mut self, this_run: Tick) {
+        self.check_change_ticks(this_run)
+    }
 }
 
-impl<T, const N: usize> ColumnOps<T> for Column<T, N> {
+impl<T, const N: usize, const E: usize, const W: usize> ColumnOps<T> for SparseColumn<T, N, E, W> {
     fn capacity(&self) -> usize {
         N
     }
 
+    fn index_bound(&self) -> usize {
+        E
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn occupancy(&self) -> &[u32] {
+        self.occupied.words()
+    }
+
     fn get(&self, index: usize) -> Option<&T> {
         self.get(index)
     }
@@ -99,11 +386,81 @@ impl<T, const N: usize> ColumnOps<T> for Column<T, N> {
         self.get_mut(index)
     }
 
-    fn insert(&mut self, index: usize, value: T) -> Option<T> {
-        self.insert(index, value)
+    fn get_mut_with_ticks(&mut self, index: usize) -> Option<(&mut T, &mut ComponentTicks)> {
+        self.get_mut_with_ticks(index)
+    }
+
+    fn ticks(&self, index: usize) -> Option<ComponentTicks> {
+        self.ticks(index)
+    }
+
+    fn insert(&mut self, index: usize, value: T, tick: Tick) -> Result<Option<T>, T> {
+        self.insert(index, value, tick)
     }
 
     fn remove(&mut self, index: usize) -> Option<T> {
         self.remove(index)
     }
-}
\ No newline at end of file
+
+    fn check_change_ticks(&mut self, this_run: Tick) {
+        self.check_change_ticks(this_run)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sparse column of three components over eight entity ids.
+    type Sparse = SparseColumn<u8, 3, 8, 1>;
+
+    #[test]
+    fn dense_insert_past_capacity_hands_the_value_back() {
+        let mut col = Column::<u8, 2, 1>::new();
+        assert_eq!(col.insert(1, 7, Tick::new(1)), Ok(None));
+        assert_eq!(col.insert(1, 8, Tick::new(2)), Ok(Some(7)));
+        assert_eq!(col.insert(2, 9, Tick::new(2)), Err(9));
+        assert_eq!(col.len(), 1);
+    }
+
+    #[test]
+    fn sparse_insert_into_a_full_column_hands_the_value_back() {
+        let mut col = Sparse::new();
+        for index in [7, 0, 4] {
+            assert_eq!(col.insert(index, index as u8, Tick::new(1)), Ok(None));
+        }
+        assert_eq!(col.insert(5, 5, Tick::new(1)), Err(5));
+        assert_eq!(col.insert(8, 8, Tick::new(1)), Err(8));
+
+        // Replacing an existing entry still works when the column is full.
+        assert_eq!(col.insert(0, 10, Tick::new(2)), Ok(Some(0)));
+        assert_eq!(col.get(5), None);
+        assert!(!ColumnOps::contains(&col, 5));
+    }
+
+    #[test]
+    fn sparse_remove_repoints_the_moved_entry() {
+        let mut col = Sparse::new();
+        for index in [7, 0, 4] {
+            col.insert(index, index as u8, Tick::new(index as u32)).expect("room");
+        }
+
+        // Removing the first dense entry swaps entity 4 into its place.
+        assert_eq!(col.remove(7), Some(7));
+        assert_eq!(col.get(7), None);
+        assert_eq!(col.get(4), Some(&4));
+        assert_eq!(col.ticks(4).map(|ticks| ticks.added), Some(Tick::new(4)));
+        assert_eq!(col.get(0), Some(&0));
+
+        // Removing the last dense entry moves nothing.
+        assert_eq!(col.remove(0), Some(0));
+        assert_eq!(col.get(4), Some(&4));
+        assert_eq!(col.remove(0), None);
+        assert_eq!(col.len(), 1);
+        assert_eq!(ColumnOps::occupancy(&col), &[1 << 4]);
+
+        // The freed entries are reusable.
+        assert_eq!(col.insert(6, 6, Tick::new(9)), Ok(None));
+        assert_eq!(col.insert(1, 1, Tick::new(9)), Ok(None));
+        assert_eq!(col.len(), 3);
+    }
+}
@@ -1,25 +1,26 @@
-//! Deferred and synchronous world mutations exposed to systems via
-//! [`Commands`].
+//! Deferred world mutations exposed to systems via [`Commands`].
 //!
 //! `Commands` carries a raw `*mut W` (set by the `#[system]` macro) plus the
 //! world's [`CommandBuffer`] for deferred despawns. Because the concrete
-//! `World` implements [`SpawnRef<T>`](crate::system::SpawnRef) and
-//! [`ResourceInsRef<R>`](crate::system::ResourceInsRef) per registered
-//! component/resource, `Commands::spawn::<T>(value)` and
-//! `insert_resource::<R>(value)` enqueue typed operations into *distinct*
-//! `World` fields — disjoint from any column a `Query` is currently borrowing
-//! immutably — so spawning during a read-only query system stays sound.
+//! `World` implements [`EntityCommandsRef<T>`](crate::system::EntityCommandsRef)
+//! and [`ResourceInsRef<R>`](crate::system::ResourceInsRef) per registered
+//! component/resource, `Commands::spawn(bundle)`, the [`EntityCommands`]
+//! inserts and removes, and `insert_resource::<R>(value)` enqueue typed
+//! operations into *distinct* `World` fields — disjoint from any column a `Query` is currently borrowing
+//! immutably, and from the resource slots — so spawning during a read-only
+//! query system stays sound.
 //! The pending values are drained into the columns by `World::flush_commands`,
 //! called between systems.
 
 use core::marker::PhantomData;
 
+use crate::bundle::Bundle;
 use crate::commands_buffer::{Command, CommandBuffer};
 use crate::entity::Entity;
-use crate::system::{ResourceInsRef, SpawnRef};
+use crate::system::{CommandsRef, EntityCommandsRef, ResourceInsRef};
 
 /// A handle systems use to spawn entities, insert resources, and defer
-/// despawns, mirroring Bevy's `Commands`.
+/// per-entity inserts, removes and despawns, mirroring Bevy's `Commands`.
 pub struct Commands<'a, W> {
     /// Raw pointer to the owning concrete `World`.
     world: *mut W,
@@ -43,46 +44,220 @@ impl<'a, W> Commands<'a, W> {
         }
     }
 
-    /// Enqueues a spawn of `value` of component type `T`, returning the new
-    /// entity id. The component is actually inserted into its column on the
-    /// next [`World::flush_commands`](crate::world::WorldApi::flush_commands).
-    pub fn spawn<T: 'static>(&mut self, value: T) -> Entity
+    /// Enqueues a spawn of `bundle` (a single component or a tuple of them),
+    /// returning the new entity id. Every component of the bundle is inserted
+    /// into its column on the same
+    /// [`World::flush_commands`](crate::world::WorldApi::flush_commands).
+    pub fn spawn<B: Bundle<W>>(&mut self, bundle: B) -> Entity
     where
-        W: SpawnRef<T>,
+        W: CommandsRef,
     {
-        // SAFETY: `world` is valid; `SpawnRef` mutates only the pending queue
-        // for `T`, which is disjoint from any column borrowed by this system.
-        unsafe { W::enqueue_spawn(self.world, value) }
+        // SAFETY: `world` is valid; reserving an id touches only the entity
+        // allocator, and the bundle mutates only the pending queues of its
+        // components, disjoint from any column borrowed by this system.
+        unsafe {
+            let entity = W::reserve_entity(self.world);
+            bundle.enqueue_insert(self.world, entity);
+            entity
+        }
     }
 
-    /// Synchronously inserts `value` as the resource `R`.
+    /// Queues the insertion of `value` as the resource `R`, applied on the
+    /// next [`World::flush_commands`](crate::world::WorldApi::flush_commands)
+    /// so any `Res<R>` borrowed by the running system stays valid. Of the
+    /// inserts and removals queued for `R` before a flush, the last wins.
     pub fn insert_resource<R: 'static>(&mut self, value: R)
     where
         W: ResourceInsRef<R>,
     {
-        // SAFETY: `world` is valid; `ResourceInsRef` writes only the `R` slot.
-        unsafe { W::insert_resource(self.world, value) }
+        // SAFETY: `world` is valid; `ResourceInsRef` writes only the pending
+        // slot of `R`, which no param borrows.
+        unsafe { W::enqueue_resource(self.world, Some(value)) }
     }
 
-    /// Queues the despawn of `entity`, mirrored by Bevy's
-    /// `commands.entity(e).despawn()`.
-    pub fn entity(&mut self, entity: Entity) -> EntityTarget {
-        self.buffer.push(Command::Despawn(entity));
-        EntityTarget { entity }
+    /// Queues the removal of the resource `R`, applied on the next
+    /// [`World::flush_commands`](crate::world::WorldApi::flush_commands) so any
+    /// `Res<R>` borrowed by the running system stays valid.
+    pub fn remove_resource<R: 'static>(&mut self)
+    where
+        W: ResourceInsRef<R>,
+    {
+        // SAFETY: as in `insert_resource`.
+        unsafe { W::enqueue_resource(self.world, None) }
+    }
+
+    /// Returns an [`EntityCommands`] builder for queuing inserts, removes or a
+    /// despawn on `entity`, mirroring Bevy's `commands.entity(e)`. Nothing is
+    /// queued until one of its methods is called.
+    pub fn entity(&mut self, entity: Entity) -> EntityCommands<'_, 'a, W> {
+        EntityCommands {
+            entity,
+            commands: self,
+        }
     }
 }
 
-/// A fluent handle returned by [`Commands::entity`] for chained despawns.
-#[derive(Debug, Clone, Copy)]
-pub struct EntityTarget {
+/// A fluent handle returned by [`Commands::entity`] for deferred operations on
+/// one entity. Every operation lands on the next
+/// [`World::flush_commands`](crate::world::WorldApi::flush_commands); a stale
+/// handle (its entity was already despawned) is ignored there.
+pub struct EntityCommands<'c, 'a, W> {
     /// The entity being targeted.
-    pub entity: Entity,
+    entity: Entity,
+    /// The `Commands` the operations are queued through.
+    commands: &'c mut Commands<'a, W>,
+}
+
+impl<W> EntityCommands<'_, '_, W> {
+    /// Returns the targeted entity.
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+
+    /// Queues inserting `bundle` (a single component or a tuple of them) on
+    /// the entity, replacing any previous values of the same types.
+    pub fn insert<B: Bundle<W>>(&mut self, bundle: B) -> &mut Self {
+        // SAFETY: `world` is valid; the bundle mutates only the pending
+        // queues of its components, disjoint from any borrowed column.
+        unsafe { bundle.enqueue_insert(self.commands.world, self.entity) };
+        self
+    }
+
+    /// Queues removing the component `C` from the entity, if present.
+    pub fn remove<C: 'static>(&mut self) -> &mut Self
+    where
+        W: EntityCommandsRef<C>,
+    {
+        // SAFETY: as in `insert`.
+        unsafe { W::enqueue_remove(self.commands.world, self.entity) };
+        self
+    }
+
+    /// Queues the despawn of the entity, removing it from every column.
+    pub fn despawn(&mut self) {
+        self.commands.buffer.push(Command::Despawn(self.entity));
+    }
 }
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Pos(u32);
+
+    #[derive(Component)]
+    struct Tag;
 
-impl EntityTarget {
-    /// Marks the entity for despawn (already queued by [`Commands::entity`]).
-    pub fn despawn(&self) {
-        // The despawn was already queued when `Commands::entity` was called;
-        // this exists to mirror Bevy's `commands.entity(e).despawn()` chain.
+    #[derive(Resource)]
+    struct Target(Entity);
+
+    define_world! {
+        struct World {
+            entities: 4,
+            schedules: 1,
+            components { pos: Pos [4], tag: Tag [4], }
+            resources { target: Target, }
+        }
+    }
+
+    #[system]
+    fn tag_untagged(mut commands: Commands, untagged: Query<Entity, (With<Pos>, Without<Tag>)>) {
+        for entity in untagged.iter() {
+            commands.entity(entity).insert((Tag, Pos(7)));
+        }
     }
-}
\ No newline at end of file
+
+    #[system]
+    fn untag_and_despawn_target(mut commands: Commands, tagged: Query<Entity, With<Tag>>, target: Res<Target>) {
+        for entity in tagged.iter() {
+            commands.entity(entity).remove::<Tag>();
+        }
+        commands.entity(target.0).despawn();
+    }
+
+    #[system]
+    fn retarget(mut commands: Commands, target: Res<Target>, tagged: Query<Entity, With<Tag>>) {
+        for entity in tagged.iter().filter(|&entity| entity != target.0) {
+            commands.insert_resource(Target(entity));
+        }
+    }
+
+    #[system]
+    fn retarget_then_drop(mut commands: Commands, mut target: ResMut<Target>) {
+        target.0 = Entity::new(0);
+        commands.insert_resource(Target(Entity::new(1)));
+        commands.remove_resource::<Target>();
+    }
+
+    #[test]
+    fn resource_commands_land_on_the_next_flush() {
+        let mut world = World::new();
+        let first = world.spawn(Pos(1)).expect("budget");
+        let tagged = world.spawn((Pos(2), Tag)).expect("budget");
+        world.insert_resource(Target(first));
+        world.add_system(Update, retarget);
+        world.run_schedule(Update);
+        // The borrowed `Res<Target>` stays untouched until the flush.
+        assert_eq!(world.get_resource::<Target>().map(|target| target.0), Some(first));
+
+        world.flush_commands();
+        assert_eq!(world.get_resource::<Target>().map(|target| target.0), Some(tagged));
+    }
+
+    #[test]
+    fn the_last_resource_command_wins() {
+        let mut world = World::new();
+        world.insert_resource(Target(Entity::new(2)));
+        world.add_system(Update, retarget_then_drop);
+        world.run_schedule(Update);
+        assert_eq!(world.get_resource::<Target>().map(|target| target.0), Some(Entity::new(0)));
+
+        world.flush_commands();
+        assert!(world.get_resource::<Target>().is_none());
+    }
+
+    #[test]
+    fn entity_commands_land_on_the_next_flush() {
+        let mut world = World::new();
+        let first = world.spawn(Pos(1)).expect("budget");
+        world.add_system(Update, tag_untagged);
+        world.run_schedule(Update);
+        assert_eq!(world.query_filtered::<&Pos, With<Tag>>().count(), 0);
+
+        world.flush_commands();
+        assert_eq!(world.query::<(&Pos, &Tag)>().get(first).map(|(pos, _)| pos), Ok(&Pos(7)));
+    }
+
+    #[system]
+    fn spawn_five(mut commands: Commands, _tagged: Query<(&Pos, &Tag)>) {
+        for i in 0..5 {
+            commands.spawn((Pos(i), Tag));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "pending queue of component `pos` has no room for entity")]
+    fn overflowing_a_pending_queue_panics() {
+        let mut world = World::new();
+        world.add_system(Update, spawn_five);
+        world.run_schedule(Update);
+    }
+
+    #[test]
+    fn entity_commands_skip_stale_handles() {
+        let mut world = World::new();
+        let stale = world.spawn(Pos(1)).expect("budget");
+        world.despawn(stale);
+        let recycled = world.spawn(Pos(2)).expect("budget");
+        world.spawn((Pos(3), Tag));
+        world.insert_resource(Target(stale));
+        world.add_system(Update, untag_and_despawn_target);
+        world.run_schedule(Update);
+        world.flush_commands();
+
+        // The despawn aimed at the stale handle spares the slot's new occupant.
+        assert!(world.is_alive(recycled));
+        assert_eq!(world.entity_count(), 2);
+        assert_eq!(world.query_filtered::<&Pos, With<Tag>>().count(), 0);
+    }
+}
@@ -1,4 +1,5 @@
-//! The deferred despawn queue held by the `World`.
+//! The deferred despawn queue held by the `World`, and the operations queued
+//! on its per-component pending queues.
 
 use heapless::Vec as HVec;
 
@@ -16,6 +17,40 @@ pub enum Command {
     Despawn(Entity),
 }
 
+/// A deferred operation on one component of one entity, queued per component
+/// type by `define_world!` and applied in order on flush.
+#[derive(Debug)]
+pub enum ComponentOp<T> {
+    /// Insert (or replace) the component on the entity.
+    Insert(T),
+    /// Insert the component built by the constructor, only if the entity
+    /// does not carry one yet (a required component).
+    Require(fn() -> T),
+    /// Remove the component from the entity, if present.
+    Remove,
+}
+
+/// Queues `op` on `entity` in the pending queue of component `component`.
+///
+/// # Panics
+/// When the queue is full: it is sized to the component's column, so more
+/// operations between two flushes are a sizing bug in `define_world!`, and
+/// dropping one would land a bundle or a removal only partly.
+pub fn enqueue<T, const N: usize>(
+    queue: &mut HVec<(Entity, ComponentOp<T>), N>,
+    entity: Entity,
+    op: ComponentOp<T>,
+    component: &str,
+) {
+    if queue.push((entity, op)).is_err() {
+        panic!(
+            "pending queue of component `{}` has no room for entity {}; raise its capacity in `define_world!`",
+            component,
+            entity.index()
+        );
+    }
+}
+
 /// A bounded queue of pending [`Command`]s awaiting flush.
 #[derive(Default)]
 pub struct CommandBuffer {
@@ -38,7 +73,8 @@ impl CommandBuffer {
 
     /// Drains the buffer, yielding pending commands.
     ///
-    /// Order is LIFO; despawns are independent of one another so this is safe.
+    /// Order is LIFO; despawns are independent of one another so this is
+    /// safe.
     pub fn drain(&mut self) -> Drain<'_> {
         Drain { buf: self }
     }
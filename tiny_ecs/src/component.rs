@@ -0,0 +1,115 @@
+//! Per-component metadata generated by `#[derive(Component)]`.
+//!
+//! Required components are declared on the derive —
+//! `#[component(requires(MoveCooldown = default_cooldown))]` — and resolved
+//! while `World::flush_commands` applies the pending queues: whenever a
+//! component lands on an entity, each of its required components that the
+//! entity still lacks is queued with its constructor, within the same flush.
+//!
+//! Lifecycle hooks are declared the same way —
+//! `#[component(on_add = .., on_insert = .., on_remove = ..)]` naming
+//! `#[system]`s — and run ahead of the observers of the matching
+//! [`observer`](crate::observer) event.
+
+use crate::entity::Entity;
+use crate::system::System;
+
+/// The components that must accompany `Self` on every entity of world `W`,
+/// mirroring Bevy's required components.
+///
+/// `#[derive(Component)]` always implements this trait (with no requirements
+/// unless `#[component(requires(..))]` is given), and `define_world!` needs it
+/// for every registered component, so a hand-written `Component` impl must
+/// provide an (empty) impl too. The derived impl bounds `W` by a registered
+/// column for each required component, so a missing one fails to compile.
+pub trait RequiredComponents<W> {
+    /// Queues every required component on `entity`, to be inserted only if
+    /// the entity does not already carry it.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut W`.
+    unsafe fn enqueue_required(world: *mut W, entity: Entity) {
+        let _ = (world, entity);
+    }
+}
+
+/// The lifecycle hooks of `Self` in world `W`, mirroring Bevy's component
+/// hooks. Each returns the `#[system]` to run for its event, if any.
+///
+/// Like [`RequiredComponents`], always implemented by `#[derive(Component)]`
+/// and needed by `define_world!` for every registered component.
+pub trait ComponentHooks<W> {
+    /// The system run when `Self` lands on an entity that lacked it.
+    fn on_add() -> Option<System> {
+        None
+    }
+
+    /// The system run on every insert of `Self`, after `on_add`.
+    fn on_insert() -> Option<System> {
+        None
+    }
+
+    /// The system run just before `Self` is removed from an entity.
+    fn on_remove() -> Option<System> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    #[component(requires(Cooldown = short_cooldown, Health))]
+    struct Player;
+
+    #[derive(Component, Debug, PartialEq)]
+    #[component(requires(Armor))]
+    struct Health(u8);
+
+    impl Default for Health {
+        fn default() -> Self {
+            Self(3)
+        }
+    }
+
+    #[derive(Component, Debug, Default, PartialEq)]
+    struct Armor(u8);
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Cooldown(u8);
+
+    fn short_cooldown() -> Cooldown {
+        Cooldown(2)
+    }
+
+    define_world! {
+        struct World {
+            entities: 2,
+            schedules: 1,
+            components { player: Player [2], health: Health [2], armor: Armor [2], cooldown: Cooldown [2], }
+            resources {}
+        }
+    }
+
+    #[test]
+    fn required_components_land_in_the_same_flush() {
+        let mut world = World::new();
+        let player = world.spawn(Player).expect("budget");
+        let query = world.query::<(&Cooldown, &Health, &Armor)>();
+        assert_eq!(query.get(player), Ok((&Cooldown(2), &Health(3), &Armor(0))));
+    }
+
+    #[test]
+    fn explicit_components_win_over_required_ones() {
+        let mut world = World::new();
+        let player = world.spawn((Player, Health(9))).expect("budget");
+        assert_eq!(world.query::<&Health>().get(player), Ok(&Health(9)));
+
+        // Re-inserting the component does not reset what it required.
+        world.set_component(player, Cooldown(5));
+        world.set_component(player, Player);
+        world.flush_commands();
+        assert_eq!(world.query::<&Cooldown>().get(player), Ok(&Cooldown(5)));
+    }
+}
@@ -1,30 +1,72 @@
 //! Lightweight entity identifiers.
 //!
-//! An `Entity` is just a `u32` index into the `World`'s columns. There is no
-//! generation counter: holding an `Entity` after it has been despawned and the
-//! index recycled is documented undefined behaviour, traded for the storage
-//! savings of skipping per-entity metadata (acceptable for a micro:bit game
-//! that despawns only at well-defined frame boundaries).
+//! An `Entity` is a `u32` index into the `World`'s columns paired with the
+//! generation of that slot. Every despawn bumps the slot's generation, so a
+//! handle kept across a despawn (e.g. inside a resource) no longer matches the
+//! recycled slot and is rejected by `despawn`, `World::is_alive` and the
+//! deferred `Commands` instead of silently aliasing the new occupant.
+//!
+//! Worlds declared with `generations: false` in `define_world!` skip the
+//! per-slot counters to save RAM; their handles always carry generation `0`, so
+//! only handles to currently free slots are detected as stale. Disabling the
+//! default `generations` cargo feature also drops the field from `Entity`
+//! itself, halving every stored handle to 4 bytes; every world must then be
+//! declared with `generations: false`.
+
+/// Whether `Entity` carries a generation, i.e. the `generations` feature is on.
+///
+/// `define_world!` checks it at compile time for generational worlds.
+pub const GENERATIONAL: bool = cfg!(feature = "generations");
 
 /// A handle to an entity in the world.
 ///
-/// Obtained from `World::spawn` / `Commands::spawn`. The wrapped `u32` is the
-/// entity's index into every component column; it is `Copy` and `Eq`/`Hash` so
+/// Obtained from `World::spawn` / `Commands::spawn`. The index addresses the
+/// entity's slot in every component column, the generation identifies which
+/// occupant of that slot the handle refers to. It is `Copy` and `Eq`/`Hash` so
 /// it can be used as a map key or compared directly.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Entity(
-    /// The raw index.
-    u32,
-);
+pub struct Entity {
+    /// The raw slot index.
+    index: u32,
+    /// The slot generation this handle was issued for.
+    #[cfg(feature = "generations")]
+    generation: u32,
+}
 
 impl Entity {
-    /// Creates an entity from a raw index.
+    /// Creates an entity from a raw index, at generation `0`.
     pub const fn new(index: u32) -> Self {
-        Self(index)
+        Self::from_raw_parts(index, 0)
+    }
+
+    /// Creates an entity from a raw index and slot generation.
+    ///
+    /// Without the `generations` feature the generation is discarded.
+    pub const fn from_raw_parts(index: u32, generation: u32) -> Self {
+        #[cfg(not(feature = "generations"))]
+        let _ = generation;
+        Self {
+            index,
+            #[cfg(feature = "generations")]
+            generation,
+        }
     }
 
     /// Returns the raw index.
     pub const fn index(self) -> u32 {
-        self.0
+        self.index
+    }
+
+    /// Returns the slot generation the handle was issued for.
+    #[cfg(feature = "generations")]
+    pub const fn generation(self) -> u32 {
+        self.generation
+    }
+
+    /// Returns the slot generation, always `0` without the `generations`
+    /// feature.
+    #[cfg(not(feature = "generations"))]
+    pub const fn generation(self) -> u32 {
+        0
     }
 }
\ No newline at end of file
@@ -0,0 +1,126 @@
+//! Fallible systems and their error handlers, mirroring `bevy_ecs::error`.
+//!
+//! A `#[system]` may return `Result<(), E>` for any `E: SystemError`. When it
+//! returns `Err`, the world passes a [`SystemFailed`] naming the system to the
+//! handler installed with `App::set_error_handler`. This module provides the
+//! stock handlers: [`panic`] (the default), [`ignore`], and [`count`], which
+//! tallies failures in the [`SystemErrors`] resource; `bevy_microbit` adds one
+//! logging over RTT.
+
+use core::fmt::Debug;
+
+use crate::system::HasResource;
+use crate::world::WorldApi;
+use crate::Resource;
+
+/// An error a system can return.
+///
+/// Implemented for every `Debug` type, so a system can `?` a
+/// `QuerySingleError` or return its own error enum.
+pub trait SystemError: Debug {}
+
+impl<T: Debug> SystemError for T {}
+
+/// The report handed to the error handler when a system fails.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemFailed<'a> {
+    /// The system function's name.
+    pub system: &'static str,
+    /// The error the system returned.
+    pub error: &'a dyn SystemError,
+}
+
+impl<'a> SystemFailed<'a> {
+    /// Creates the report for `system` failing with `error`.
+    pub fn new(system: &'static str, error: &'a dyn SystemError) -> Self {
+        Self { system, error }
+    }
+}
+
+// =====================================================================
+// --- Handlers ---------------------------------------------------------
+
+/// Panics with the failure; the handler used when none is installed.
+pub fn panic<W>(_world: &mut W, failed: SystemFailed<'_>) {
+    panic!("system {} failed: {:?}", failed.system, failed.error);
+}
+
+/// Discards the failure.
+pub fn ignore<W>(_world: &mut W, _failed: SystemFailed<'_>) {}
+
+/// Tallies system failures, updated by the [`count`] handler.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SystemErrors {
+    /// The number of failures so far.
+    pub count: u32,
+    /// The name of the system that failed last.
+    pub last: Option<&'static str>,
+}
+
+impl Resource for SystemErrors {}
+
+/// Records the failure in the [`SystemErrors`] resource, inserting it on the
+/// first failure.
+pub fn count<W: WorldApi + HasResource<SystemErrors>>(world: &mut W, failed: SystemFailed<'_>) {
+    world.init_resource::<SystemErrors>();
+    if let Some(mut errors) = world.get_resource_mut::<SystemErrors>() {
+        errors.count = errors.count.saturating_add(1);
+        errors.last = Some(failed.system);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Player;
+
+    define_world! {
+        struct World {
+            entities: 2,
+            schedules: 1,
+            components { player: Player [2], }
+            resources { errors: SystemErrors, }
+        }
+    }
+
+    /// Fails unless exactly one player exists.
+    #[system]
+    fn find_player(players: Query<Entity, With<Player>>) -> Result<(), QuerySingleError> {
+        players.single()?;
+        Ok(())
+    }
+
+    #[derive(Debug)]
+    struct Corrupt;
+
+    #[system]
+    fn load(_world: &mut World) -> Result<(), Corrupt> {
+        Err(Corrupt)
+    }
+
+    #[test]
+    fn the_count_handler_tallies_failures() {
+        let mut world = World::new();
+        world.set_error_handler(crate::error::count);
+        world.add_systems(Update, (find_player, load));
+        world.run_schedule(Update);
+        let errors = world.get_resource::<SystemErrors>().copied().expect("inserted");
+        assert_eq!((errors.count, errors.last), (2, Some("load")));
+
+        // A system that succeeds reports nothing.
+        world.spawn(Player).expect("budget");
+        world.add_system(Update, find_player);
+        world.run_schedule(Update);
+        assert_eq!(world.get_resource::<SystemErrors>().map(|errors| errors.count), Some(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "system find_player failed: NoEntities")]
+    fn failures_panic_without_a_handler() {
+        let mut world = World::new();
+        world.add_system(Update, find_player);
+        world.run_schedule(Update);
+    }
+}
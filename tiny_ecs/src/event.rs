@@ -0,0 +1,290 @@
+//! Bounded, double-buffered typed events, mirroring `bevy_ecs::event`.
+//!
+//! Each event type is registered in the `events { ... }` section of
+//! `define_world!` with a per-buffer capacity, e.g.
+//! `crashed: Crashed [4, overflow: DropNewest]`. The `World` keeps one
+//! [`Events`] per type: systems append to its *current* buffer through
+//! [`EventWriter`], and `World::update_events` (called once per frame by the
+//! runner) retires the current buffer to *previous* and starts a fresh one. An
+//! event therefore stays readable until the end of the frame after it was
+//! sent, so a reader running before its writer in the frame still sees it.
+//!
+//! Every event is numbered on arrival. [`EventReader`] keeps the id of the
+//! next event it has not read in a per-system cursor (kept in the world's
+//! [`SystemLocals`](crate::system::SystemLocals)), so several readers
+//! consume the same events independently.
+
+use heapless::Deque;
+
+/// What [`Events::send`] does when the current buffer is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Evict the oldest event of the current buffer to make room (default).
+    #[default]
+    DropOldest,
+    /// Discard the event being sent.
+    DropNewest,
+}
+
+/// The two event buffers of one event type `E`, `N` events each.
+pub struct Events<E, const N: usize> {
+    /// Events sent during the previous frame.
+    previous: Deque<E, N>,
+    /// The id of the first event in `previous`.
+    previous_start: usize,
+    /// Events sent during the current frame.
+    current: Deque<E, N>,
+    /// The id of the first event in `current`.
+    current_start: usize,
+    /// The policy for sending into a full `current` buffer.
+    overflow: Overflow,
+    /// The number of events lost to overflow so far.
+    dropped: usize,
+}
+
+impl<E, const N: usize> Events<E, N> {
+    /// Creates empty buffers using the given `overflow` policy.
+    pub const fn new(overflow: Overflow) -> Self {
+        Self {
+            previous: Deque::new(),
+            previous_start: 0,
+            current: Deque::new(),
+            current_start: 0,
+            overflow,
+            dropped: 0,
+        }
+    }
+
+    /// Returns the number of events lost to overflow so far.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+}
+
+/// Object-safe access to [`Events`] of any capacity.
+pub trait EventsOps<E> {
+    /// Appends `event` to the current buffer, applying the overflow policy
+    /// when it is full.
+    fn send(&mut self, event: E);
+
+    /// Retires the current buffer to previous, dropping the events sent two
+    /// frames ago.
+    fn update(&mut self);
+
+    /// Returns the id of the oldest event still buffered.
+    fn oldest_id(&self) -> usize;
+
+    /// Returns the id the next sent event will get.
+    fn next_id(&self) -> usize;
+
+    /// Returns the event with id `id`, if it is still buffered.
+    fn get(&self, id: usize) -> Option<&E>;
+}
+
+impl<E, const N: usize> EventsOps<E> for Events<E, N> {
+    fn send(&mut self, event: E) {
+        if self.current.is_full() {
+            self.dropped += 1;
+            match self.overflow {
+                Overflow::DropOldest => {
+                    self.current.pop_front();
+                    self.current_start += 1;
+                }
+                Overflow::DropNewest => return,
+            }
+        }
+        let _ = self.current.push_back(event);
+    }
+
+    fn update(&mut self) {
+        self.previous = core::mem::take(&mut self.current);
+        self.previous_start = self.current_start;
+        self.current_start = self.previous_start + self.previous.len();
+    }
+
+    fn oldest_id(&self) -> usize {
+        self.previous_start
+    }
+
+    fn next_id(&self) -> usize {
+        self.current_start + self.current.len()
+    }
+
+    fn get(&self, id: usize) -> Option<&E> {
+        // Ids run contiguously through `previous`, then `current`, except for
+        // the gap `DropOldest` opens at the front of `current`.
+        if id >= self.current_start {
+            self.current.iter().nth(id - self.current_start)
+        } else if id >= self.previous_start {
+            self.previous.iter().nth(id - self.previous_start)
+        } else {
+            None
+        }
+    }
+}
+
+// =====================================================================
+// --- System params ----------------------------------------------------
+
+/// Sends events of type `E`, mirroring Bevy's `EventWriter`.
+pub struct EventWriter<'w, E> {
+    /// The world's buffers for `E`.
+    events: &'w mut dyn EventsOps<E>,
+}
+
+impl<'w, E> EventWriter<'w, E> {
+    /// Wraps the world's buffers for `E`.
+    pub fn new(events: &'w mut dyn EventsOps<E>) -> Self {
+        Self { events }
+    }
+
+    /// Sends `event` to every reader.
+    pub fn send(&mut self, event: E) {
+        self.events.send(event);
+    }
+
+    /// Sends every event of `events`, in order.
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = E>) {
+        for event in events {
+            self.events.send(event);
+        }
+    }
+}
+
+/// Reads the events of type `E` the system has not seen yet, mirroring
+/// Bevy's `EventReader`.
+pub struct EventReader<'w, 's, E> {
+    /// The world's buffers for `E`.
+    events: &'w dyn EventsOps<E>,
+    /// The id of the next unread event, kept across runs of the system.
+    cursor: &'s mut usize,
+}
+
+impl<'w, 's, E> EventReader<'w, 's, E> {
+    /// Wraps the world's buffers for `E` and the system's `cursor`.
+    pub fn new(events: &'w dyn EventsOps<E>, cursor: &'s mut usize) -> Self {
+        Self { events, cursor }
+    }
+
+    /// Iterates the unread events, oldest first, marking each as read as it
+    /// is yielded. Events evicted before the system ran are skipped.
+    pub fn read(&mut self) -> EventIter<'_, E> {
+        let next = (*self.cursor).max(self.events.oldest_id());
+        EventIter {
+            events: self.events,
+            cursor: self.cursor,
+            next,
+        }
+    }
+
+    /// Returns the number of unread events.
+    pub fn len(&self) -> usize {
+        let start = (*self.cursor).max(self.events.oldest_id());
+        (start..self.events.next_id())
+            .filter(|&id| self.events.get(id).is_some())
+            .count()
+    }
+
+    /// Returns `true` when there is no unread event.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Marks every buffered event as read without reading it.
+    pub fn clear(&mut self) {
+        *self.cursor = self.events.next_id();
+    }
+}
+
+/// Iterator over the unread events of an [`EventReader`].
+pub struct EventIter<'r, E> {
+    /// The world's buffers.
+    events: &'r dyn EventsOps<E>,
+    /// The reader's cursor, advanced past every yielded event.
+    cursor: &'r mut usize,
+    /// The id of the next event to inspect.
+    next: usize,
+}
+
+impl<'r, E> Iterator for EventIter<'r, E> {
+    type Item = &'r E;
+    fn next(&mut self) -> Option<&'r E> {
+        while self.next < self.events.next_id() {
+            let id = self.next;
+            self.next += 1;
+            *self.cursor = self.next;
+            if let Some(event) = self.events.get(id) {
+                return Some(event);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads every unread event through a reader with `cursor`.
+    fn read_all(events: &Events<u8, 2>, cursor: &mut usize) -> heapless::Vec<u8, 4> {
+        EventReader::new(events, cursor).read().copied().collect()
+    }
+
+    #[test]
+    fn events_outlive_one_update() {
+        let mut events = Events::<u8, 2>::new(Overflow::DropOldest);
+        let (mut early, mut late) = (0, 0);
+        events.send(1);
+        assert_eq!(read_all(&events, &mut early), [1]);
+
+        events.update();
+        events.send(2);
+        assert_eq!(read_all(&events, &mut early), [2]);
+        assert_eq!(read_all(&events, &mut late), [1, 2]);
+
+        // Two updates retire an event for good.
+        events.update();
+        events.update();
+        assert_eq!(read_all(&events, &mut late), []);
+        assert_eq!(events.oldest_id(), events.next_id());
+    }
+
+    #[test]
+    fn drop_oldest_leaves_an_id_gap_readers_skip() {
+        let mut events = Events::<u8, 2>::new(Overflow::DropOldest);
+        let mut cursor = 0;
+        events.send(1);
+        events.send(2);
+        events.update();
+        events.send(3);
+        events.send(4);
+        events.send(5);
+
+        // Id 2 (event 3) was evicted from `current`, between the buffers.
+        assert_eq!(events.dropped(), 1);
+        assert_eq!(events.get(2), None);
+        let mut reader = EventReader::new(&events, &mut cursor);
+        assert_eq!(reader.len(), 4);
+        assert!(reader.read().copied().eq([1, 2, 4, 5]));
+        assert_eq!(cursor, events.next_id());
+    }
+
+    #[test]
+    fn drop_newest_keeps_the_buffered_events() {
+        let mut events = Events::<u8, 2>::new(Overflow::DropNewest);
+        let mut cursor = 0;
+        events.send(1);
+        events.send(2);
+        events.send(3);
+        assert_eq!(events.dropped(), 1);
+        assert_eq!(read_all(&events, &mut cursor), [1, 2]);
+
+        // Nothing new means nothing unread.
+        let mut reader = EventReader::new(&events, &mut cursor);
+        assert!(reader.is_empty());
+        events.send(4);
+        reader = EventReader::new(&events, &mut cursor);
+        reader.clear();
+        assert!(read_all(&events, &mut cursor).is_empty());
+    }
+}
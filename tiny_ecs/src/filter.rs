@@ -0,0 +1,299 @@
+//! Query filters: [`With`], [`Without`], [`Added`], [`Changed`] and [`Or`],
+//! mirroring Bevy's `Query<D, F>` filter parameter.
+//!
+//! A filter narrows down which entities a [`Query`](crate::system::Query)
+//! yields without borrowing any component data: it only reads the occupancy
+//! bitsets and change ticks of the columns it names. Tuples of filters require
+//! every member to pass, `Or<(..)>` requires at least one, and `()` (the
+//! default) lets every entity through.
+//!
+//! ```ignore
+//! #[system]
+//! fn draw(debris: Query<&Debris, Without<Player>>) { /* ... */ }
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::change_detection::{ComponentTicks, SystemTicks};
+use crate::column::ColumnOps;
+use crate::system::{column_word, ChangeTicksRef, ColumnRef};
+
+/// How a [`Query`](crate::system::Query) decides which entities pass its filter
+/// parameter.
+///
+/// # Safety
+/// Implementors must only read column occupancy through raw pointers derived
+/// from the `*mut W` handed to [`QueryFilter::make_state`], never component
+/// data, so a filter can name a column another parameter borrows mutably.
+pub unsafe trait QueryFilter<W> {
+    /// The state captured at construction time (column pointers etc.).
+    type State;
+
+    /// Builds the state from a raw world pointer.
+    ///
+    /// # Safety
+    /// `world` must point to a valid `W` for as long as the state is used.
+    unsafe fn make_state(world: *mut W) -> Self::State;
+
+    /// Whether [`QueryFilter::word`] is exact, i.e. every set bit passes and
+    /// [`QueryFilter::matches`] never has to be consulted. Holds for every
+    /// occupancy-only filter, so counting stays a popcount per word.
+    const WORD_EXACT: bool = true;
+
+    /// Returns a mask of occupancy word `word` that is a superset of the
+    /// entities passing the filter; iteration skips every cleared bit.
+    fn word(state: &Self::State, word: usize) -> u32;
+
+    /// Returns `true` when entity `index` passes the filter.
+    fn matches(state: &Self::State, index: usize) -> bool;
+}
+
+/// Returns `true` when the column behind `col` holds a component for `index`.
+fn occupies<T>(col: *const dyn ColumnOps<T>, index: usize) -> bool {
+    // SAFETY: filter states only hold column pointers valid for their use.
+    let col: &dyn ColumnOps<T> = unsafe { &*col };
+    col.contains(index)
+}
+
+/// Returns the change ticks of entity `index`'s component in `col`, if any.
+fn ticks_of<T>(col: *const dyn ColumnOps<T>, index: usize) -> Option<ComponentTicks> {
+    // SAFETY: filter states only hold column pointers valid for their use.
+    let col: &dyn ColumnOps<T> = unsafe { &*col };
+    col.ticks(index)
+}
+
+// =====================================================================
+// --- With / Without ---------------------------------------------------
+
+/// Passes entities that have a `T` component, without fetching it.
+pub struct With<T>(PhantomData<T>);
+
+unsafe impl<T: 'static, W: ColumnRef<T>> QueryFilter<W> for With<T> {
+    type State = *const dyn ColumnOps<T>;
+
+    unsafe fn make_state(world: *mut W) -> Self::State {
+        // SAFETY: caller guarantees `world` is valid.
+        unsafe { <W as ColumnRef<T>>::col_ref_raw(world) }
+    }
+
+    fn word(state: &Self::State, word: usize) -> u32 {
+        column_word(*state, word)
+    }
+
+    fn matches(state: &Self::State, index: usize) -> bool {
+        occupies(*state, index)
+    }
+}
+
+/// Passes entities that do not have a `T` component.
+pub struct Without<T>(PhantomData<T>);
+
+unsafe impl<T: 'static, W: ColumnRef<T>> QueryFilter<W> for Without<T> {
+    type State = *const dyn ColumnOps<T>;
+
+    unsafe fn make_state(world: *mut W) -> Self::State {
+        // SAFETY: caller guarantees `world` is valid.
+        unsafe { <W as ColumnRef<T>>::col_ref_raw(world) }
+    }
+
+    fn word(state: &Self::State, word: usize) -> u32 {
+        !column_word(*state, word)
+    }
+
+    fn matches(state: &Self::State, index: usize) -> bool {
+        !occupies(*state, index)
+    }
+}
+
+// =====================================================================
+// --- Added / Changed --------------------------------------------------
+
+/// Passes entities whose `T` was inserted since the system last ran.
+pub struct Added<T>(PhantomData<T>);
+
+unsafe impl<T: 'static, W: ColumnRef<T> + ChangeTicksRef> QueryFilter<W> for Added<T> {
+    type State = (*const dyn ColumnOps<T>, SystemTicks);
+    // The occupancy word only narrows down the candidates; the ticks decide.
+    const WORD_EXACT: bool = false;
+
+    unsafe fn make_state(world: *mut W) -> Self::State {
+        // SAFETY: caller guarantees `world` is valid.
+        unsafe { (<W as ColumnRef<T>>::col_ref_raw(world), W::system_ticks(world)) }
+    }
+
+    fn word(state: &Self::State, word: usize) -> u32 {
+        column_word(state.0, word)
+    }
+
+    fn matches(state: &Self::State, index: usize) -> bool {
+        let (col, system) = *state;
+        ticks_of(col, index).is_some_and(|ticks| ticks.is_added(system.last_run, system.this_run))
+    }
+}
+
+/// Passes entities whose `T` was inserted or mutated since the system last
+/// ran.
+pub struct Changed<T>(PhantomData<T>);
+
+unsafe impl<T: 'static, W: ColumnRef<T> + ChangeTicksRef> QueryFilter<W> for Changed<T> {
+    type State = (*const dyn ColumnOps<T>, SystemTicks);
+    const WORD_EXACT: bool = false;
+
+    unsafe fn make_state(world: *mut W) -> Self::State {
+        // SAFETY: caller guarantees `world` is valid.
+        unsafe { (<W as ColumnRef<T>>::col_ref_raw(world), W::system_ticks(world)) }
+    }
+
+    fn word(state: &Self::State, word: usize) -> u32 {
+        column_word(state.0, word)
+    }
+
+    fn matches(state: &Self::State, index: usize) -> bool {
+        let (col, system) = *state;
+        ticks_of(col, index).is_some_and(|ticks| ticks.is_changed(system.last_run, system.this_run))
+    }
+}
+
+// =====================================================================
+// --- Combinators ------------------------------------------------------
+
+/// Passes entities that pass at least one filter of the tuple `F`, e.g.
+/// `Or<(With<Player>, With<Debris>)>`.
+pub struct Or<F>(PhantomData<F>);
+
+// `()` — no filter, every entity passes.
+unsafe impl<W> QueryFilter<W> for () {
+    type State = ();
+
+    unsafe fn make_state(_world: *mut W) -> Self::State {}
+
+    fn word(_state: &Self::State, _word: usize) -> u32 {
+        u32::MAX
+    }
+
+    fn matches(_state: &Self::State, _index: usize) -> bool {
+        true
+    }
+}
+
+/// Implements [`QueryFilter`] for a tuple (every member must pass) and for
+/// [`Or`] over the same tuple (any member may pass).
+macro_rules! impl_filter_tuple {
+    ($($f:ident),+) => {
+        unsafe impl<W, $($f: QueryFilter<W>),+> QueryFilter<W> for ($($f,)+) {
+            type State = ($($f::State,)+);
+            const WORD_EXACT: bool = true $(&& $f::WORD_EXACT)+;
+
+            unsafe fn make_state(world: *mut W) -> Self::State {
+                // SAFETY: caller guarantees `world` is valid.
+                unsafe { ($($f::make_state(world),)+) }
+            }
+
+            #[allow(non_snake_case)]
+            fn word(state: &Self::State, word: usize) -> u32 {
+                let ($($f,)+) = state;
+                u32::MAX $(& $f::word($f, word))+
+            }
+
+            #[allow(non_snake_case)]
+            fn matches(state: &Self::State, index: usize) -> bool {
+                let ($($f,)+) = state;
+                true $(&& $f::matches($f, index))+
+            }
+        }
+
+        unsafe impl<W, $($f: QueryFilter<W>),+> QueryFilter<W> for Or<($($f,)+)> {
+            type State = ($($f::State,)+);
+            const WORD_EXACT: bool = true $(&& $f::WORD_EXACT)+;
+
+            unsafe fn make_state(world: *mut W) -> Self::State {
+                // SAFETY: caller guarantees `world` is valid.
+                unsafe { ($($f::make_state(world),)+) }
+            }
+
+            #[allow(non_snake_case)]
+            fn word(state: &Self::State, word: usize) -> u32 {
+                let ($($f,)+) = state;
+                0 $(| $f::word($f, word))+
+            }
+
+            #[allow(non_snake_case)]
+            fn matches(state: &Self::State, index: usize) -> bool {
+                let ($($f,)+) = state;
+                false $(|| $f::matches($f, index))+
+            }
+        }
+    };
+}
+
+impl_filter_tuple!(A);
+impl_filter_tuple!(A, B);
+impl_filter_tuple!(A, B, C);
+impl_filter_tuple!(A, B, C, D);
+impl_filter_tuple!(A, B, C, D, E);
+impl_filter_tuple!(A, B, C, D, E, F);
+impl_filter_tuple!(A, B, C, D, E, F, G);
+impl_filter_tuple!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct Id(u32);
+
+    #[derive(Component)]
+    struct Red;
+
+    #[derive(Component)]
+    struct Blue;
+
+    define_world! {
+        struct World {
+            entities: 8,
+            schedules: 1,
+            components { id: Id [8], red: Red [8], blue: Blue [2, sparse], }
+            resources {}
+        }
+    }
+
+    /// Spawns ids 0..4: 0 plain, 1 red, 2 blue, 3 red and blue.
+    fn populated() -> World {
+        let mut world = World::new();
+        world.spawn(Id(0));
+        world.spawn((Id(1), Red));
+        world.spawn((Id(2), Blue));
+        world.spawn((Id(3), Red, Blue));
+        world
+    }
+
+    /// Collects the ids passing the filter `F`, as a bitmask.
+    fn ids<F: QueryFilter<World>>(world: &mut World) -> u32 {
+        world.query_filtered::<&Id, F>().iter().fold(0, |ids, id| ids | 1 << id.0)
+    }
+
+    #[test]
+    fn with_and_without_read_occupancy() {
+        let mut world = populated();
+        assert_eq!(ids::<With<Red>>(&mut world), 0b1010);
+        assert_eq!(ids::<Without<Red>>(&mut world), 0b0101);
+        assert_eq!(ids::<With<Blue>>(&mut world), 0b1100);
+        assert_eq!(ids::<Without<Blue>>(&mut world), 0b0011);
+    }
+
+    #[test]
+    fn tuples_require_every_member() {
+        let mut world = populated();
+        assert_eq!(ids::<(With<Red>, With<Blue>)>(&mut world), 0b1000);
+        assert_eq!(ids::<(With<Red>, Without<Blue>)>(&mut world), 0b0010);
+        assert_eq!(ids::<()>(&mut world), 0b1111);
+    }
+
+    #[test]
+    fn or_requires_any_member() {
+        let mut world = populated();
+        assert_eq!(ids::<Or<(With<Red>, With<Blue>)>>(&mut world), 0b1110);
+        assert_eq!(ids::<Or<(Without<Red>, With<Blue>)>>(&mut world), 0b1101);
+        assert_eq!(world.query_filtered::<&Id, Or<(With<Red>, With<Blue>)>>().count(), 3);
+    }
+}
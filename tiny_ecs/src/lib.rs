@@ -39,30 +39,56 @@
 #![warn(missing_docs)]
 #![forbid(unsafe_op_in_unsafe_fn)]
 
+// Lets the macros' `::tiny_ecs` paths resolve inside this crate's own tests.
+#[cfg(test)]
+extern crate self as tiny_ecs;
+
 pub mod app;
+pub mod bitset;
+pub mod bundle;
+pub mod change_detection;
 pub mod column;
 pub mod commands;
 pub mod commands_buffer;
+pub mod component;
 pub mod entity;
+pub mod error;
+pub mod event;
+pub mod filter;
+pub mod observer;
+pub mod removal_detection;
 pub mod schedule;
 pub mod system;
+pub mod system_param;
 pub mod time;
 pub mod world;
 
 // Re-export the proc-macros so users only depend on `tiny_ecs`.
-// `Component`/`Resource`/`ScheduleLabel` live in *both* namespaces here: the
-// trait (type namespace, defined in the crate) and the derive macro (macro
-// namespace, re-exported from `tiny_ecs_macros`).
-pub use tiny_ecs_macros::{define_world, system, Component, Resource, ScheduleLabel};
+// `Bundle`/`Component`/`Resource`/`ScheduleLabel`/`SystemParam`/`SystemSet` live in *both* namespaces
+// here: the trait (type namespace, defined in the crate) and the derive macro
+// (macro namespace, re-exported from `tiny_ecs_macros`).
+pub use tiny_ecs_macros::{define_world, system, Bundle, Component, Resource, ScheduleLabel, SystemParam, SystemSet};
+
+/// Re-exports the [`Bundle`](crate::bundle::Bundle) trait next to its derive.
+pub use crate::bundle::Bundle;
+
+/// Re-exports the [`SystemParam`](crate::system_param::SystemParam) trait next
+/// to its derive.
+pub use crate::system_param::SystemParam;
+
+/// Re-exports the [`SystemSet`](crate::schedule::SystemSet) trait next to its
+/// derive.
+pub use crate::schedule::SystemSet;
 
 /// Re-exports the `Time`/`Timer` family at the crate root for convenience.
 pub use crate::time::{Time, Timer, TimerMode};
 
 /// Marker trait for component types.
 ///
-/// Implemented via `#[derive(Component)]`; the marker keeps the derive trivial
-/// and column access is keyed by the `ColumnRef` impl that `define_world!`
-/// generates per registered component.
+/// Implemented via `#[derive(Component)]`, together with
+/// [`RequiredComponents`](crate::component::RequiredComponents); the marker
+/// itself stays trivial and column access is keyed by the `ColumnRef` impl
+/// that `define_world!` generates per registered component.
 pub trait Component: 'static {}
 
 /// Marker trait for resource types.
@@ -74,20 +100,32 @@ pub trait Resource: 'static {}
 /// desktop Bevy import.
 pub mod prelude {
     pub use crate::app::{App, AppExit, Plugin, Plugins};
-    pub use crate::column::{Column, ColumnOps};
-    pub use crate::commands::{Commands, EntityTarget};
+    pub use crate::change_detection::{DetectChanges, DetectChangesMut, Mut, Tick};
+    pub use crate::column::{Column, ColumnOps, SparseColumn};
+    pub use crate::commands::{Commands, EntityCommands};
     pub use crate::commands_buffer::CommandBuffer;
     pub use crate::entity::Entity;
-    pub use crate::schedule::{Schedule, MAX_SYSTEMS_PER, ScheduleLabel, Startup, PreUpdate, Update, PostUpdate};
+    pub use crate::error::{SystemError, SystemErrors, SystemFailed};
+    pub use crate::event::{EventReader, EventWriter, Events, Overflow};
+    pub use crate::filter::{Added, Changed, Or, QueryFilter, With, Without};
+    pub use crate::observer::{OnAdd, OnInsert, OnRemove, Trigger};
+    pub use crate::removal_detection::RemovedComponents;
+    pub use crate::schedule::{
+        IntoSetConfig, IntoSystemConfig, IntoSystemConfigs, IntoSystemSet, Schedule, MAX_SYSTEMS_PER, ScheduleLabel, Startup,
+        PreUpdate, Update, PostUpdate,
+    };
     pub use crate::system::{
-        ColumnRef, CommandsRef, Fetch, HasResource, IntoSystem, Query, Res, ResMut,
-        ResourceInsRef, ResourceRef, SpawnRef, System,
+        ChangeTicksRef, ColumnRef, CommandsRef, Condition, DiagnosticsRef, EntitiesRef, EntityCommandsRef, EventsRef, Fetch,
+        HasResource, IntoCondition, IntoSystem, LocalsRef,
+        Local, Query, QueryCombinationIter, QueryCombinationIterMut, QueryEntityError, QuerySingleError, Res,
+        RemovedRef, ResMut, ResourceInsRef, ResourceRef, Single, System, SystemSkipped, TriggerRef,
     };
+    pub use crate::system_param::SystemParamFetch;
     pub use crate::time::{Time, Timer, TimerMode};
-    pub use crate::world::WorldApi;
-    // Brings in the `Component`/`Resource` traits *and* their derive macros
-    // (both namespaces at the crate root, since the macros are re-exported
-    // there and the traits are defined there).
-    pub use crate::{Component, Resource};
+    pub use crate::world::{FromWorld, WorldApi};
+    // Brings in the `Bundle`/`Component`/`Resource`/`SystemParam`/`SystemSet` traits *and* their derive
+    // macros (both namespaces at the crate root, since the macros are
+    // re-exported there and the traits are defined or re-exported there).
+    pub use crate::{Bundle, Component, Resource, SystemParam, SystemSet};
     pub use tiny_ecs_macros::{define_world, system};
 }
\ No newline at end of file
@@ -0,0 +1,204 @@
+//! Component lifecycle events and the systems that observe them, mirroring
+//! Bevy's hooks and observers.
+//!
+//! Three events fire per component type `T`: [`OnAdd<T>`] when `T` lands on an
+//! entity that lacked it, [`OnInsert<T>`] on every insert (including
+//! replacements), and [`OnRemove<T>`] just before `T` leaves an entity, by
+//! `remove::<T>()` or by a despawn. `World::flush_commands` and
+//! `World::despawn` fire them.
+//!
+//! Two kinds of system react to an event, both ordinary `#[system]`s that take
+//! a [`Trigger<E>`] param naming the entity:
+//!
+//! * hooks, declared on the component itself with
+//!   `#[component(on_add = .., on_insert = .., on_remove = ..)]` and run
+//!   first;
+//! * observers, registered at runtime with `App::observe::<OnRemove<T>>(..)`
+//!   and run in registration order.
+//!
+//! Both run in the middle of a flush or a despawn. Ordinary systems defer
+//! their world access through `Commands`, and whatever they queue lands in the
+//! same flush; exclusive ones take `&mut World` and act on it directly. An
+//! `OnRemove` system that despawns an entity already being despawned is a
+//! no-op.
+
+use core::marker::PhantomData;
+
+use crate::entity::Entity;
+use crate::Component;
+
+/// Maximum number of observers a `World` holds across all events; registering
+/// one more panics.
+pub const MAX_OBSERVERS: usize = 8;
+
+/// A lifecycle event that observers can be registered for.
+pub trait ComponentEvent: 'static {}
+
+/// Fired when a `T` is inserted on an entity that did not carry one.
+pub struct OnAdd<T>(PhantomData<fn() -> T>);
+
+/// Fired on every insert of a `T`, after [`OnAdd<T>`] for a new one.
+pub struct OnInsert<T>(PhantomData<fn() -> T>);
+
+/// Fired just before a `T` is removed from an entity, while it can still be
+/// read.
+pub struct OnRemove<T>(PhantomData<fn() -> T>);
+
+impl<T: Component> ComponentEvent for OnAdd<T> {}
+impl<T: Component> ComponentEvent for OnInsert<T> {}
+impl<T: Component> ComponentEvent for OnRemove<T> {}
+
+/// The system param handing a hook or observer the entity its event fired
+/// for, mirroring Bevy's `Trigger`.
+///
+/// Outside a hook or observer run it names the entity of the latest event.
+pub struct Trigger<E> {
+    /// The entity the event fired for.
+    entity: Entity,
+    /// Tags the param with its event type.
+    _event: PhantomData<fn() -> E>,
+}
+
+impl<E> Trigger<E> {
+    /// Creates a trigger for `entity`.
+    pub fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _event: PhantomData,
+        }
+    }
+
+    /// Returns the entity the event fired for.
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MAX_OBSERVERS;
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    #[component(on_add = arm, on_remove = disarm)]
+    struct Bomb;
+
+    #[derive(Component)]
+    struct Fuse;
+
+    #[derive(Component)]
+    #[component(on_add = detonate)]
+    struct Spark;
+
+    #[derive(Component)]
+    #[component(on_remove = scuttle)]
+    struct Wreck;
+
+    /// Every hook and observer run, in order: a tag and the trigger's entity.
+    #[derive(Resource, Default)]
+    struct Log(heapless::Vec<(&'static str, Entity), 8>);
+
+    define_world! {
+        struct World {
+            entities: 4,
+            schedules: 1,
+            components { bomb: Bomb [4], fuse: Fuse [4], spark: Spark [4], wreck: Wreck [4], }
+            resources { log: Log, }
+        }
+    }
+
+    /// Records `tag` for the trigger's entity.
+    fn record(log: &mut Log, tag: &'static str, entity: Entity) {
+        log.0.push((tag, entity)).expect("log room");
+    }
+
+    #[system]
+    fn arm(trigger: Trigger<OnAdd<Bomb>>, mut log: ResMut<Log>) {
+        record(&mut log, "arm", trigger.entity());
+    }
+
+    #[system]
+    fn disarm(trigger: Trigger<OnRemove<Bomb>>, mut log: ResMut<Log>) {
+        record(&mut log, "disarm", trigger.entity());
+    }
+
+    #[system]
+    fn watch_bomb(trigger: Trigger<OnAdd<Bomb>>, mut log: ResMut<Log>) {
+        record(&mut log, "watch", trigger.entity());
+    }
+
+    #[system]
+    fn watch_fuse(trigger: Trigger<OnRemove<Fuse>>, mut log: ResMut<Log>) {
+        record(&mut log, "fuse", trigger.entity());
+    }
+
+    #[system]
+    fn watch_spark(trigger: Trigger<OnAdd<Spark>>, mut log: ResMut<Log>) {
+        record(&mut log, "spark", trigger.entity());
+    }
+
+    /// An exclusive hook firing events of its own mid-trigger.
+    #[system]
+    fn detonate(world: &mut World) {
+        let fuses: heapless::Vec<Entity, 4> = world.query_filtered::<Entity, With<Fuse>>().iter().collect();
+        for fuse in fuses {
+            world.despawn(fuse);
+        }
+    }
+
+    /// An exclusive removal hook despawning every wreck, including the one
+    /// whose removal fired it.
+    #[system]
+    fn scuttle(world: &mut World) {
+        let wrecks: heapless::Vec<Entity, 4> = world.query_filtered::<Entity, With<Wreck>>().iter().collect();
+        for wreck in wrecks {
+            let despawned = world.despawn(wreck);
+            let mut log = world.get_resource_mut::<Log>().expect("inserted");
+            record(&mut log, if despawned { "sunk" } else { "scuttle" }, wreck);
+        }
+    }
+
+    #[test]
+    fn hooks_run_before_observers_in_registration_order() {
+        let mut world = World::new();
+        world.init_resource::<Log>();
+        world.add_observer::<OnAdd<Bomb>>(IntoSystem::<World>::into_system(watch_bomb));
+        let bomb = world.spawn(Bomb).expect("budget");
+        world.despawn(bomb);
+        let log = &world.get_resource::<Log>().expect("inserted").0;
+        assert_eq!(log, &[("arm", bomb), ("watch", bomb), ("disarm", bomb)]);
+    }
+
+    #[test]
+    fn nested_events_hand_the_trigger_back() {
+        let mut world = World::new();
+        world.init_resource::<Log>();
+        world.add_observer::<OnRemove<Fuse>>(IntoSystem::<World>::into_system(watch_fuse));
+        world.add_observer::<OnAdd<Spark>>(IntoSystem::<World>::into_system(watch_spark));
+        let fuse = world.spawn(Fuse).expect("budget");
+        let spark = world.spawn(Spark).expect("budget");
+        let log = &world.get_resource::<Log>().expect("inserted").0;
+        assert_eq!(log, &[("fuse", fuse), ("spark", spark)]);
+    }
+
+    #[test]
+    fn despawning_the_trigger_entity_from_on_remove_is_a_no_op() {
+        let mut world = World::new();
+        world.init_resource::<Log>();
+        let wreck = world.spawn((Wreck, Fuse)).expect("budget");
+        world.add_observer::<OnRemove<Fuse>>(IntoSystem::<World>::into_system(watch_fuse));
+        assert!(world.despawn(wreck));
+        assert!(!world.is_alive(wreck));
+        let log = &world.get_resource::<Log>().expect("inserted").0;
+        assert_eq!(log, &[("fuse", wreck), ("scuttle", wreck)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "more than 8 observers registered")]
+    fn observers_past_the_limit_panic() {
+        let mut world = World::new();
+        for _ in 0..=MAX_OBSERVERS {
+            world.add_observer::<OnAdd<Bomb>>(IntoSystem::<World>::into_system(watch_bomb));
+        }
+    }
+}
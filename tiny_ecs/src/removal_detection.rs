@@ -0,0 +1,232 @@
+//! Removal detection: the bounded per-component removal log and the
+//! [`RemovedComponents`] system param, mirroring
+//! `bevy_ecs::removal_detection`.
+//!
+//! A component opts in with a `removed: N` column option in `define_world!`
+//! (`debris: Debris [64, removed: 8]`). Every removal of that component — by
+//! `remove::<T>()` or by a despawn — then records the entity and the change
+//! tick it happened at in a ring of `N` entries, overwriting the oldest when
+//! full. A system reading `RemovedComponents<T>` sees the entries newer than
+//! its previous run, so each reader observes every removal once as long as
+//! the log does not wrap between two of its runs.
+
+use core::marker::PhantomData;
+
+use crate::change_detection::{SystemTicks, Tick};
+use crate::entity::Entity;
+
+/// A ring of the latest `N` removals of one component type.
+pub struct RemovalLog<const N: usize> {
+    /// The recorded removals; the oldest sits at `start`.
+    entries: [(Entity, Tick); N],
+    /// Index of the oldest entry.
+    start: usize,
+    /// Number of recorded entries (at most `N`).
+    len: usize,
+}
+
+impl<const N: usize> RemovalLog<N> {
+    /// Creates an empty log.
+    pub const fn new() -> Self {
+        Self {
+            entries: [(Entity::new(0), Tick::new(0)); N],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// Records the removal of `entity` at `tick`, dropping the oldest entry
+    /// when the log is full.
+    pub fn push(&mut self, entity: Entity, tick: Tick) {
+        if N == 0 {
+            return;
+        }
+        if self.len < N {
+            self.entries[(self.start + self.len) % N] = (entity, tick);
+            self.len += 1;
+        } else {
+            self.entries[self.start] = (entity, tick);
+            self.start = (self.start + 1) % N;
+        }
+    }
+
+    /// Clamps the tick of every entry relative to `this_run`, see
+    /// [`Tick::check_tick`].
+    pub fn check_change_ticks(&mut self, this_run: Tick) {
+        self.entries.iter_mut().for_each(|(_, tick)| {
+            tick.check_tick(this_run);
+        });
+    }
+}
+
+impl<const N: usize> Default for RemovalLog<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Object-safe read access to a [`RemovalLog`] of any capacity.
+pub trait RemovalLogOps {
+    /// Returns the number of recorded removals.
+    fn len(&self) -> usize;
+
+    /// Returns `true` when no removal is recorded.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the `i`-th recorded removal, oldest first.
+    fn get(&self, i: usize) -> Option<(Entity, Tick)>;
+}
+
+impl<const N: usize> RemovalLogOps for RemovalLog<N> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, i: usize) -> Option<(Entity, Tick)> {
+        (i < self.len).then(|| self.entries[(self.start + i) % N])
+    }
+}
+
+// =====================================================================
+// --- System param -----------------------------------------------------
+
+/// The entities that lost their `T` since the reading system last ran,
+/// mirroring Bevy's `RemovedComponents`.
+pub struct RemovedComponents<'w, T> {
+    /// The world's removal log for `T`.
+    log: &'w dyn RemovalLogOps,
+    /// The reading system's ticks.
+    system: SystemTicks,
+    /// Tags the param with its component type.
+    _component: PhantomData<fn() -> T>,
+}
+
+impl<'w, T> RemovedComponents<'w, T> {
+    /// Wraps the removal `log` for a system running with `system`.
+    pub fn new(log: &'w dyn RemovalLogOps, system: SystemTicks) -> Self {
+        Self {
+            log,
+            system,
+            _component: PhantomData,
+        }
+    }
+
+    /// Iterates the removed entities, oldest first. An entity appears once per
+    /// removal, and may already be despawned or carry a new `T`.
+    pub fn read(&self) -> RemovedIter<'w> {
+        RemovedIter {
+            log: self.log,
+            system: self.system,
+            next: 0,
+        }
+    }
+
+    /// Returns the number of removals since the system last ran.
+    pub fn len(&self) -> usize {
+        self.read().count()
+    }
+
+    /// Returns `true` when nothing was removed since the system last ran.
+    pub fn is_empty(&self) -> bool {
+        self.read().next().is_none()
+    }
+}
+
+impl<'w, T> IntoIterator for &RemovedComponents<'w, T> {
+    type Item = Entity;
+    type IntoIter = RemovedIter<'w>;
+    fn into_iter(self) -> RemovedIter<'w> {
+        self.read()
+    }
+}
+
+/// Iterator over the entries of a removal log newer than a system's last run.
+pub struct RemovedIter<'w> {
+    /// The log being read.
+    log: &'w dyn RemovalLogOps,
+    /// The reading system's ticks.
+    system: SystemTicks,
+    /// Index of the next entry to inspect.
+    next: usize,
+}
+
+impl Iterator for RemovedIter<'_> {
+    type Item = Entity;
+    fn next(&mut self) -> Option<Entity> {
+        while let Some((entity, tick)) = self.log.get(self.next) {
+            self.next += 1;
+            if tick.is_newer_than(self.system.last_run, self.system.this_run) {
+                return Some(entity);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn the_ring_keeps_the_latest_entries_oldest_first() {
+        let mut log = RemovalLog::<3>::new();
+        for index in 0..5 {
+            log.push(Entity::new(index), Tick::new(index));
+        }
+        assert_eq!(log.len(), 3);
+        let kept: heapless::Vec<u32, 3> = (0..3).filter_map(|i| log.get(i)).map(|(e, _)| e.index()).collect();
+        assert_eq!(kept, [2, 3, 4]);
+        assert_eq!(log.get(3), None);
+
+        // A zero-capacity log records nothing.
+        let mut empty = RemovalLog::<0>::new();
+        empty.push(Entity::new(0), Tick::new(1));
+        assert!(empty.is_empty());
+    }
+
+    #[derive(Component)]
+    struct Debris;
+
+    #[derive(Resource, Default)]
+    struct Seen(usize);
+
+    define_world! {
+        struct World {
+            entities: 8,
+            schedules: 1,
+            components { debris: Debris [8, removed: 2], }
+            resources { seen: Seen, }
+        }
+    }
+
+    #[system]
+    fn count_removed(removed: RemovedComponents<Debris>, mut seen: ResMut<Seen>) {
+        seen.0 = removed.len();
+    }
+
+    #[test]
+    fn readers_see_each_removal_once_within_the_ring() {
+        let mut world = World::new();
+        world.init_resource::<Seen>();
+        world.add_system(Update, count_removed);
+        let debris: [Entity; 3] = core::array::from_fn(|_| world.spawn(Debris).expect("budget"));
+        let seen = |world: &mut World| {
+            world.run_schedule(Update);
+            world.get_resource::<Seen>().map(|seen| seen.0)
+        };
+
+        world.despawn(debris[0]);
+        assert_eq!(seen(&mut world), Some(1));
+        assert_eq!(seen(&mut world), Some(0));
+
+        // Wrapping the two-entry ring between runs loses the oldest removal.
+        world.despawn(debris[1]);
+        world.despawn(debris[2]);
+        let extra = world.spawn(Debris).expect("budget");
+        world.despawn(extra);
+        assert_eq!(seen(&mut world), Some(2));
+    }
+}
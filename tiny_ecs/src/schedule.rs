@@ -9,13 +9,71 @@
 //! The four conventional labels ([`Startup`], [`PreUpdate`], [`Update`],
 //! [`PostUpdate`]) are predefined here so platform plugins and apps can share
 //! them without ceremony.
+//!
+//! A system may be registered with run conditions, read-only `#[system]`s
+//! returning `bool`, and placed in a [`SystemSet`] whose conditions all of its
+//! systems share:
+//!
+//! ```ignore
+//! app.configure_set(Update, Playing.run_if(not_game_over));
+//! app.add_system(Update, fall_debris.in_set(Playing));
+//! app.add_system(Update, draw.run_if(frame_due));
+//! ```
+//!
+//! Systems run in registration order unless ordered against other systems or
+//! sets with `before` / `after`, or chained as a tuple:
+//!
+//! ```ignore
+//! app.add_systems(Update, (player_input, fall_debris, collision).chain());
+//! app.add_system(Update, draw.after(collision));
+//! ```
+//!
+//! The schedule sorts itself on its first run after a system was added,
+//! panicking on a cycle.
+//!
+//! # Limits
+//!
+//! A schedule's pools are fixed at compile time: `define_world!` sizes every
+//! schedule of its world with its optional `systems`, `sets`, `conditions`
+//! and `orderings` params, defaulting to [`MAX_SYSTEMS_PER`],
+//! [`MAX_SETS_PER`], [`MAX_CONDITIONS_PER`] and [`MAX_ORDERINGS_PER`]. A
+//! system takes at most [`MAX_CONDITIONS`] run conditions, as does a set,
+//! and at most [`MAX_ORDERINGS`] `before` / `after` constraints, counting
+//! the one `chain` adds.
+//! Going past any of these panics at registration, naming the system or set,
+//! rather than dropping it or what guards it.
+
+use core::any::TypeId;
+use core::fmt;
+use core::marker::PhantomData;
 
 use heapless::Vec as HVec;
 
-use crate::system::System;
+use crate::change_detection::{SystemTicks, Tick};
+use crate::system::{Condition, IntoCondition, IntoSystem, System};
+
+/// Default maximum number of systems per schedule; at most 32.
+pub const MAX_SYSTEMS_PER: usize = 8;
+
+/// Default maximum number of system sets per schedule.
+pub const MAX_SETS_PER: usize = 2;
+
+/// Default maximum number of run conditions per schedule, over all its
+/// systems and sets.
+pub const MAX_CONDITIONS_PER: usize = 4;
+
+/// Default maximum number of ordering constraints per schedule.
+pub const MAX_ORDERINGS_PER: usize = 8;
 
-/// Maximum number of systems per schedule.
-pub const MAX_SYSTEMS_PER: usize = 32;
+/// Maximum number of run conditions given to one system or set.
+pub const MAX_CONDITIONS: usize = 4;
+
+/// Maximum number of `before` / `after` constraints given to one system.
+pub const MAX_ORDERINGS: usize = 4;
+
+/// Maximum number of systems registered together by
+/// [`App::add_systems`](crate::app::App::add_systems).
+pub const MAX_SYSTEMS_PER_ADD: usize = 8;
 
 // ---------------------------------------------------------------------
 // --- Schedule labels -------------------------------------------------
@@ -48,32 +106,522 @@ impl ScheduleLabel for Update {}
 pub struct PostUpdate;
 impl ScheduleLabel for PostUpdate {}
 
+// ---------------------------------------------------------------------
+// --- System sets -----------------------------------------------------
+
+/// Marker trait for types naming a group of systems that share run
+/// conditions and ordering.
+///
+/// Implemented via `#[derive(SystemSet)]`; like [`ScheduleLabel`], a set is
+/// keyed by the [`TypeId`] of its type, so a unit struct is all it takes.
+pub trait SystemSet: 'static {}
+
+/// Identifies a set, or a system, within a schedule: returns the `TypeId` and
+/// name of its type.
+///
+/// A fn pointer rather than the 16-byte `TypeId` itself, so every scheduled
+/// system costs 4 bytes of RAM for it.
+pub type SetKey = fn() -> (TypeId, &'static str);
+
+/// The [`SetKey`] of type `T`.
+fn key_of<T: 'static>() -> (TypeId, &'static str) {
+    (TypeId::of::<T>(), core::any::type_name::<T>())
+}
+
+/// A [`SystemSet`], or a system standing for all its registrations, that
+/// systems can be ordered against; mirrors Bevy's `IntoSystemSet`.
+///
+/// `Marker` keeps the impls for systems and for sets apart; it is always
+/// inferred.
+pub trait IntoSystemSet<W, Marker> {
+    /// Returns the key of the set.
+    fn into_key(self) -> SetKey;
+}
+
+impl<W, S: IntoSystem<W> + 'static> IntoSystemSet<W, ()> for S {
+    fn into_key(self) -> SetKey {
+        key_of::<S>
+    }
+}
+
+/// The [`IntoSystemSet`] marker of a [`SystemSet`].
+pub struct IsSystemSet;
+
+impl<W, S: SystemSet> IntoSystemSet<W, IsSystemSet> for S {
+    fn into_key(self) -> SetKey {
+        key_of::<S>
+    }
+}
+
+// ---------------------------------------------------------------------
+// --- Configs ---------------------------------------------------------
+
+/// A system on its way into a schedule, with its run conditions, set and
+/// ordering.
+///
+/// Built by the [`IntoSystemConfig`] methods; `W` ties the conditions to the
+/// system's world type.
+pub struct SystemConfig<W> {
+    /// The type-erased system.
+    system: System,
+    /// The system's own key, which `before` / `after` of others refer to.
+    key: SetKey,
+    /// Conditions that must all hold for the system to run.
+    conditions: HVec<Condition, MAX_CONDITIONS>,
+    /// The set the system belongs to, if any.
+    set: Option<SetKey>,
+    /// Systems and sets this one runs before.
+    before: HVec<SetKey, MAX_ORDERINGS>,
+    /// Systems and sets this one runs after.
+    after: HVec<SetKey, MAX_ORDERINGS>,
+    /// Ties the config to the world type.
+    _world: PhantomData<fn(*mut W)>,
+}
+
+impl<W> SystemConfig<W> {
+    /// Runs the system after `other`.
+    ///
+    /// # Panics
+    /// When the system already runs after [`MAX_ORDERINGS`] others.
+    fn push_after(&mut self, other: SetKey) {
+        if self.after.push(other).is_err() {
+            panic!("system `{}` runs after more than {} others", (self.key)().1, MAX_ORDERINGS);
+        }
+    }
+}
+
+/// Converts a system, or a system already configured, into a
+/// [`SystemConfig`], mirroring Bevy's `IntoSystemConfigs`.
+///
+/// `Marker` keeps the impls for systems and for configs apart; it is always
+/// inferred.
+pub trait IntoSystemConfig<W, Marker>: Sized {
+    /// Returns the config registering the system.
+    fn into_config(self) -> SystemConfig<W>;
+
+    /// Runs the system only when `condition` returns `true`. Several
+    /// conditions must all hold.
+    ///
+    /// # Panics
+    /// When the system already has [`MAX_CONDITIONS`] conditions.
+    fn run_if<C: IntoCondition<W>>(self, condition: C) -> SystemConfig<W> {
+        let mut config = self.into_config();
+        if config.conditions.push(condition.into_condition()).is_err() {
+            panic!("system `{}` has more than {} run conditions", (config.key)().1, MAX_CONDITIONS);
+        }
+        config
+    }
+
+    /// Puts the system in `set`, so it also runs only when the set's
+    /// conditions hold, and is ordered with the set.
+    fn in_set<S: SystemSet>(self, _set: S) -> SystemConfig<W> {
+        let mut config = self.into_config();
+        config.set = Some(key_of::<S>);
+        config
+    }
+
+    /// Runs the system before `other`, a system or set.
+    ///
+    /// # Panics
+    /// When the system already runs before [`MAX_ORDERINGS`] others.
+    fn before<M, T: IntoSystemSet<W, M>>(self, other: T) -> SystemConfig<W> {
+        let mut config = self.into_config();
+        if config.before.push(other.into_key()).is_err() {
+            panic!("system `{}` runs before more than {} others", (config.key)().1, MAX_ORDERINGS);
+        }
+        config
+    }
+
+    /// Runs the system after `other`, a system or set.
+    ///
+    /// # Panics
+    /// When the system already runs after [`MAX_ORDERINGS`] others.
+    fn after<M, T: IntoSystemSet<W, M>>(self, other: T) -> SystemConfig<W> {
+        let mut config = self.into_config();
+        config.push_after(other.into_key());
+        config
+    }
+}
+
+impl<W, S: IntoSystem<W> + 'static> IntoSystemConfig<W, ()> for S {
+    fn into_config(self) -> SystemConfig<W> {
+        SystemConfig {
+            system: self.into_system(),
+            key: key_of::<S>,
+            conditions: HVec::new(),
+            set: None,
+            before: HVec::new(),
+            after: HVec::new(),
+            _world: PhantomData,
+        }
+    }
+}
+
+/// The [`IntoSystemConfig`] marker of a [`SystemConfig`] itself.
+pub struct Configured;
+
+impl<W> IntoSystemConfig<W, Configured> for SystemConfig<W> {
+    fn into_config(self) -> SystemConfig<W> {
+        self
+    }
+}
+
+/// Several systems registered together by
+/// [`App::add_systems`](crate::app::App::add_systems).
+pub struct SystemConfigs<W> {
+    /// The systems, in tuple order.
+    configs: HVec<SystemConfig<W>, MAX_SYSTEMS_PER_ADD>,
+}
+
+impl<W> IntoIterator for SystemConfigs<W> {
+    type Item = SystemConfig<W>;
+    type IntoIter = <HVec<SystemConfig<W>, MAX_SYSTEMS_PER_ADD> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.configs.into_iter()
+    }
+}
+
+/// Converts a tuple of systems into [`SystemConfigs`].
+pub trait IntoSystemConfigs<W, Marker>: Sized {
+    /// Returns the configs of the systems.
+    fn into_configs(self) -> SystemConfigs<W>;
+
+    /// Runs the systems one after another, in tuple order.
+    ///
+    /// # Panics
+    /// When a system already runs after [`MAX_ORDERINGS`] others.
+    fn chain(self) -> SystemConfigs<W> {
+        let mut configs = self.into_configs();
+        let mut previous: Option<SetKey> = None;
+        for config in configs.configs.iter_mut() {
+            if let Some(key) = previous {
+                config.push_after(key);
+            }
+            previous = Some(config.key);
+        }
+        configs
+    }
+
+    /// Puts every system in `set`.
+    fn in_set<S: SystemSet>(self, _set: S) -> SystemConfigs<W> {
+        let mut configs = self.into_configs();
+        for config in configs.configs.iter_mut() {
+            config.set = Some(key_of::<S>);
+        }
+        configs
+    }
+}
+
+impl<W> IntoSystemConfigs<W, Configured> for SystemConfigs<W> {
+    fn into_configs(self) -> SystemConfigs<W> {
+        self
+    }
+}
+
+/// Implements [`IntoSystemConfigs`] for a tuple of systems or configs, e.g.
+/// `(player_input, fall_debris.run_if(playing))`.
+macro_rules! impl_system_configs_tuple {
+    ($(($s:ident, $m:ident)),+) => {
+        impl<W, $($m, $s: IntoSystemConfig<W, $m>),+> IntoSystemConfigs<W, ($($m,)+)> for ($($s,)+) {
+            #[allow(non_snake_case)]
+            fn into_configs(self) -> SystemConfigs<W> {
+                let ($($s,)+) = self;
+                let mut configs = HVec::new();
+                $(let _ = configs.push($s.into_config());)+
+                SystemConfigs { configs }
+            }
+        }
+    };
+}
+
+impl_system_configs_tuple!((A, MA));
+impl_system_configs_tuple!((A, MA), (B, MB));
+impl_system_configs_tuple!((A, MA), (B, MB), (C, MC));
+impl_system_configs_tuple!((A, MA), (B, MB), (C, MC), (D, MD));
+impl_system_configs_tuple!((A, MA), (B, MB), (C, MC), (D, MD), (E, ME));
+impl_system_configs_tuple!((A, MA), (B, MB), (C, MC), (D, MD), (E, ME), (F, MF));
+impl_system_configs_tuple!((A, MA), (B, MB), (C, MC), (D, MD), (E, ME), (F, MF), (G, MG));
+impl_system_configs_tuple!((A, MA), (B, MB), (C, MC), (D, MD), (E, ME), (F, MF), (G, MG), (H, MH));
+
+/// A [`SystemSet`] with the run conditions its systems share.
+pub struct SetConfig<W> {
+    /// The set's key.
+    set: SetKey,
+    /// Conditions that must all hold for the set's systems to run.
+    conditions: HVec<Condition, MAX_CONDITIONS>,
+    /// Ties the config to the world type.
+    _world: PhantomData<fn(*mut W)>,
+}
+
+/// Converts a set, or a set already given conditions, into a [`SetConfig`].
+pub trait IntoSetConfig<W>: Sized {
+    /// Returns the config of the set.
+    fn into_set_config(self) -> SetConfig<W>;
+
+    /// Runs the set's systems only when `condition` returns `true`. Several
+    /// conditions must all hold.
+    ///
+    /// # Panics
+    /// When the set already has [`MAX_CONDITIONS`] conditions.
+    fn run_if<C: IntoCondition<W>>(self, condition: C) -> SetConfig<W> {
+        let mut config = self.into_set_config();
+        if config.conditions.push(condition.into_condition()).is_err() {
+            panic!("set `{}` has more than {} run conditions", (config.set)().1, MAX_CONDITIONS);
+        }
+        config
+    }
+}
+
+impl<W, S: SystemSet> IntoSetConfig<W> for S {
+    fn into_set_config(self) -> SetConfig<W> {
+        SetConfig {
+            set: key_of::<S>,
+            conditions: HVec::new(),
+            _world: PhantomData,
+        }
+    }
+}
+
+impl<W> IntoSetConfig<W> for SetConfig<W> {
+    fn into_set_config(self) -> SetConfig<W> {
+        self
+    }
+}
+
 // ---------------------------------------------------------------------
 // --- Schedule --------------------------------------------------------
 
-/// A schedule: an ordered, bounded list of systems.
-pub struct Schedule {
-    /// The systems to run, in registration order.
-    systems: HVec<System, MAX_SYSTEMS_PER>,
+/// The `set` of a [`ScheduledSystem`] outside any set.
+const NO_SET: u8 = u8::MAX;
+
+/// A system registered in a [`Schedule`], with the tick of its last run.
+struct ScheduledSystem {
+    /// The type-erased system.
+    system: System,
+    /// The change tick this system last ran at (`0` before its first run).
+    last_run: Tick,
+    /// The system's own key, matched by ordering constraints.
+    key: SetKey,
+    /// The index of the system's set in [`Schedule::sets`], or [`NO_SET`].
+    set: u8,
+}
+
+/// A schedule: a bounded list of systems, run in dependency order.
+///
+/// Conditions and orderings live in schedule-wide pools rather than per
+/// system, so a schedule costs RAM for the ones actually registered. The
+/// pools hold up to `SYSTEMS` systems (at most 32), `SETS` sets,
+/// `CONDITIONS` run conditions and `ORDERINGS` ordering constraints.
+pub struct Schedule<
+    const SYSTEMS: usize = MAX_SYSTEMS_PER,
+    const SETS: usize = MAX_SETS_PER,
+    const CONDITIONS: usize = MAX_CONDITIONS_PER,
+    const ORDERINGS: usize = MAX_ORDERINGS_PER,
+> {
+    /// The systems, in registration order.
+    systems: HVec<ScheduledSystem, SYSTEMS>,
+    /// The sets its systems belong to or that were configured.
+    sets: HVec<SetKey, SETS>,
+    /// Run conditions, each with its owner: a system's index, or `SYSTEMS`
+    /// plus a set's index.
+    conditions: HVec<(u8, Condition), CONDITIONS>,
+    /// Ordering constraints: the systems of the first set run before those
+    /// of the second.
+    orderings: HVec<(SetKey, SetKey), ORDERINGS>,
+    /// Indices into `systems` in the order they run.
+    order: HVec<u8, SYSTEMS>,
+    /// Whether `order` covers every system added so far.
+    sorted: bool,
+    /// Whether this is the stand-in for a schedule that is running.
+    running: bool,
 }
-impl Schedule {
+impl<const SYSTEMS: usize, const SETS: usize, const CONDITIONS: usize, const ORDERINGS: usize>
+    Schedule<SYSTEMS, SETS, CONDITIONS, ORDERINGS>
+{
+    /// The sort tracks each system's predecessors in a `u32` bitmask, and a
+    /// condition's owner is a `u8` below [`NO_SET`].
+    const FITS: () = assert!(
+        SYSTEMS <= 32 && SYSTEMS + SETS < NO_SET as usize,
+        "a schedule holds at most 32 systems"
+    );
+
+    /// The owner of the conditions of the set at `index`.
+    const fn set_owner(index: u8) -> u8 {
+        SYSTEMS as u8 + index
+    }
+
     /// Creates an empty schedule.
     pub const fn new() -> Self {
+        let () = Self::FITS;
         Self {
             systems: HVec::new(),
+            sets: HVec::new(),
+            conditions: HVec::new(),
+            orderings: HVec::new(),
+            order: HVec::new(),
+            sorted: true,
+            running: false,
+        }
+    }
+
+    /// Creates the empty stand-in a world keeps for a schedule while it is
+    /// taken out to run: running it does nothing, and adding to it panics.
+    pub const fn running() -> Self {
+        let mut schedule = Self::new();
+        schedule.running = true;
+        schedule
+    }
+
+    /// Appends a system with its conditions, set and ordering.
+    ///
+    /// # Panics
+    /// When the schedule is running, or has no room left for the system, its
+    /// set, its conditions or its orderings, as the system would be lost, run
+    /// unguarded or run out of order.
+    pub fn add<W>(&mut self, config: SystemConfig<W>) {
+        let name = (config.key)().1;
+        assert!(!self.running, "system `{}` added to its schedule while the schedule runs", name);
+        let index = self.systems.len() as u8;
+        let set = config.set.map_or(NO_SET, |key| self.set_index(key));
+        let pushed = self.systems.push(ScheduledSystem {
+            system: config.system,
+            last_run: Tick::default(),
+            key: config.key,
+            set,
+        });
+        if pushed.is_err() {
+            panic!("no room for system `{}` in its schedule; raise `systems:` in `define_world!`", name);
+        }
+        for condition in config.conditions {
+            if self.conditions.push((index, condition)).is_err() {
+                panic!("no room for the run conditions of system `{}`; raise `conditions:` in `define_world!`", name);
+            }
+        }
+        let before = config.before.into_iter().map(|other| (config.key, other));
+        let after = config.after.into_iter().map(|other| (other, config.key));
+        for ordering in before.chain(after) {
+            if self.orderings.push(ordering).is_err() {
+                panic!("no room for the orderings of system `{}`; raise `orderings:` in `define_world!`", name);
+            }
+        }
+        self.sorted = false;
+    }
+
+    /// Adds `config`'s conditions to its set's.
+    ///
+    /// # Panics
+    /// When the schedule is running, or has no room left for the set or its
+    /// conditions, as the set's systems would run unguarded.
+    pub fn configure_set<W>(&mut self, config: SetConfig<W>) {
+        let name = (config.set)().1;
+        assert!(!self.running, "set `{}` configured while its schedule runs", name);
+        let owner = Self::set_owner(self.set_index(config.set));
+        for condition in config.conditions {
+            if self.conditions.push((owner, condition)).is_err() {
+                panic!("no room for the run conditions of set `{}`; raise `conditions:` in `define_world!`", name);
+            }
         }
     }
 
-    /// Appends a system; saturates silently when full.
-    pub fn add(&mut self, system: System) {
-        let _ = self.systems.push(system);
+    /// Returns the index of the set `key`, adding it when new.
+    ///
+    /// # Panics
+    /// When the set is new and `SETS` sets are known already.
+    fn set_index(&mut self, key: SetKey) -> u8 {
+        let id = key().0;
+        if let Some(index) = self.sets.iter().position(|set| set().0 == id) {
+            return index as u8;
+        }
+        if self.sets.push(key).is_err() {
+            panic!("no room for set `{}` in its schedule; raise `sets:` in `define_world!`", key().1);
+        }
+        self.sets.len() as u8 - 1
     }
 
-    /// Runs every system in registration order against the raw world pointer.
-    pub fn run(&self, world: *mut ()) {
-        for system in self.systems.iter() {
-            system(world);
+    /// Orders the systems so every constraint holds, keeping registration
+    /// order wherever the constraints allow.
+    ///
+    /// # Panics
+    /// When the constraints form a cycle, naming the systems on it.
+    pub fn sort(&mut self) {
+        let len = self.systems.len();
+        // `before[i]`: the systems that must run before system `i`.
+        let mut before = [0u32; SYSTEMS];
+        for &(first, then) in &self.orderings {
+            let first = self.members(first);
+            for i in bits(self.members(then)) {
+                before[i] |= first & !(1 << i);
+            }
+        }
+        self.order.clear();
+        let mut done = 0u32;
+        while self.order.len() < len {
+            let ready = (0..len).find(|&i| done & (1 << i) == 0 && before[i] & !done == 0);
+            let Some(i) = ready else {
+                panic!("cycle in system ordering among {}", Cycle { systems: &self.systems, done });
+            };
+            done |= 1 << i;
+            let _ = self.order.push(i as u8);
         }
+        self.sorted = true;
+    }
+
+    /// Returns the systems in the set `key` (or registered as system `key`)
+    /// as a bitmask over `systems`.
+    fn members(&self, key: SetKey) -> u32 {
+        let id = key().0;
+        let set = self.sets.iter().position(|set| set().0 == id).map_or(NO_SET, |index| index as u8);
+        let mut mask = 0;
+        for (i, system) in self.systems.iter().enumerate() {
+            if (system.key)().0 == id || (set != NO_SET && system.set == set) {
+                mask |= 1 << i;
+            }
+        }
+        mask
+    }
+
+    /// Runs every system in dependency order against the raw world pointer,
+    /// sorting the schedule first if a system was added since the last run.
+    ///
+    /// Before each system, the world's change tick (behind `ticks`) advances
+    /// and records the system's previous run, so change detection inside the
+    /// system (and its conditions) compares against it. The system is then
+    /// called only when its conditions hold, and its set's too; a set's
+    /// conditions are evaluated once per run, at its first system.
+    ///
+    /// # Safety
+    /// `world` must be a valid pointer to the world the systems were built
+    /// for, and `ticks` must point to that world's tick slot.
+    pub unsafe fn run(&mut self, world: *mut (), ticks: *mut SystemTicks) {
+        if !self.sorted {
+            self.sort();
+        }
+        let mut set_runs: [Option<bool>; SETS] = [None; SETS];
+        for &index in &self.order {
+            let entry = &mut self.systems[index as usize];
+            // SAFETY: the caller hands in the world's own tick slot; systems
+            // only copy it out, never hold a borrow across this write.
+            let this_run = unsafe { (*ticks).begin(entry.last_run) };
+            let set = entry.set;
+            let set_holds = (set != NO_SET).then(|| {
+                let owner = Self::set_owner(set);
+                *set_runs[set as usize].get_or_insert_with(|| holds(&self.conditions, owner, world))
+            });
+            if set_holds == Some(false) || !holds(&self.conditions, index, world) {
+                continue;
+            }
+            (entry.system)(world);
+            entry.last_run = this_run;
+        }
+    }
+
+    /// Clamps the last-run tick of every system relative to `this_run`, see
+    /// [`Tick::check_tick`].
+    pub fn check_change_ticks(&mut self, this_run: Tick) {
+        self.systems.iter_mut().for_each(|entry| {
+            entry.last_run.check_tick(this_run);
+        });
     }
 
     /// Returns the number of systems registered.
@@ -86,8 +634,216 @@ impl Schedule {
         self.systems.is_empty()
     }
 }
-impl Default for Schedule {
+impl<const SYSTEMS: usize, const SETS: usize, const CONDITIONS: usize, const ORDERINGS: usize> Default
+    for Schedule<SYSTEMS, SETS, CONDITIONS, ORDERINGS>
+{
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+/// Whether every condition owned by `owner` holds.
+fn holds(conditions: &[(u8, Condition)], owner: u8, world: *mut ()) -> bool {
+    conditions.iter().all(|&(o, condition)| o != owner || condition(world))
+}
+
+/// Iterates the indices of the set bits of `mask`.
+fn bits(mask: u32) -> impl Iterator<Item = usize> {
+    (0..32).filter(move |&i| mask & (1 << i) != 0)
+}
+
+/// Names the systems left unsorted by a cycle, for the panic message.
+struct Cycle<'a> {
+    /// The schedule's systems.
+    systems: &'a [ScheduledSystem],
+    /// The systems sorted before the cycle was hit.
+    done: u32,
+}
+
+impl fmt::Display for Cycle<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names = (0..self.systems.len())
+            .filter(|&i| self.done & (1 << i) == 0)
+            .map(|i| (self.systems[i].key)().1);
+        if let Some(first) = names.next() {
+            f.write_str(first)?;
+        }
+        names.try_for_each(|name| write!(f, ", {name}"))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    /// The ids of the systems run, in order.
+    #[derive(Resource, Default)]
+    struct Runs(heapless::Vec<u8, 8>);
+
+    /// Whether the `open` condition holds.
+    #[derive(Resource)]
+    struct Gate(bool);
+
+    #[derive(SystemSet)]
+    struct Guarded;
+
+    #[derive(SystemSet)]
+    struct Other;
+
+    define_world! {
+        struct World {
+            entities: 1,
+            schedules: 1,
+            systems: 4,
+            sets: 1,
+            conditions: 2,
+            orderings: 2,
+            resources { runs: Runs, gate: Gate, }
+        }
+    }
+
+    #[system]
+    fn open(gate: Res<Gate>) -> bool {
+        gate.0
+    }
+
+    #[system]
+    fn a(mut runs: ResMut<Runs>) {
+        runs.0.push(1).expect("room");
+    }
+
+    #[system]
+    fn b(mut runs: ResMut<Runs>) {
+        runs.0.push(2).expect("room");
+    }
+
+    #[system]
+    fn c(mut runs: ResMut<Runs>) {
+        runs.0.push(3).expect("room");
+    }
+
+    #[system]
+    fn d(mut runs: ResMut<Runs>) {
+        runs.0.push(4).expect("room");
+    }
+
+    /// A world with an empty run log and the gate set to `opened`.
+    fn world(opened: bool) -> World {
+        let mut world = World::new();
+        world.insert_resource(Runs::default());
+        world.insert_resource(Gate(opened));
+        world
+    }
+
+    /// Runs `Update` once, returning the ids of the systems run.
+    fn run(world: &mut World) -> heapless::Vec<u8, 8> {
+        world.run_schedule(Update);
+        core::mem::take(&mut world.get_resource_mut::<Runs>().expect("inserted").0)
+    }
+
+    #[test]
+    fn conditions_guard_their_system() {
+        let mut world = world(false);
+        world.add_systems(Update, (a.run_if(open), b));
+        assert_eq!(run(&mut world), [2]);
+
+        world.insert_resource(Gate(true));
+        assert_eq!(run(&mut world), [1, 2]);
+    }
+
+    #[test]
+    fn set_conditions_guard_every_member() {
+        let mut world = world(false);
+        world.configure_set(Update, Guarded.run_if(open));
+        world.add_systems(Update, (a.in_set(Guarded), b.in_set(Guarded), c));
+        assert_eq!(run(&mut world), [3]);
+
+        world.insert_resource(Gate(true));
+        assert_eq!(run(&mut world), [1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "schedule::tests::a` has more than 4 run conditions")]
+    fn conditions_beyond_the_system_limit_panic() {
+        let mut world = world(true);
+        world.add_system(Update, a.run_if(open).run_if(open).run_if(open).run_if(open).run_if(open));
+    }
+
+    #[test]
+    #[should_panic(expected = "no room for the run conditions of system `tiny_ecs::schedule::tests::c`")]
+    fn conditions_beyond_the_pool_panic() {
+        let mut world = world(true);
+        world.add_systems(Update, (a.run_if(open), b.run_if(open), c.run_if(open)));
+    }
+
+    #[test]
+    #[should_panic(expected = "no room for set `tiny_ecs::schedule::tests::Other` in its schedule")]
+    fn sets_beyond_the_pool_panic() {
+        let mut world = world(true);
+        world.add_systems(Update, (a.in_set(Guarded), b.in_set(Other)));
+    }
+
+    #[test]
+    #[should_panic(expected = "no room for system `tiny_ecs::schedule::tests::a` in its schedule")]
+    fn systems_beyond_the_pool_panic() {
+        let mut world = world(true);
+        world.add_systems(Update, (a, b, c, d));
+        world.add_system(Update, a);
+    }
+
+    #[test]
+    fn unordered_systems_keep_registration_order() {
+        let mut world = world(true);
+        world.add_systems(Update, (c, a, b));
+        assert_eq!(run(&mut world), [3, 1, 2]);
+    }
+
+    #[test]
+    fn chains_run_in_tuple_order() {
+        let mut world = world(true);
+        world.add_system(Update, d);
+        world.add_systems(Update, (c, b, a).chain());
+        assert_eq!(run(&mut world), [4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn systems_added_later_are_sorted_in() {
+        let mut world = world(true);
+        world.add_systems(Update, (a, b));
+        assert_eq!(run(&mut world), [1, 2]);
+
+        // Ordering against a set covers each of its systems; the
+        // unconstrained `b` keeps its place at the front.
+        world.add_systems(Update, (c.in_set(Guarded), d.before(a).after(Guarded)));
+        assert_eq!(run(&mut world), [2, 3, 4, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "cycle in system ordering among tiny_ecs::schedule::tests::a, tiny_ecs::schedule::tests::b")]
+    fn cycles_panic_naming_their_systems() {
+        let mut world = world(true);
+        world.add_systems(Update, (c, a.after(b), b.after(a)));
+        world.run_schedule(Update);
+    }
+
+    #[test]
+    #[should_panic(expected = "schedule::tests::a` runs after more than 4 others")]
+    fn orderings_beyond_the_system_limit_panic() {
+        let mut world = world(true);
+        world.add_system(Update, a.after(b).after(c).after(d).after(b).after(c));
+    }
+
+    #[test]
+    #[should_panic(expected = "no room for the orderings of system `tiny_ecs::schedule::tests::d`")]
+    fn orderings_beyond_the_pool_panic() {
+        let mut world = world(true);
+        world.add_systems(Update, (a, b, c, d).chain());
+    }
+
+    #[test]
+    #[should_panic(expected = "no room for schedule `tiny_ecs::schedule::PostUpdate`")]
+    fn schedules_beyond_the_table_panic() {
+        let mut world = world(true);
+        world.add_system(Update, a);
+        world.add_system(PostUpdate, b);
+    }
+}
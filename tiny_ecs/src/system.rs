@@ -1,6 +1,6 @@
 //! System param plumbing: marker traits, [`Res`]/[`ResMut`] wrappers, the
-//! [`Query`] iterator and its [`Fetch`] implementations, [`ScheduleLabel`],
-//! and the [`IntoSystem`] glue.
+//! [`Query`] iterator and its [`Fetch`] implementations (filters live in
+//! [`filter`](crate::filter)), [`ScheduleLabel`], and the [`IntoSystem`] glue.
 //!
 //! The concrete `World` is generated by the `define_world!` macro per app; it
 //! implements [`ColumnRef<T>`], [`ResourceRef<R>`] and [`CommandsRef`] for each
@@ -17,9 +17,15 @@
 
 use core::marker::PhantomData;
 
+use crate::bitset::{words_for, Ones, WORD_BITS};
+use crate::change_detection::{ComponentTicks, DetectChanges, DetectChangesMut, Mut, SystemTicks, Tick};
 use crate::column::ColumnOps;
 use crate::commands_buffer::CommandBuffer;
 use crate::entity::Entity;
+use crate::error::SystemFailed;
+use crate::event::EventsOps;
+use crate::filter::QueryFilter;
+use crate::removal_detection::RemovalLogOps;
 use crate::Resource;
 
 // =====================================================================
@@ -67,19 +73,40 @@ pub trait ResourceRef<R: 'static>: Sized {
     /// # Safety
     /// `world` must be a valid `*mut Self`.
     unsafe fn res_mut_raw(world: *mut Self) -> *mut R;
+    /// Returns a raw `*mut` pointer to the resource's change ticks.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn res_ticks_raw(world: *mut Self) -> *mut ComponentTicks;
 }
 
-/// Synchronous resource insertion.
+/// Resource insertion and removal.
 ///
-/// Implemented for each registered resource by `define_world!`; writing the
-/// `R` slot is disjoint from any column borrow, so `Commands::insert_resource`
-/// can run inside a system that also holds queries.
+/// Implemented for each registered resource by `define_world!`. Besides the
+/// `R` slot, each resource has a pending slot, disjoint from every borrow a
+/// system holds, so `Commands::insert_resource` can run inside a system that
+/// also reads or writes `R`.
 pub trait ResourceInsRef<R: 'static>: Sized {
     /// Installs `value` as the resource `R`.
     ///
     /// # Safety
     /// `world` must be a valid `*mut Self`.
     unsafe fn insert_resource(world: *mut Self, value: R);
+
+    /// Takes the resource `R` out of the world, leaving its slot empty.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`, and no `Res<R>` / `ResMut<R>`
+    /// borrow may be alive.
+    unsafe fn remove_resource(world: *mut Self) -> Option<R>;
+
+    /// Queues `value` as the resource `R`, or its removal for `None`, applied
+    /// on the next `World::flush_commands`; a later call replaces an earlier
+    /// one.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn enqueue_resource(world: *mut Self, value: Option<R>);
 }
 
 
@@ -88,19 +115,67 @@ pub trait HasResource<R: 'static>: ResourceRef<R> + ResourceInsRef<R> {}
 impl <R: 'static, W: ResourceRef<R> + ResourceInsRef<R>> HasResource<R> for W {}
 
 
-/// Typed spawn-queue access for a component `T`.
+/// Typed pending-queue access for a component `T`.
 ///
 /// Implemented for each registered component by `define_world!`. The `World`
-/// carries a per-component pending spawn queue (a distinct field from the
-/// column itself), so enqueuing a spawn while a `Query<&T>` borrows the column
-/// immutably stays sound.
-pub trait SpawnRef<T: 'static>: Sized {
-    /// Allocates an entity id and enqueues `value` to be inserted on the next
-    /// [`WorldApi::flush_commands`](crate::world::WorldApi::flush_commands).
+/// carries a per-component pending queue (a distinct field from the column
+/// itself), so enqueuing an insert while a `Query<&T>` borrows the column
+/// immutably stays sound. Queued inserts and removes of `T` are applied in the
+/// order they were issued on the next
+/// [`WorldApi::flush_commands`](crate::world::WorldApi::flush_commands).
+///
+/// The queue holds as many operations as the column holds components; each
+/// method panics, naming the component, when it is full.
+pub trait EntityCommandsRef<T: 'static>: Sized {
+    /// Enqueues inserting `value` on `entity` at the next flush. Stale
+    /// handles are ignored when the queue is flushed.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn enqueue_insert(world: *mut Self, entity: Entity, value: T);
+
+    /// Enqueues inserting the value built by `ctor` on `entity` at the next
+    /// flush, unless the entity already carries a `T` by then.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn enqueue_require(world: *mut Self, entity: Entity, ctor: fn() -> T);
+
+    /// Enqueues removing `T` from `entity` at the next flush.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn enqueue_remove(world: *mut Self, entity: Entity);
+}
+
+/// Entity-liveness access for the concrete `World`.
+///
+/// Implemented by `define_world!`; lets queries hand out [`Entity`] handles that
+/// carry the current generation of their slot, and lets deferred operations
+/// reject handles whose slot was recycled since they were issued.
+pub trait EntitiesRef: Sized {
+    /// Returns the handle currently occupying slot `index` (its generation is
+    /// the slot's live generation).
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn resolve_entity(world: *mut Self, index: u32) -> Entity;
+    /// Returns `true` when `entity` refers to the live occupant of its slot.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn entity_alive(world: *mut Self, entity: Entity) -> bool;
+    /// Returns one past the highest slot index ever handed out.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn entity_bound(world: *mut Self) -> usize;
+    /// Returns word `word` of the liveness bitset: bit `b` is set when slot
+    /// `word * 32 + b` holds a live entity (`0` past the entity budget).
     ///
     /// # Safety
     /// `world` must be a valid `*mut Self`.
-    unsafe fn enqueue_spawn(world: *mut Self, value: T) -> Entity;
+    unsafe fn alive_word(world: *mut Self, word: usize) -> u32;
 }
 
 /// Command-buffer access for the [`Commands`](crate::commands::Commands) param.
@@ -110,20 +185,115 @@ pub trait CommandsRef: Sized {
     /// # Safety
     /// `world` must be a valid `*mut Self`.
     unsafe fn commands_raw(world: *mut Self) -> *mut CommandBuffer;
+
+    /// Allocates an entity id for a deferred spawn; its components land on
+    /// the next flush. Touches only the entity allocator, so it may run while
+    /// the calling system holds query items.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn reserve_entity(world: *mut Self) -> Entity;
+}
+
+/// Removal-log access for a component `T` declared with a `removed: N` column
+/// option in `define_world!`, read by the
+/// [`RemovedComponents`](crate::removal_detection::RemovedComponents) param.
+pub trait RemovedRef<T: 'static>: Sized {
+    /// Returns a raw pointer to the removal log of `T`.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn removed_raw(world: *mut Self) -> *const dyn RemovalLogOps;
+}
+
+/// Event-buffer access for an event type `E` registered in the `events`
+/// section of `define_world!`, used by the
+/// [`EventWriter`](crate::event::EventWriter) and
+/// [`EventReader`](crate::event::EventReader) params.
+pub trait EventsRef<E: 'static>: Sized {
+    /// Returns a raw `*mut` pointer to the buffers of `E`.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn events_raw(world: *mut Self) -> *mut dyn EventsOps<E>;
+}
+
+/// Trigger access for the [`Trigger`](crate::observer::Trigger) param of hooks
+/// and observers.
+pub trait TriggerRef: Sized {
+    /// Returns the entity of the event currently (or last) being fired.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn trigger_entity(world: *mut Self) -> Entity;
+}
+
+/// Change-tick access for the concrete `World`.
+///
+/// Implemented by `define_world!`; lets `&mut T` fetches, the
+/// [`Added`](crate::filter::Added) / [`Changed`](crate::filter::Changed)
+/// filters and [`Res`]/[`ResMut`] compare against the running system's ticks.
+pub trait ChangeTicksRef: Sized {
+    /// Returns the ticks of the system currently running.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn system_ticks(world: *mut Self) -> SystemTicks;
+}
+
+/// Skip diagnostics for the [`Single`] param, and error reports for fallible
+/// systems.
+///
+/// Implemented by `define_world!`, which forwards every report to the handler
+/// installed with [`WorldApi::set_skip_handler`](crate::world::WorldApi::set_skip_handler)
+/// or [`WorldApi::set_error_handler`](crate::world::WorldApi::set_error_handler).
+pub trait DiagnosticsRef: Sized {
+    /// Reports that a system was skipped.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`.
+    unsafe fn report_skip(world: *mut Self, skipped: SystemSkipped);
+
+    /// Reports that a system returned an error.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`, with no system param borrow alive.
+    unsafe fn report_error(world: *mut Self, failed: SystemFailed<'_>);
+}
+
+/// Per-system state access for [`Local`], [`EventReader`](crate::event::EventReader)
+/// cursors and custom params.
+///
+/// Implemented by `define_world!`, forwarding to the world's [`SystemLocals`].
+pub trait LocalsRef: Sized {
+    /// Returns the value stored under `key`, creating it with `init` on the
+    /// system's first run. See [`SystemLocals::get_or_init`].
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut Self`, and the requirements of
+    /// [`SystemLocals::get_or_init`] hold.
+    unsafe fn local_raw<T: Send + 'static>(world: *mut Self, key: LocalKey, system: &str, init: fn() -> T) -> *mut T;
 }
 
 // =====================================================================
 // --- Resources --------------------------------------------------------
 
 /// A shared immutable view of a [`Resource`], mirroring Bevy's `Res`.
+///
+/// Reports through [`DetectChanges`] whether the resource was inserted or
+/// mutated since the system last ran.
 pub struct Res<'a, R: Resource> {
     /// The borrowed resource value.
     value: &'a R,
+    /// The resource's change ticks.
+    ticks: ComponentTicks,
+    /// The running system's ticks.
+    system: SystemTicks,
 }
 impl<'a, R: Resource> Res<'a, R> {
-    /// Creates a `Res` from a borrow.
-    pub const fn new(value: &'a R) -> Self {
-        Self { value }
+    /// Creates a `Res` from a borrow and the resource's change ticks.
+    pub const fn new(value: &'a R, ticks: ComponentTicks, system: SystemTicks) -> Self {
+        Self { value, ticks, system }
     }
 }
 impl<R: Resource> core::ops::Deref for Res<'_, R> {
@@ -132,51 +302,105 @@ impl<R: Resource> core::ops::Deref for Res<'_, R> {
         self.value
     }
 }
+impl<R: Resource> DetectChanges for Res<'_, R> {
+    fn is_added(&self) -> bool {
+        self.ticks.is_added(self.system.last_run, self.system.this_run)
+    }
+
+    fn is_changed(&self) -> bool {
+        self.ticks.is_changed(self.system.last_run, self.system.this_run)
+    }
+
+    fn last_changed(&self) -> Tick {
+        self.ticks.changed
+    }
+}
 
 /// An exclusive mutable view of a [`Resource`], mirroring Bevy's `ResMut`.
+///
+/// Marks the resource as changed only when mutably dereferenced.
 pub struct ResMut<'a, R: Resource> {
-    /// The borrowed resource value.
-    value: &'a mut R,
+    /// The borrowed resource value, with its change ticks.
+    value: Mut<'a, R>,
 }
 impl<'a, R: Resource> ResMut<'a, R> {
-    /// Creates a `ResMut` from a mutable borrow.
-    pub fn new(value: &'a mut R) -> Self {
-        Self { value }
+    /// Creates a `ResMut` from a mutable borrow and the resource's change
+    /// ticks.
+    pub fn new(value: &'a mut R, ticks: &'a mut ComponentTicks, system: SystemTicks) -> Self {
+        Self {
+            value: Mut::new(value, ticks, system),
+        }
     }
 }
 impl<R: Resource> core::ops::Deref for ResMut<'_, R> {
     type Target = R;
     fn deref(&self) -> &R {
-        self.value
+        &self.value
     }
 }
 impl<R: Resource> core::ops::DerefMut for ResMut<'_, R> {
     fn deref_mut(&mut self) -> &mut R {
-        self.value
+        &mut self.value
+    }
+}
+impl<R: Resource> DetectChanges for ResMut<'_, R> {
+    fn is_added(&self) -> bool {
+        self.value.is_added()
+    }
+
+    fn is_changed(&self) -> bool {
+        self.value.is_changed()
+    }
+
+    fn last_changed(&self) -> Tick {
+        self.value.last_changed()
+    }
+}
+impl<R: Resource> DetectChangesMut for ResMut<'_, R> {
+    type Inner = R;
+
+    fn set_changed(&mut self) {
+        self.value.set_changed();
+    }
+
+    fn bypass_change_detection(&mut self) -> &mut R {
+        self.value.bypass_change_detection()
     }
 }
 
 // =====================================================================
 // --- Query / Fetch ----------------------------------------------------
 
-/// A query over one or more component columns of a concrete `World` `W`.
+/// A query over one or more component columns of a concrete `World` `W`,
+/// optionally narrowed down by a [`QueryFilter`] `F` (Bevy's `Query<D, F>`).
 ///
 /// Constructed by the `#[system]` macro from a raw world pointer; iteration
 /// yields [`Fetch::Item`] with the world's lifetime `'w` for every entity that
-/// has all requested components. Supported parameter shapes mirror the Bevy
-/// patterns the game uses: `&T`, `&mut T`, `(Entity, &T)`, `(Entity, &mut T)`,
-/// and the two-component tuples `(&T, &U)`, `(&mut T, &mut U)`, `(&T, &mut U)`.
-pub struct Query<'w, F: Fetch<'w, W> + 'w, W> {
+/// has all requested components and passes the filter. Supported parameter
+/// shapes mirror the Bevy patterns the game uses: `&T`, `&mut T`, `Entity`,
+/// the optional `Option<&T>` / `Option<&mut T>` (which match every live
+/// entity), and tuples of up to eight of those, e.g. `(Entity, &mut T)`,
+/// `(&T, &mut U)` or `(Entity, &Debris, Option<&Velocity>)`. `&mut T` yields
+/// a [`Mut<T>`](crate::change_detection::Mut), which marks the component as
+/// changed once written through.
+///
+/// Iteration ANDs the occupancy bitsets of the participating columns and of
+/// the filter one word at a time (see [`Fetch::word`] and
+/// [`QueryFilter::word`]), so only entities that can match are probed.
+pub struct Query<'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W> = ()> {
     /// Opaque column-reference state captured at construction (raw pointers).
-    state: F::State,
-    /// Iteration bound = the minimum capacity of participating columns.
-    cap: usize,
-    /// The next index to probe during iteration.
-    idx: usize,
+    state: D::State,
+    /// The filter's state (occupancy-only column pointers).
+    filter: F::State,
+    /// Number of occupancy words covering the smallest `index_bound` of the
+    /// participating columns.
+    words: usize,
+    /// The owning world, used to validate entities passed to [`Query::get`].
+    world: *mut W,
     /// Ties `'w` to the world borrow.
     _life: PhantomData<&'w W>,
 }
-impl<'w, F: Fetch<'w, W> + 'w, W> Query<'w, F, W> {
+impl<'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W>> Query<'w, D, W, F> {
     /// Builds a query from a raw world pointer.
     ///
     /// # Safety
@@ -185,87 +409,369 @@ impl<'w, F: Fetch<'w, W> + 'w, W> Query<'w, F, W> {
     /// held queries derived from the same world.
     pub unsafe fn from_world(world: *mut W) -> Self {
         // SAFETY: caller guarantees `world` is valid for `'w`.
-        let (state, cap) = unsafe { F::make_state(world) };
+        let (state, cap) = unsafe { D::make_state(world) };
+        // SAFETY: as above; filters only read occupancy.
+        let filter = unsafe { F::make_state(world) };
         Self {
             state,
-            cap,
-            idx: 0,
+            filter,
+            words: words_for(cap),
+            world,
             _life: PhantomData,
         }
     }
 
     /// Returns the matching component(s) for the single entity that has the
     /// queried components, mirroring Bevy's `Query::single`.
-    pub fn single(&self) -> Result<F::Item<'w>, ()> {
-        for i in 0..self.cap {
-            if let Some(item) = F::fetch(&self.state, i) {
-                return Ok(item);
-            }
+    ///
+    /// Fails when no entity or more than one entity matches.
+    pub fn single(&self) -> Result<D::Item<'w>, QuerySingleError> {
+        let mut iter = self.iter();
+        let item = iter.next().ok_or(QuerySingleError::NoEntities)?;
+        match iter.next() {
+            Some(_) => Err(QuerySingleError::MultipleEntities),
+            None => Ok(item),
         }
-        Err(())
     }
 
     /// Returns a mutable view of the matching component(s) for the single
     /// entity, mirroring Bevy's `Query::single_mut`.
-    pub fn single_mut(&mut self) -> Result<F::Item<'w>, ()> {
-        for i in 0..self.cap {
-            if let Some(item) = F::fetch_mut(&mut self.state, i) {
-                return Ok(item);
-            }
+    ///
+    /// Fails when no entity or more than one entity matches.
+    pub fn single_mut(&mut self) -> Result<D::Item<'w>, QuerySingleError> {
+        let mut iter = self.iter_mut();
+        let item = iter.next().ok_or(QuerySingleError::NoEntities)?;
+        match iter.next() {
+            Some(_) => Err(QuerySingleError::MultipleEntities),
+            None => Ok(item),
         }
-        Err(())
+    }
+
+    /// Returns the number of entities holding every queried component and
+    /// passing the filter.
+    ///
+    /// Computed from the occupancy bitsets alone, one popcount per word,
+    /// without touching any component.
+    pub fn count(&self) -> usize {
+        (0..self.words).map(|word| self.matches_in(word)).sum()
+    }
+
+    /// Returns `true` when no entity matches the query.
+    pub fn is_empty(&self) -> bool {
+        (0..self.words).all(|word| self.matches_in(word) == 0)
     }
 
     /// Returns an iterator over the populated matches (immutable views).
-    pub fn iter(&self) -> QueryIter<'_, 'w, F, W> {
+    pub fn iter(&self) -> QueryIter<'_, 'w, D, W, F> {
         QueryIter {
             state: &self.state,
-            cap: self.cap,
-            idx: 0,
+            filter: &self.filter,
+            cursor: WordCursor::new(self.words),
             _life: PhantomData,
         }
     }
 
     /// Returns an iterator over the populated matches (mutable views).
-    pub fn iter_mut(&mut self) -> QueryIterMut<'_, 'w, F, W> {
+    pub fn iter_mut(&mut self) -> QueryIterMut<'_, 'w, D, W, F> {
         QueryIterMut {
             state: &mut self.state,
-            cap: self.cap,
-            idx: 0,
+            filter: &self.filter,
+            cursor: WordCursor::new(self.words),
             _life: PhantomData,
         }
     }
+
+    /// Returns an iterator over every unordered `K`-tuple of distinct
+    /// matching entities, mirroring Bevy's `Query::iter_combinations`.
+    ///
+    /// Tuples come out in ascending index order (`[a, b]` with `a < b`), each
+    /// exactly once; nothing is allocated. Like [`Query::iter`], this only
+    /// yields for read-only fetches.
+    pub fn iter_combinations<const K: usize>(&self) -> QueryCombinationIter<'_, 'w, D, W, F, K> {
+        QueryCombinationIter {
+            state: &self.state,
+            filter: &self.filter,
+            words: self.words,
+            cursor: CombinationCursor::new(),
+            _life: PhantomData,
+        }
+    }
+
+    /// Returns a lending iterator over every unordered `K`-tuple of distinct
+    /// matching entities with mutable access, mirroring Bevy's
+    /// `Query::iter_combinations_mut`.
+    ///
+    /// An entity shows up in many tuples, so items only live until the next
+    /// [`QueryCombinationIterMut::fetch_next`] call:
+    ///
+    /// ```ignore
+    /// let mut pairs = debris.iter_combinations_mut::<2>();
+    /// while let Some([a, b]) = pairs.fetch_next() { /* ... */ }
+    /// ```
+    pub fn iter_combinations_mut<const K: usize>(&mut self) -> QueryCombinationIterMut<'_, 'w, D, W, F, K> {
+        QueryCombinationIterMut {
+            state: &mut self.state,
+            filter: &self.filter,
+            words: self.words,
+            cursor: CombinationCursor::new(),
+            _life: PhantomData,
+        }
+    }
+
+    /// Returns the number of matching entities within occupancy word `word`.
+    fn matches_in(&self, word: usize) -> usize {
+        let mask = candidate_word::<D, W, F>(&self.state, &self.filter, word);
+        if F::WORD_EXACT {
+            return mask.count_ones() as usize;
+        }
+        Ones::new(mask, word).filter(|&i| F::matches(&self.filter, i)).count()
+    }
+
+    /// Returns `true` when entity `index` holds every queried component and
+    /// passes the filter.
+    fn matches_index(&self, index: usize) -> bool {
+        let word = index / WORD_BITS;
+        if word >= self.words {
+            return false;
+        }
+        let mask = candidate_word::<D, W, F>(&self.state, &self.filter, word);
+        mask & (1 << (index % WORD_BITS)) != 0 && (F::WORD_EXACT || F::matches(&self.filter, index))
+    }
+}
+
+/// Why [`Query::single`] / [`Query::single_mut`] found no unique match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuerySingleError {
+    /// No entity matches the query.
+    NoEntities,
+    /// More than one entity matches the query.
+    MultipleEntities,
+}
+
+/// Why a [`Query`] could not hand out the item of a given [`Entity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryEntityError {
+    /// The entity was despawned, or the handle is stale.
+    NoSuchEntity(Entity),
+    /// The entity lacks a queried component or fails the filter.
+    QueryDoesNotMatch(Entity),
+    /// The same entity was requested twice from [`Query::get_many_mut`].
+    AliasedMutability(Entity),
+}
+
+impl<'w, D: Fetch<'w, W> + 'w, W: EntitiesRef, F: QueryFilter<W>> Query<'w, D, W, F> {
+    /// Returns the read-only item of `entity`, mirroring Bevy's `Query::get`:
+    /// on queries containing `&mut T` it yields `&T` instead of [`Mut<T>`].
+    pub fn get(&self, entity: Entity) -> Result<D::ReadItem<'_>, QueryEntityError> {
+        let index = self.entity_index(entity)?;
+        D::fetch_read(&self.state, index).ok_or(QueryEntityError::QueryDoesNotMatch(entity))
+    }
+
+    /// Returns the mutable item of `entity`, mirroring Bevy's
+    /// `Query::get_mut`.
+    ///
+    /// The item borrows the query, so two of them never alias:
+    ///
+    /// ```compile_fail
+    /// # use tiny_ecs::prelude::*;
+    /// # #[derive(Component)]
+    /// # struct Pos(u32);
+    /// # define_world! {
+    /// #     struct World {
+    /// #         entities: 1,
+    /// #         schedules: 1,
+    /// #         components { pos: Pos [1], }
+    /// #     }
+    /// # }
+    /// #[system]
+    /// fn nudge(mut positions: Query<(Entity, &mut Pos)>) {
+    ///     let Some((entity, _)) = positions.iter().next() else { return };
+    ///     let (_, mut first) = positions.get_mut(entity).expect("matches");
+    ///     let (_, mut second) = positions.get_mut(entity).expect("matches");
+    ///     first.0 += 1;
+    ///     second.0 += 1;
+    /// }
+    /// ```
+    pub fn get_mut(&mut self, entity: Entity) -> Result<D::Item<'_>, QueryEntityError> {
+        let index = self.entity_index(entity)?;
+        D::fetch_mut(&mut self.state, index)
+            .map(D::shrink)
+            .ok_or(QueryEntityError::QueryDoesNotMatch(entity))
+    }
+
+    /// Returns `true` when `entity` is alive and matches the query.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entity_index(entity).is_ok()
+    }
+
+    /// Returns the mutable items of `K` distinct entities at once, mirroring
+    /// Bevy's `Query::get_many_mut`.
+    ///
+    /// Fails with [`QueryEntityError::AliasedMutability`] when an entity is
+    /// listed twice, so the returned items never alias; like
+    /// [`Query::get_mut`]'s, they borrow the query.
+    pub fn get_many_mut<const K: usize>(&mut self, entities: [Entity; K]) -> Result<[D::Item<'_>; K], QueryEntityError> {
+        // Reject duplicates (by slot, so stale twins are caught too) and
+        // unmatched entities before fetching anything.
+        for (i, entity) in entities.iter().enumerate() {
+            if entities[..i].iter().any(|other| other.index() == entity.index()) {
+                return Err(QueryEntityError::AliasedMutability(*entity));
+            }
+            self.entity_index(*entity)?;
+        }
+
+        let indices = entities.map(|entity| entity.index() as usize);
+        fetch_many_mut::<D, W, K>(&mut self.state, &indices)
+            .map(|items| items.map(D::shrink))
+            .map_err(|i| QueryEntityError::QueryDoesNotMatch(entities[i]))
+    }
+
+    /// Resolves `entity` to its slot index when it is alive and matches.
+    fn entity_index(&self, entity: Entity) -> Result<usize, QueryEntityError> {
+        // SAFETY: the world outlives the query.
+        if !unsafe { W::entity_alive(self.world, entity) } {
+            return Err(QueryEntityError::NoSuchEntity(entity));
+        }
+        let index = entity.index() as usize;
+        if !self.matches_index(index) {
+            return Err(QueryEntityError::QueryDoesNotMatch(entity));
+        }
+        Ok(index)
+    }
+}
+
+/// The item of the one entity matching a query, mirroring Bevy's `Single`.
+///
+/// As a `#[system]` parameter, `Single<D>` / `Single<D, F>` runs the query
+/// and, unless exactly one entity matches, skips the whole system (reporting a
+/// [`SystemSkipped`] diagnostic) instead of running it. Dereferences to the
+/// item:
+///
+/// ```ignore
+/// #[system]
+/// fn collision(player: Single<&Player>, debris: Query<&Debris>) {
+///     let hit = debris.iter().any(|piece| piece.x == player.x);
+/// }
+/// ```
+pub struct Single<'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W> = ()> {
+    /// The matching item.
+    item: D::Item<'w>,
+    /// Ties `'w` to the world borrow.
+    _life: PhantomData<&'w W>,
+    /// Keeps the filter type in the signature.
+    _filter: PhantomData<F>,
+}
+impl<'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W>> Single<'w, D, W, F> {
+    /// Wraps the item of the single matching entity.
+    pub fn new(item: D::Item<'w>) -> Self {
+        Self {
+            item,
+            _life: PhantomData,
+            _filter: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped item, e.g. to destructure a tuple of `&mut`.
+    pub fn into_inner(self) -> D::Item<'w> {
+        self.item
+    }
+}
+impl<'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W>> core::ops::Deref for Single<'w, D, W, F> {
+    type Target = D::Item<'w>;
+    fn deref(&self) -> &Self::Target {
+        &self.item
+    }
+}
+impl<'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W>> core::ops::DerefMut for Single<'w, D, W, F> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.item
+    }
+}
+
+/// Diagnostic emitted when a system is skipped because one of its [`Single`]
+/// parameters did not match exactly one entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemSkipped {
+    /// The system function's name.
+    pub system: &'static str,
+    /// The name of the offending parameter.
+    pub param: &'static str,
+    /// Why the parameter did not match.
+    pub error: QuerySingleError,
+}
+
+/// ANDs the fetch's and the filter's masks of occupancy word `word`.
+fn candidate_word<'w, D: Fetch<'w, W>, W, F: QueryFilter<W>>(state: &D::State, filter: &F::State, word: usize) -> u32 {
+    D::word(state, word) & F::word(filter, word)
+}
+
+/// Walks the candidate entity indices of a query, one ANDed occupancy word at
+/// a time.
+struct WordCursor {
+    /// Total number of words to scan.
+    words: usize,
+    /// The next word to load once `ones` runs dry.
+    word: usize,
+    /// The remaining candidates of the current word.
+    ones: Ones,
+}
+impl WordCursor {
+    /// Creates a cursor positioned before the first word.
+    const fn new(words: usize) -> Self {
+        Self {
+            words,
+            word: 0,
+            ones: Ones::new(0, 0),
+        }
+    }
+
+    /// Returns the next candidate index, loading further words through `load`
+    /// (which yields the candidate mask of a given word).
+    fn next_index(&mut self, mut load: impl FnMut(usize) -> u32) -> Option<usize> {
+        loop {
+            if let Some(index) = self.ones.next() {
+                return Some(index);
+            }
+            if self.word >= self.words {
+                return None;
+            }
+            self.ones = Ones::new(load(self.word), self.word);
+            self.word += 1;
+        }
+    }
 }
 
 /// Immutable iterator produced by [`Query::iter`] / `for x in &query`.
 ///
 /// Borrows the query's state immutably; items carry the world lifetime `'w`.
-pub struct QueryIter<'s, 'w, F: Fetch<'w, W> + 'w, W> {
+pub struct QueryIter<'s, 'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W> = ()> {
     /// Borrow of the query's state.
-    state: &'s F::State,
-    /// Iteration bound.
-    cap: usize,
-    /// The next index to probe.
-    idx: usize,
+    state: &'s D::State,
+    /// Borrow of the query's filter state.
+    filter: &'s F::State,
+    /// Position within the candidate indices.
+    cursor: WordCursor,
     /// Ties `'w` to the world.
     _life: PhantomData<&'w W>,
 }
-impl<'s, 'w, F: Fetch<'w, W> + 'w, W> Iterator for QueryIter<'s, 'w, F, W> {
-    type Item = F::Item<'w>;
+impl<'s, 'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W>> Iterator for QueryIter<'s, 'w, D, W, F> {
+    type Item = D::Item<'w>;
     fn next(&mut self) -> Option<Self::Item> {
-        while self.idx < self.cap {
-            let i = self.idx;
-            self.idx += 1;
-            if let Some(item) = F::fetch(self.state, i) {
+        let (state, filter) = (self.state, self.filter);
+        while let Some(i) = self.cursor.next_index(|word| candidate_word::<D, W, F>(state, filter, word)) {
+            if !F::WORD_EXACT && !F::matches(filter, i) {
+                continue;
+            }
+            if let Some(item) = D::fetch(state, i) {
                 return Some(item);
             }
         }
         None
     }
 }
-impl<'s, 'w, F: Fetch<'w, W> + 'w, W> IntoIterator for &'s Query<'w, F, W> {
-    type Item = F::Item<'w>;
-    type IntoIter = QueryIter<'s, 'w, F, W>;
+impl<'s, 'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W>> IntoIterator for &'s Query<'w, D, W, F> {
+    type Item = D::Item<'w>;
+    type IntoIter = QueryIter<'s, 'w, D, W, F>;
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
@@ -276,37 +782,189 @@ impl<'s, 'w, F: Fetch<'w, W> + 'w, W> IntoIterator for &'s Query<'w, F, W> {
 /// Borrows the query's state mutably for the cursor, but yielded items carry
 /// the world lifetime `'w` (reconstructed from raw column pointers), so each
 /// `next()` call returns a fresh non-overlapping `&'w mut T`.
-pub struct QueryIterMut<'s, 'w, F: Fetch<'w, W> + 'w, W> {
-    /// Mutable borrow of the query's state (only the cursor mutates).
-    state: &'s mut F::State,
-    /// Iteration bound.
-    cap: usize,
-    /// The next index to probe.
-    idx: usize,
+pub struct QueryIterMut<'s, 'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W> = ()> {
+    /// Mutable borrow of the query's state.
+    state: &'s mut D::State,
+    /// Borrow of the query's filter state.
+    filter: &'s F::State,
+    /// Position within the candidate indices.
+    cursor: WordCursor,
     /// Ties `'w` to the world.
     _life: PhantomData<&'w W>,
 }
-impl<'s, 'w, F: Fetch<'w, W> + 'w, W> Iterator for QueryIterMut<'s, 'w, F, W> {
-    type Item = F::Item<'w>;
+impl<'s, 'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W>> Iterator for QueryIterMut<'s, 'w, D, W, F> {
+    type Item = D::Item<'w>;
     fn next(&mut self) -> Option<Self::Item> {
-        while self.idx < self.cap {
-            let i = self.idx;
-            self.idx += 1;
-            if let Some(item) = F::fetch_mut(self.state, i) {
+        let filter = self.filter;
+        loop {
+            let state = &*self.state;
+            let i = self.cursor.next_index(|word| candidate_word::<D, W, F>(state, filter, word))?;
+            if !F::WORD_EXACT && !F::matches(filter, i) {
+                continue;
+            }
+            if let Some(item) = D::fetch_mut(self.state, i) {
                 return Some(item);
             }
         }
-        None
     }
 }
-impl<'s, 'w, F: Fetch<'w, W> + 'w, W> IntoIterator for &'s mut Query<'w, F, W> {
-    type Item = F::Item<'w>;
-    type IntoIter = QueryIterMut<'s, 'w, F, W>;
+impl<'s, 'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W>> IntoIterator for &'s mut Query<'w, D, W, F> {
+    type Item = D::Item<'w>;
+    type IntoIter = QueryIterMut<'s, 'w, D, W, F>;
     fn into_iter(self) -> Self::IntoIter {
         self.iter_mut()
     }
 }
 
+/// Mutably fetches the items at `K` distinct indices; `Err` carries the
+/// position of the first one that does not yield.
+fn fetch_many_mut<'w, D: Fetch<'w, W> + 'w, W, const K: usize>(state: &mut D::State, indices: &[usize; K]) -> Result<[D::Item<'w>; K], usize> {
+    let items: [Option<D::Item<'w>>; K] = core::array::from_fn(|i| D::fetch_mut(state, indices[i]));
+    if let Some(missing) = items.iter().position(Option::is_none) {
+        return Err(missing);
+    }
+    Ok(items.map(|item| item.expect("checked above")))
+}
+
+/// Returns the first index at or after `from` that passes both the fetch
+/// and the filter.
+fn next_match<'w, D: Fetch<'w, W>, W, F: QueryFilter<W>>(state: &D::State, filter: &F::State, words: usize, from: usize) -> Option<usize> {
+    // Mask off the bits below `from` in its word only.
+    let mut low = u32::MAX << (from % WORD_BITS);
+    for word in from / WORD_BITS..words {
+        let mask = candidate_word::<D, W, F>(state, filter, word) & low;
+        let mut ones = Ones::new(mask, word);
+        if let Some(index) = ones.find(|&i| F::WORD_EXACT || F::matches(filter, i)) {
+            return Some(index);
+        }
+        low = u32::MAX;
+    }
+    None
+}
+
+/// Walks the strictly ascending `K`-tuples of matching entity indices, in
+/// lexicographic order.
+struct CombinationCursor<const K: usize> {
+    /// The current tuple.
+    indices: [usize; K],
+    /// Whether the first tuple has been produced.
+    started: bool,
+    /// Whether every tuple has been produced.
+    done: bool,
+}
+impl<const K: usize> CombinationCursor<K> {
+    /// Creates a cursor positioned before the first tuple.
+    const fn new() -> Self {
+        Self {
+            indices: [0; K],
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Moves to the next tuple; `next_match(from)` yields the first matching
+    /// index at or after `from`.
+    fn advance(&mut self, mut next_match: impl FnMut(usize) -> Option<usize>) -> Option<[usize; K]> {
+        if self.done || K == 0 {
+            self.done = true;
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            if self.fill(0, &mut next_match) {
+                return Some(self.indices);
+            }
+            self.done = true;
+            return None;
+        }
+
+        // Bump the rightmost position that can still move, then refill every
+        // position after it with the smallest matches that keep the order.
+        for pos in (0..K).rev() {
+            if let Some(index) = next_match(self.indices[pos] + 1) {
+                self.indices[pos] = index;
+                if self.fill(pos + 1, &mut next_match) {
+                    return Some(self.indices);
+                }
+            }
+        }
+        self.done = true;
+        None
+    }
+
+    /// Fills positions `from..K` with ascending matches; `false` when the
+    /// matches run out.
+    fn fill(&mut self, from: usize, next_match: &mut impl FnMut(usize) -> Option<usize>) -> bool {
+        for pos in from..K {
+            let start = if pos == 0 { 0 } else { self.indices[pos - 1] + 1 };
+            match next_match(start) {
+                Some(index) => self.indices[pos] = index,
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Iterator produced by [`Query::iter_combinations`], yielding `[Item; K]`
+/// for every unordered `K`-tuple of distinct matching entities.
+pub struct QueryCombinationIter<'s, 'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W>, const K: usize> {
+    /// Borrow of the query's state.
+    state: &'s D::State,
+    /// Borrow of the query's filter state.
+    filter: &'s F::State,
+    /// Number of occupancy words to scan.
+    words: usize,
+    /// Position within the tuples.
+    cursor: CombinationCursor<K>,
+    /// Ties `'w` to the world.
+    _life: PhantomData<&'w W>,
+}
+impl<'s, 'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W>, const K: usize> Iterator for QueryCombinationIter<'s, 'w, D, W, F, K> {
+    type Item = [D::Item<'w>; K];
+    fn next(&mut self) -> Option<Self::Item> {
+        let (state, filter, words) = (self.state, self.filter, self.words);
+        loop {
+            let indices = self.cursor.advance(|from| next_match::<D, W, F>(state, filter, words, from))?;
+            let items: [Option<D::Item<'w>>; K] = core::array::from_fn(|i| D::fetch(state, indices[i]));
+            if items.iter().all(Option::is_some) {
+                return Some(items.map(|item| item.expect("checked above")));
+            }
+        }
+    }
+}
+
+/// Lending iterator produced by [`Query::iter_combinations_mut`].
+///
+/// Not an [`Iterator`]: the same entity appears in several tuples, so each
+/// tuple borrows the iterator until the next [`fetch_next`](Self::fetch_next).
+pub struct QueryCombinationIterMut<'s, 'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W>, const K: usize> {
+    /// Mutable borrow of the query's state.
+    state: &'s mut D::State,
+    /// Borrow of the query's filter state.
+    filter: &'s F::State,
+    /// Number of occupancy words to scan.
+    words: usize,
+    /// Position within the tuples.
+    cursor: CombinationCursor<K>,
+    /// Ties `'w` to the world.
+    _life: PhantomData<&'w W>,
+}
+impl<'s, 'w, D: Fetch<'w, W> + 'w, W, F: QueryFilter<W>, const K: usize> QueryCombinationIterMut<'s, 'w, D, W, F, K> {
+    /// Returns the next tuple, borrowing the iterator until the next call.
+    pub fn fetch_next(&mut self) -> Option<[D::Item<'_>; K]> {
+        let (filter, words) = (self.filter, self.words);
+        loop {
+            let state = &*self.state;
+            let indices = self.cursor.advance(|from| next_match::<D, W, F>(state, filter, words, from))?;
+            // The indices are distinct, so the items never alias each other.
+            if let Ok(items) = fetch_many_mut::<D, W, K>(self.state, &indices) {
+                return Some(items.map(D::shrink));
+            }
+        }
+    }
+}
+
 /// How a [`Query`] obtains row data from the concrete `World` `W`.
 ///
 /// Each supported query parameter shape implements `Fetch`; the impl owns raw
@@ -323,24 +981,52 @@ pub unsafe trait Fetch<'w, W> {
     type Item<'i>
     where
         Self: 'w;
+    /// The read-only view of an item, handed out by [`Query::get`]: `&T` in
+    /// place of `&mut T`, the item itself for read-only fetches.
+    type ReadItem<'i>
+    where
+        Self: 'w,
+        'w: 'i;
 
     /// Builds the state from a raw world pointer; returns it plus the
-    /// iteration capacity (the minimum column capacity participating).
+    /// iteration bound (the smallest `index_bound` of participating columns).
     ///
     /// # Safety
     /// `world` must point to a valid `W` for `'w`.
     unsafe fn make_state(world: *mut W) -> (Self::State, usize);
 
+    /// Returns the candidate mask of occupancy word `word`: bit `b` is set
+    /// when entity `word * 32 + b` holds every column the fetch reads.
+    fn word(state: &Self::State, word: usize) -> u32;
+
     /// Fetches the item at `index`, if the entity matches.
     fn fetch(state: &Self::State, index: usize) -> Option<Self::Item<'w>>;
     /// Fetches the mutable item at `index`, if the entity matches.
     fn fetch_mut(state: &mut Self::State, index: usize) -> Option<Self::Item<'w>> {
         Self::fetch(state, index)
     }
+    /// Fetches the read-only item at `index` for as long as `state` is
+    /// borrowed, if the entity matches.
+    fn fetch_read<'s>(state: &'s Self::State, index: usize) -> Option<Self::ReadItem<'s>>
+    where
+        'w: 's;
+
+    /// Shortens an item's lifetime from `'w` to `'a`, so lending iterators
+    /// such as [`QueryCombinationIterMut`] can tie items to their own borrow.
+    fn shrink<'a>(item: Self::Item<'w>) -> Self::Item<'a>
+    where
+        'w: 'a;
 }
 
 // --------------------- Concrete Fetch implementations ----------------
 
+/// Reads occupancy word `word` of the column behind `col` (`0` past its end).
+pub(crate) fn column_word<T>(col: *const dyn ColumnOps<T>, word: usize) -> u32 {
+    // SAFETY: fetch and filter states only hold column pointers valid for `'w`.
+    let col: &dyn ColumnOps<T> = unsafe { &*col };
+    col.occupancy_word(word)
+}
+
 // `&T` — immutable single-component query.
 unsafe impl<'w, T: 'static, W: ColumnRef<T>> Fetch<'w, W> for &'w T {
     type State = *const dyn ColumnOps<T>;
@@ -348,15 +1034,24 @@ unsafe impl<'w, T: 'static, W: ColumnRef<T>> Fetch<'w, W> for &'w T {
         = &'i T
     where
         Self: 'w;
+    type ReadItem<'i>
+        = &'i T
+    where
+        Self: 'w,
+        'w: 'i;
 
     unsafe fn make_state(world: *mut W) -> (Self::State, usize) {
         // SAFETY: caller guarantees `world` is valid for `'w`.
         let col = unsafe { <W as ColumnRef<T>>::col_ref_raw(world) };
-        // SAFETY: reading capacity is immutable and `col` is valid for `'w`.
-        let cap = unsafe { (&*col).capacity() };
+        // SAFETY: reading the index bound is immutable and `col` is valid for `'w`.
+        let cap = unsafe { (&*col).index_bound() };
         (col, cap)
     }
 
+    fn word(state: &Self::State, word: usize) -> u32 {
+        column_word(*state, word)
+    }
+
     fn fetch(state: &Self::State, index: usize) -> Option<Self::Item<'w>> {
         // Copy the raw pointer out without keeping a borrow of `state`.
         let ptr: *const dyn ColumnOps<T> = *state;
@@ -364,21 +1059,48 @@ unsafe impl<'w, T: 'static, W: ColumnRef<T>> Fetch<'w, W> for &'w T {
         let col: &'w dyn ColumnOps<T> = unsafe { &*ptr };
         col.get(index)
     }
+
+    fn fetch_read<'s>(state: &'s Self::State, index: usize) -> Option<Self::ReadItem<'s>>
+    where
+        'w: 's,
+    {
+        // SAFETY: the column is valid for `'w`, which outlives `'s`.
+        let col: &'s dyn ColumnOps<T> = unsafe { &**state };
+        col.get(index)
+    }
+
+    fn shrink<'a>(item: Self::Item<'w>) -> Self::Item<'a>
+    where
+        'w: 'a,
+    {
+        item
+    }
 }
 
-// `&mut T` — mutable single-component query.
-unsafe impl<'w, T: 'static, W: ColumnRef<T>> Fetch<'w, W> for &'w mut T {
-    type State = *mut dyn ColumnOps<T>;
+// `&mut T` — mutable single-component query, yielding [`Mut`] so only
+// actual writes mark the component as changed.
+unsafe impl<'w, T: 'static, W: ColumnRef<T> + ChangeTicksRef> Fetch<'w, W> for &'w mut T {
+    type State = (*mut dyn ColumnOps<T>, SystemTicks);
     type Item<'i>
-        = &'i mut T
+        = Mut<'i, T>
     where
         Self: 'w;
+    type ReadItem<'i>
+        = &'i T
+    where
+        Self: 'w,
+        'w: 'i;
 
     unsafe fn make_state(world: *mut W) -> (Self::State, usize) {
         // SAFETY: caller guarantees `world` is valid for `'w`.
         let col = unsafe { <W as ColumnRef<T>>::col_mut_raw(world) };
-        let cap = unsafe { (&*col).capacity() };
-        (col, cap)
+        let cap = unsafe { (&*col).index_bound() };
+        let system = unsafe { W::system_ticks(world) };
+        ((col, system), cap)
+    }
+
+    fn word(state: &Self::State, word: usize) -> u32 {
+        column_word(state.0, word)
     }
 
     fn fetch(_state: &Self::State, _index: usize) -> Option<Self::Item<'w>> {
@@ -389,153 +1111,459 @@ unsafe impl<'w, T: 'static, W: ColumnRef<T>> Fetch<'w, W> for &'w mut T {
 
     fn fetch_mut(state: &mut Self::State, index: usize) -> Option<Self::Item<'w>> {
         // Read the raw pointer out, releasing the borrow of `state`.
-        let ptr: *mut dyn ColumnOps<T> = *state;
+        let (ptr, system): (*mut dyn ColumnOps<T>, SystemTicks) = *state;
         // SAFETY: valid for `'w`; slots at distinct indices are disjoint.
         let col: &'w mut dyn ColumnOps<T> = unsafe { &mut *ptr };
-        col.get_mut(index)
+        let (value, ticks) = col.get_mut_with_ticks(index)?;
+        Some(Mut::new(value, ticks, system))
+    }
+
+    fn fetch_read<'s>(state: &'s Self::State, index: usize) -> Option<Self::ReadItem<'s>>
+    where
+        'w: 's,
+    {
+        // Reading through the write pointer is fine: the query is borrowed
+        // immutably for `'s`, so no `Mut` item can be live alongside.
+        // SAFETY: the column is valid for `'w`, which outlives `'s`.
+        let col: &'s dyn ColumnOps<T> = unsafe { &*state.0 };
+        col.get(index)
     }
-}
 
-/// Paired column-pointer state for two-component queries.
-pub struct PairState<A, B> {
-    /// First column state.
-    pub a: A,
-    /// Second column state.
-    pub b: B,
+    fn shrink<'a>(item: Self::Item<'w>) -> Self::Item<'a>
+    where
+        'w: 'a,
+    {
+        item
+    }
 }
 
-// `(&T, &U)` — two immutable components.
-unsafe impl<'w, T: 'static, U: 'static, W: ColumnRef<T> + ColumnRef<U>> Fetch<'w, W>
-    for (&'w T, &'w U)
-{
-    type State = PairState<*const dyn ColumnOps<T>, *const dyn ColumnOps<U>>;
+// `Entity` — the handle of every live entity, at its slot's generation.
+unsafe impl<'w, W: EntitiesRef> Fetch<'w, W> for Entity {
+    type State = *mut W;
     type Item<'i>
-        = (&'i T, &'i U)
+        = Entity
     where
         Self: 'w;
+    type ReadItem<'i>
+        = Entity
+    where
+        Self: 'w,
+        'w: 'i;
 
     unsafe fn make_state(world: *mut W) -> (Self::State, usize) {
-        let a = unsafe { <W as ColumnRef<T>>::col_ref_raw(world) };
-        let b = unsafe { <W as ColumnRef<U>>::col_ref_raw(world) };
-        let cap = unsafe { (&*a).capacity().min((&*b).capacity()) };
-        (PairState { a, b }, cap)
+        // SAFETY: caller guarantees `world` is valid for `'w`.
+        let bound = unsafe { W::entity_bound(world) };
+        (world, bound)
+    }
+
+    fn word(state: &Self::State, word: usize) -> u32 {
+        // SAFETY: the world outlives the query state.
+        unsafe { W::alive_word(*state, word) }
     }
 
     fn fetch(state: &Self::State, index: usize) -> Option<Self::Item<'w>> {
-        let col_a: &'w dyn ColumnOps<T> = unsafe { &*state.a };
-        let col_b: &'w dyn ColumnOps<U> = unsafe { &*state.b };
-        Some((col_a.get(index)?, col_b.get(index)?))
+        // SAFETY: the world outlives the query state.
+        Some(unsafe { W::resolve_entity(*state, index as u32) })
+    }
+
+    fn fetch_read<'s>(state: &'s Self::State, index: usize) -> Option<Self::ReadItem<'s>>
+    where
+        'w: 's,
+    {
+        Self::fetch(state, index)
+    }
+
+    fn shrink<'a>(item: Self::Item<'w>) -> Self::Item<'a>
+    where
+        'w: 'a,
+    {
+        item
     }
 }
 
-// `(&mut T, &mut U)` — two mutable components of distinct types.
-unsafe impl<'w, T: 'static, U: 'static, W: ColumnRef<T> + ColumnRef<U>> Fetch<'w, W>
-    for (&'w mut T, &'w mut U)
-{
-    type State = PairState<*mut dyn ColumnOps<T>, *mut dyn ColumnOps<U>>;
+/// Column-pointer state for the optional fetches: the column is probed per
+/// entity, while the world's liveness bitset drives iteration.
+pub struct OptionState<C, W> {
+    /// The (possibly empty) column pointer.
+    pub col: C,
+    /// The owning world, used for its liveness bitset.
+    pub world: *mut W,
+}
+
+// `Option<&T>` — every live entity, with its `T` when it has one.
+unsafe impl<'w, T: 'static, W: ColumnRef<T> + EntitiesRef> Fetch<'w, W> for Option<&'w T> {
+    type State = OptionState<*const dyn ColumnOps<T>, W>;
     type Item<'i>
-        = (&'i mut T, &'i mut U)
+        = Option<&'i T>
     where
         Self: 'w;
+    type ReadItem<'i>
+        = Option<&'i T>
+    where
+        Self: 'w,
+        'w: 'i;
 
     unsafe fn make_state(world: *mut W) -> (Self::State, usize) {
-        let a = unsafe { <W as ColumnRef<T>>::col_mut_raw(world) };
-        let b = unsafe { <W as ColumnRef<U>>::col_mut_raw(world) };
-        let cap = unsafe { (&*a).capacity().min((&*b).capacity()) };
-        (PairState { a, b }, cap)
+        // SAFETY: caller guarantees `world` is valid for `'w`.
+        let col = unsafe { <W as ColumnRef<T>>::col_ref_raw(world) };
+        let bound = unsafe { W::entity_bound(world) };
+        (OptionState { col, world }, bound)
     }
 
-    fn fetch(_state: &Self::State, _index: usize) -> Option<Self::Item<'w>> {
-        None
+    fn word(state: &Self::State, word: usize) -> u32 {
+        // An absent `T` does not exclude the entity, only liveness does.
+        // SAFETY: the world outlives the query state.
+        unsafe { W::alive_word(state.world, word) }
     }
 
-    fn fetch_mut(state: &mut Self::State, index: usize) -> Option<Self::Item<'w>> {
-        let col_a: &'w mut dyn ColumnOps<T> = unsafe { &mut *state.a };
-        let col_b: &'w mut dyn ColumnOps<U> = unsafe { &mut *state.b };
-        Some((col_a.get_mut(index)?, col_b.get_mut(index)?))
+    fn fetch(state: &Self::State, index: usize) -> Option<Self::Item<'w>> {
+        // SAFETY: the column is valid for `'w`; each slot is disjoint.
+        let col: &'w dyn ColumnOps<T> = unsafe { &*state.col };
+        Some(col.get(index))
+    }
+
+    fn fetch_read<'s>(state: &'s Self::State, index: usize) -> Option<Self::ReadItem<'s>>
+    where
+        'w: 's,
+    {
+        // SAFETY: the column is valid for `'w`, which outlives `'s`.
+        let col: &'s dyn ColumnOps<T> = unsafe { &*state.col };
+        Some(col.get(index))
+    }
+
+    fn shrink<'a>(item: Self::Item<'w>) -> Self::Item<'a>
+    where
+        'w: 'a,
+    {
+        item
     }
 }
 
-// `(&T, &mut U)` — mixed mutability two-component query.
-unsafe impl<'w, T: 'static, U: 'static, W: ColumnRef<T> + ColumnRef<U>> Fetch<'w, W>
-    for (&'w T, &'w mut U)
-{
-    type State = PairState<*const dyn ColumnOps<T>, *mut dyn ColumnOps<U>>;
+// `Option<&mut T>` — every live entity, with mutable access to its `T`.
+unsafe impl<'w, T: 'static, W: ColumnRef<T> + EntitiesRef + ChangeTicksRef> Fetch<'w, W> for Option<&'w mut T> {
+    type State = OptionState<*mut dyn ColumnOps<T>, W>;
     type Item<'i>
-        = (&'i T, &'i mut U)
+        = Option<Mut<'i, T>>
     where
         Self: 'w;
+    type ReadItem<'i>
+        = Option<&'i T>
+    where
+        Self: 'w,
+        'w: 'i;
 
     unsafe fn make_state(world: *mut W) -> (Self::State, usize) {
-        let a = unsafe { <W as ColumnRef<T>>::col_ref_raw(world) };
-        let b = unsafe { <W as ColumnRef<U>>::col_mut_raw(world) };
-        let cap = unsafe { (&*a).capacity().min((&*b).capacity()) };
-        (PairState { a, b }, cap)
+        // SAFETY: caller guarantees `world` is valid for `'w`.
+        let col = unsafe { <W as ColumnRef<T>>::col_mut_raw(world) };
+        let bound = unsafe { W::entity_bound(world) };
+        (OptionState { col, world }, bound)
+    }
+
+    fn word(state: &Self::State, word: usize) -> u32 {
+        // SAFETY: the world outlives the query state.
+        unsafe { W::alive_word(state.world, word) }
     }
 
     fn fetch(_state: &Self::State, _index: usize) -> Option<Self::Item<'w>> {
+        // Mirrors `&mut T`: no mutable item on the immutable path.
         None
     }
 
     fn fetch_mut(state: &mut Self::State, index: usize) -> Option<Self::Item<'w>> {
-        let col_a: &'w dyn ColumnOps<T> = unsafe { &*state.a };
-        let col_b: &'w mut dyn ColumnOps<U> = unsafe { &mut *state.b };
-        Some((col_a.get(index)?, col_b.get_mut(index)?))
+        // SAFETY: valid for `'w`; slots at distinct indices are disjoint.
+        let col: &'w mut dyn ColumnOps<T> = unsafe { &mut *state.col };
+        // SAFETY: the world outlives the query state.
+        let system = unsafe { W::system_ticks(state.world) };
+        Some(col.get_mut_with_ticks(index).map(|(value, ticks)| Mut::new(value, ticks, system)))
     }
+
+    fn fetch_read<'s>(state: &'s Self::State, index: usize) -> Option<Self::ReadItem<'s>>
+    where
+        'w: 's,
+    {
+        // SAFETY: the column is valid for `'w`, which outlives `'s`; the
+        // immutable query borrow rules out a live `Mut` item.
+        let col: &'s dyn ColumnOps<T> = unsafe { &*state.col };
+        Some(col.get(index))
+    }
+
+    fn shrink<'a>(item: Self::Item<'w>) -> Self::Item<'a>
+    where
+        'w: 'a,
+    {
+        item
+    }
+}
+
+/// Implements [`Fetch`] for a tuple of fetches, e.g. `(Entity, &mut T)`,
+/// `(&T, &mut U, Option<&V>)`. An entity matches when it matches every
+/// member; the members' columns must be distinct types.
+macro_rules! impl_fetch_tuple {
+    ($($f:ident),+) => {
+        unsafe impl<'w, W, $($f: Fetch<'w, W> + 'w),+> Fetch<'w, W> for ($($f,)+) {
+            type State = ($($f::State,)+);
+            type Item<'i>
+                = ($($f::Item<'i>,)+)
+            where
+                Self: 'w;
+            type ReadItem<'i>
+                = ($($f::ReadItem<'i>,)+)
+            where
+                Self: 'w,
+                'w: 'i;
+
+            #[allow(non_snake_case)]
+            unsafe fn make_state(world: *mut W) -> (Self::State, usize) {
+                let mut cap = usize::MAX;
+                // SAFETY: caller guarantees `world` is valid for `'w`.
+                $(let $f = unsafe { $f::make_state(world) }; cap = cap.min($f.1);)+
+                (($($f.0,)+), cap)
+            }
+
+            #[allow(non_snake_case)]
+            fn word(state: &Self::State, word: usize) -> u32 {
+                let ($($f,)+) = state;
+                u32::MAX $(& $f::word($f, word))+
+            }
+
+            #[allow(non_snake_case)]
+            fn fetch(state: &Self::State, index: usize) -> Option<Self::Item<'w>> {
+                let ($($f,)+) = state;
+                Some(($($f::fetch($f, index)?,)+))
+            }
+
+            #[allow(non_snake_case)]
+            fn fetch_mut(state: &mut Self::State, index: usize) -> Option<Self::Item<'w>> {
+                let ($($f,)+) = state;
+                Some(($($f::fetch_mut($f, index)?,)+))
+            }
+
+            #[allow(non_snake_case)]
+            fn fetch_read<'s>(state: &'s Self::State, index: usize) -> Option<Self::ReadItem<'s>>
+            where
+                'w: 's,
+            {
+                let ($($f,)+) = state;
+                Some(($($f::fetch_read($f, index)?,)+))
+            }
+
+            #[allow(non_snake_case)]
+            fn shrink<'a>(item: Self::Item<'w>) -> Self::Item<'a>
+            where
+                'w: 'a,
+            {
+                let ($($f,)+) = item;
+                ($($f::shrink($f),)+)
+            }
+        }
+    };
 }
 
+impl_fetch_tuple!(A);
+impl_fetch_tuple!(A, B);
+impl_fetch_tuple!(A, B, C);
+impl_fetch_tuple!(A, B, C, D);
+impl_fetch_tuple!(A, B, C, D, E);
+impl_fetch_tuple!(A, B, C, D, E, F);
+impl_fetch_tuple!(A, B, C, D, E, F, G);
+impl_fetch_tuple!(A, B, C, D, E, F, G, H);
+
+// =====================================================================
+// --- Per-system state -------------------------------------------------
 
-/// `(Entity, &T)` — drives iteration off `T`'s column.
-pub struct EntityRefState<T: 'static> {
-    /// The driving column pointer.
-    pub col: *const dyn ColumnOps<T>,
+/// Identifies one piece of per-system state: the system's type (`TypeId` of
+/// the struct `#[system]` emits, so every instantiation of a generic system
+/// is distinct) and the position of the param owning the state.
+#[derive(Clone, Copy)]
+pub struct LocalKey {
+    /// Returns the `TypeId` of the system.
+    pub system: fn() -> core::any::TypeId,
+    /// The position of the param among the system's params.
+    pub param: u8,
 }
-unsafe impl<'w, T: 'static, W: ColumnRef<T>> Fetch<'w, W> for (Entity, &'w T) {
-    type State = EntityRefState<T>;
-    type Item<'i>
-        = (Entity, &'i T)
-    where
-        Self: 'w;
 
-    unsafe fn make_state(world: *mut W) -> (Self::State, usize) {
-        let col = unsafe { <W as ColumnRef<T>>::col_ref_raw(world) };
-        let cap = unsafe { (&*col).capacity() };
-        (EntityRefState { col }, cap)
+impl LocalKey {
+    /// Whether both keys name the same state.
+    fn matches(&self, other: &LocalKey) -> bool {
+        self.param == other.param && (self.system)() == (other.system)()
     }
+}
 
-    fn fetch(state: &Self::State, index: usize) -> Option<Self::Item<'w>> {
-        let col: &'w dyn ColumnOps<T> = unsafe { &*state.col };
-        let t = col.get(index)?;
-        Some((Entity::new(index as u32), t))
+/// The header of one value in a [`SystemLocals`] store.
+struct LocalRecord {
+    /// Which state the value is.
+    key: LocalKey,
+    /// The offset of the next record, relative to this one.
+    len: u16,
+    /// The offset of the value, relative to this record.
+    value: u16,
+    /// Drops the value in place.
+    drop: unsafe fn(*mut u8),
+}
+
+/// The bytes of a [`SystemLocals`] store, aligned for any value it accepts.
+#[repr(C, align(8))]
+struct LocalBytes<const N: usize>([core::mem::MaybeUninit<u8>; N]);
+
+/// The per-system state of one world (each [`Local`], [`EventReader`](crate::event::EventReader)
+/// cursor and custom param state), in `N` bytes the world reserves via the
+/// `locals: N` entry of `define_world!`.
+///
+/// Each value is created on its system's first run and found again by its
+/// [`LocalKey`], so it belongs to one system of one world: every world type,
+/// and every instantiation of a generic system, keeps its own. A system added
+/// to several schedules shares one value between its registrations. Values
+/// are never removed, and are dropped with the world.
+pub struct SystemLocals<const N: usize> {
+    /// The records laid out so far, each a [`LocalRecord`] then its value.
+    bytes: LocalBytes<N>,
+    /// The bytes in use.
+    used: usize,
+}
+
+impl<const N: usize> SystemLocals<N> {
+    /// Creates an empty store.
+    pub const fn new() -> Self {
+        Self {
+            bytes: LocalBytes([core::mem::MaybeUninit::uninit(); N]),
+            used: 0,
+        }
+    }
+
+    /// Returns the value stored under `key`, creating it with `init` on first
+    /// use. Works on a raw pointer so the values handed out earlier stay
+    /// valid, as one system holds several at once.
+    ///
+    /// # Panics
+    /// When the store has no room for the value, naming `system`.
+    ///
+    /// # Safety
+    /// `this` must be valid, and `T` must be the type stored under `key` by
+    /// earlier calls. The value must not be borrowed elsewhere, i.e. its
+    /// system does not run re-entrantly.
+    pub unsafe fn get_or_init<T: Send + 'static>(this: *mut Self, key: LocalKey, system: &str, init: fn() -> T) -> *mut T {
+        // SAFETY: the caller guarantees `this` is valid; only raw pointers
+        // are formed, never a borrow of the whole store.
+        let (base, used) = unsafe { ((&raw mut (*this).bytes).cast::<u8>(), &raw mut (*this).used) };
+
+        // Look for the value among the existing records
+        let mut offset = 0;
+        while offset < unsafe { *used } {
+            // SAFETY: records are laid out back to back up to `used`.
+            let record = unsafe { &*base.add(offset).cast::<LocalRecord>() };
+            if record.key.matches(&key) {
+                return unsafe { base.add(offset + record.value as usize).cast::<T>() };
+            }
+            offset += record.len as usize;
+        }
+
+        // Lay out a new record past the last one
+        assert!(core::mem::align_of::<T>() <= 8, "system `{system}` keeps state aligned to more than 8 bytes");
+        let value = (core::mem::size_of::<LocalRecord>()).next_multiple_of(core::mem::align_of::<T>());
+        let len = (value + core::mem::size_of::<T>()).next_multiple_of(core::mem::align_of::<LocalRecord>());
+        if offset + len > N {
+            panic!("no room for the state of system `{system}`; raise `locals:` in `define_world!`");
+        }
+        let record = LocalRecord {
+            key,
+            len: len as u16,
+            value: value as u16,
+            drop: |value| unsafe { core::ptr::drop_in_place(value.cast::<T>()) },
+        };
+        // SAFETY: the record fits in the bytes checked above, aligned since
+        // every record length is a multiple of the header's alignment.
+        unsafe {
+            base.add(offset).cast::<LocalRecord>().write(record);
+            let slot = base.add(offset + value).cast::<T>();
+            slot.write(init());
+            *used = offset + len;
+            slot
+        }
     }
 }
 
-/// `(Entity, &mut T)` — drives iteration off `T`'s column, mutably.
-pub struct EntityMutState<T: 'static> {
-    /// The driving column pointer.
-    pub col: *mut dyn ColumnOps<T>,
+impl<const N: usize> Default for SystemLocals<N> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
-unsafe impl<'w, T: 'static, W: ColumnRef<T>> Fetch<'w, W> for (Entity, &'w mut T) {
-    type State = EntityMutState<T>;
-    type Item<'i>
-        = (Entity, &'i mut T)
-    where
-        Self: 'w;
 
-    unsafe fn make_state(world: *mut W) -> (Self::State, usize) {
-        let col = unsafe { <W as ColumnRef<T>>::col_mut_raw(world) };
-        let cap = unsafe { (&*col).capacity() };
-        (EntityMutState { col }, cap)
+impl<const N: usize> Drop for SystemLocals<N> {
+    fn drop(&mut self) {
+        let base = (&raw mut self.bytes).cast::<u8>();
+        let mut offset = 0;
+        while offset < self.used {
+            // SAFETY: records are laid out back to back up to `used`, each
+            // followed by its initialised value.
+            unsafe {
+                let record = &*base.add(offset).cast::<LocalRecord>();
+                (record.drop)(base.add(offset + record.value as usize));
+                offset += record.len as usize;
+            }
+        }
+    }
+}
+
+/// A value moved out of a [`SystemLocals`] store for the run of an exclusive
+/// system, whose `&mut World` covers the store; moved back when dropped.
+pub struct TakenLocal<T> {
+    /// The value's slot in the store.
+    slot: *mut T,
+    /// The value, while the system runs.
+    value: core::mem::ManuallyDrop<T>,
+}
+
+impl<T> TakenLocal<T> {
+    /// Moves the value out of `slot`.
+    ///
+    /// # Safety
+    /// `slot` must come from [`SystemLocals::get_or_init`], and stay valid
+    /// and unread until this is dropped.
+    pub unsafe fn take(slot: *mut T) -> Self {
+        // SAFETY: the caller guarantees `slot` holds a value nobody else reads.
+        let value = core::mem::ManuallyDrop::new(unsafe { slot.read() });
+        Self { slot, value }
     }
 
-    fn fetch(_state: &Self::State, _index: usize) -> Option<Self::Item<'w>> {
-        None
+    /// Lends the value to the system.
+    pub fn local(&mut self) -> Local<'_, T> {
+        Local::new(&mut self.value)
     }
+}
 
-    fn fetch_mut(state: &mut Self::State, index: usize) -> Option<Self::Item<'w>> {
-        let col: &'w mut dyn ColumnOps<T> = unsafe { &mut *state.col };
-        let t = col.get_mut(index)?;
-        Some((Entity::new(index as u32), t))
+impl<T> Drop for TakenLocal<T> {
+    fn drop(&mut self) {
+        // SAFETY: `take` guarantees the slot is still valid; the value is
+        // moved back exactly once.
+        unsafe { self.slot.write(core::mem::ManuallyDrop::take(&mut self.value)) };
+    }
+}
+
+/// Private state of one system that persists across its runs, mirroring
+/// Bevy's `Local`. Starts as `T::default()` on the system's first run, and
+/// lives in the world's [`SystemLocals`], so each world keeps its own.
+pub struct Local<'s, T> {
+    /// The system's value.
+    value: &'s mut T,
+}
+
+impl<'s, T> Local<'s, T> {
+    /// Wraps the system's value.
+    pub fn new(value: &'s mut T) -> Self {
+        Self { value }
+    }
+}
+
+impl<T> core::ops::Deref for Local<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T> core::ops::DerefMut for Local<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
     }
 }
 
@@ -549,4 +1577,379 @@ pub type System = fn(*mut ());
 pub trait IntoSystem<W> {
     /// Returns the type-erased callable bound to world type `W`.
     fn into_system(self) -> System;
-}
\ No newline at end of file
+}
+
+/// The argument of the constructor fn `#[system]` generates for a generic
+/// system, so `despawn_all::<Debris>` is a value (the fn) naming a type (the
+/// system struct it returns): Rust has no generic unit structs.
+///
+/// The system's bounds carry over to each instantiation:
+///
+/// ```
+/// use tiny_ecs::prelude::*;
+///
+/// #[derive(Component)]
+/// struct Debris;
+///
+/// define_world! {
+///     struct World {
+///         entities: 4,
+///         schedules: 1,
+///         components { debris: Debris [4], }
+///     }
+/// }
+///
+/// #[system]
+/// fn despawn_all<T: Component>(mut commands: Commands, all: Query<Entity, With<T>>) {
+///     all.iter().for_each(|entity| commands.entity(entity).despawn());
+/// }
+///
+/// let mut world = World::new();
+/// world.add_system(Update, despawn_all::<Debris>);
+/// ```
+///
+/// so instantiating it with a type the world has no column for does not
+/// compile:
+///
+/// ```compile_fail
+/// # use tiny_ecs::prelude::*;
+/// # #[derive(Component)]
+/// # struct Debris;
+/// #[derive(Component)]
+/// struct Player;
+/// # define_world! {
+/// #     struct World {
+/// #         entities: 4,
+/// #         schedules: 1,
+/// #         components { debris: Debris [4], }
+/// #     }
+/// # }
+/// # #[system]
+/// # fn despawn_all<T: Component>(mut commands: Commands, all: Query<Entity, With<T>>) {
+/// #     all.iter().for_each(|entity| commands.entity(entity).despawn());
+/// # }
+/// # let mut world = World::new();
+/// world.add_system(Update, despawn_all::<Player>);
+/// ```
+///
+/// A generic type may be instantiated as any type, so a param borrowing it
+/// aliases every other param borrowing a type of its kind, unless `With` /
+/// `Without` filters keep them apart. Registered as `swap::<Debris>`, this
+/// would hand out two `&mut` into one column:
+///
+/// ```compile_fail
+/// # use tiny_ecs::prelude::*;
+/// # #[derive(Component)]
+/// # struct Debris;
+/// #[system]
+/// fn swap<T: Component>(mut any: Query<&mut T>, mut debris: Query<&mut Debris>) {}
+/// ```
+pub struct GenericSystem;
+
+/// A generic system's constructor converts like the system it builds.
+impl<W, F, S> IntoSystem<W> for F
+where
+    F: FnOnce(GenericSystem) -> S,
+    S: IntoSystem<W>,
+{
+    fn into_system(self) -> System {
+        self(GenericSystem).into_system()
+    }
+}
+
+/// A type-erased run condition: a read-only system returning whether the
+/// systems it guards run this time.
+pub type Condition = fn(*mut ()) -> bool;
+
+/// Converts a `#[system]` function returning `bool` into a [`Condition`]
+/// specialised to `W`.
+pub trait IntoCondition<W> {
+    /// Returns the type-erased condition bound to world type `W`.
+    fn into_condition(self) -> Condition;
+}
+
+/// A generic condition's constructor converts like the condition it builds.
+impl<W, F, C> IntoCondition<W> for F
+where
+    F: FnOnce(GenericSystem) -> C,
+    C: IntoCondition<W>,
+{
+    fn into_condition(self) -> Condition {
+        self(GenericSystem).into_condition()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::prelude::*;
+
+    #[derive(Component, Clone, Copy, Debug, PartialEq)]
+    struct Pos(u32);
+
+    #[derive(Component)]
+    struct Tag;
+
+    /// The value of each `Local` counter, in run order.
+    #[derive(Resource, Default)]
+    struct Runs(heapless::Vec<u32, 4>);
+
+    define_world! {
+        struct World {
+            entities: 33,
+            schedules: 1,
+            components { pos: Pos [33], tag: Tag [33], }
+            resources { runs: Runs, }
+        }
+    }
+
+    define_world! {
+        struct Other {
+            entities: 1,
+            schedules: 1,
+            locals: 0,
+            resources { runs: Runs, }
+        }
+    }
+
+    /// Spawns `Pos(i)` on every entity id, tagging the ids in `tagged`.
+    fn populated(tagged: &[u32]) -> World {
+        let mut world = World::new();
+        for i in 0..33 {
+            let entity = world.spawn(Pos(i)).expect("budget");
+            if tagged.contains(&i) {
+                world.set_component(entity, Tag);
+            }
+        }
+        world
+    }
+
+    #[test]
+    fn iteration_reaches_the_last_word() {
+        let mut world = populated(&[]);
+        let seen = world.query::<&Pos>().iter().fold(0u64, |seen, pos| seen | 1 << pos.0);
+        assert_eq!(seen, (1 << 33) - 1);
+        assert_eq!(world.query::<&Pos>().count(), 33);
+    }
+
+    #[test]
+    fn iteration_skips_despawned_slots_at_word_edges() {
+        let mut world = populated(&[]);
+        for index in [0, 31, 32] {
+            let entity = world.entity_at(index);
+            world.despawn(entity);
+        }
+        let seen: heapless::Vec<u32, 33> = world.query::<&Pos>().iter().map(|pos| pos.0).collect();
+        assert_eq!(seen.first(), Some(&1));
+        assert_eq!(seen.last(), Some(&30));
+        assert_eq!(seen.len(), 30);
+    }
+
+    #[test]
+    fn optional_fetches_match_entities_without_the_component() {
+        let mut world = populated(&[3, 32]);
+        let query = world.query::<(Entity, Option<&Tag>)>();
+        assert_eq!(query.count(), 33);
+        let tagged = query.iter().filter(|(_, tag)| tag.is_some()).fold(0u64, |seen, (e, _)| seen | 1 << e.index());
+        assert_eq!(tagged, 1 << 3 | 1 << 32);
+
+        // An optional write only touches the entities holding the component.
+        let mut query = world.query::<(&Pos, Option<&mut Tag>)>();
+        assert_eq!(query.iter_mut().filter(|(_, tag)| tag.is_some()).count(), 2);
+    }
+
+    #[test]
+    fn tuples_fetch_up_to_eight_members() {
+        let mut world = populated(&[5]);
+        let query = world.query::<(Entity, &Pos, &Pos, &Pos, &Pos, &Pos, &Pos, &Tag)>();
+        let (entity, a, .., h, _) = query.single().expect("one tagged entity");
+        assert_eq!((entity.index(), a.0, h.0), (5, 5, 5));
+
+        // Tuples nest, as in Bevy.
+        let query = world.query::<((Entity, &Pos), (&Tag,))>();
+        assert_eq!(query.count(), 1);
+    }
+
+    #[test]
+    fn get_reads_through_mutable_queries() {
+        let mut world = populated(&[7]);
+        let seventh = world.entity_at(7);
+        let mut query = world.query::<(&mut Pos, &Tag)>();
+        assert_eq!(query.get(seventh).map(|(pos, _)| *pos), Ok(Pos(7)));
+        query.get_mut(seventh).expect("matches").0.0 = 70;
+        assert_eq!(query.get(seventh).map(|(pos, _)| *pos), Ok(Pos(70)));
+
+        let first = world.entity_at(0);
+        let query = world.query::<(&mut Pos, &Tag)>();
+        assert_eq!(query.get(first).err(), Some(QueryEntityError::QueryDoesNotMatch(first)));
+    }
+
+    #[test]
+    fn get_many_mut_reports_the_offending_entity() {
+        let mut world = populated(&[1, 2, 3]);
+        let [first, second, third, fourth] = [0, 1, 2, 3].map(|index| world.entity_at(index));
+        world.despawn(fourth);
+
+        let mut query = world.query::<(&mut Pos, &Tag)>();
+        let [(mut a, _), (mut b, _)] = query.get_many_mut([second, third]).expect("distinct matches");
+        (a.0, b.0) = (b.0, a.0);
+        assert_eq!(query.get(second).map(|(pos, _)| *pos), Ok(Pos(2)));
+        assert_eq!(query.get_many_mut([second, first]).err(), Some(QueryEntityError::QueryDoesNotMatch(first)));
+        assert_eq!(query.get_many_mut([second, fourth]).err(), Some(QueryEntityError::NoSuchEntity(fourth)));
+        assert_eq!(query.get_many_mut([second, second]).err(), Some(QueryEntityError::AliasedMutability(second)));
+    }
+
+    #[test]
+    fn combinations_are_ascending_and_distinct() {
+        let mut world = populated(&[30, 31, 32]);
+        let query = world.query_filtered::<&Pos, With<Tag>>();
+        let pairs: heapless::Vec<[u32; 2], 4> = query.iter_combinations::<2>().map(|[a, b]| [a.0, b.0]).collect();
+        assert_eq!(pairs, [[30, 31], [30, 32], [31, 32]]);
+        assert_eq!(query.iter_combinations::<3>().count(), 1);
+        assert_eq!(query.iter_combinations::<4>().next(), None);
+    }
+
+    #[test]
+    fn mutable_combinations_visit_every_pair() {
+        let mut world = populated(&[0, 5, 32]);
+        let mut query = world.query_filtered::<&mut Pos, With<Tag>>();
+        let mut pairs = query.iter_combinations_mut::<2>();
+        let mut visits = 0;
+        while let Some([mut a, mut b]) = pairs.fetch_next() {
+            a.0 += 100;
+            b.0 += 100;
+            visits += 1;
+        }
+        assert_eq!(visits, 3);
+
+        // Each entity is part of two of the three pairs.
+        let query = world.query_filtered::<&Pos, With<Tag>>();
+        assert!(query.iter().map(|pos| pos.0).eq([200, 205, 232]));
+    }
+
+    #[test]
+    fn single_requires_exactly_one_match() {
+        let mut world = populated(&[]);
+        let query = world.query_filtered::<&Pos, With<Tag>>();
+        assert_eq!(query.single().err(), Some(QuerySingleError::NoEntities));
+
+        let mut world = populated(&[4, 9]);
+        let query = world.query_filtered::<&Pos, With<Tag>>();
+        assert_eq!(query.single().err(), Some(QuerySingleError::MultipleEntities));
+
+        let mut world = populated(&[9]);
+        let ninth = world.entity_at(9);
+        let mut query = world.query_filtered::<&mut Pos, With<Tag>>();
+        query.single_mut().expect("one match").0 = 90;
+        assert_eq!(query.get(ninth).copied(), Ok(Pos(90)));
+    }
+
+    /// Skips seen by [`count_skips`].
+    static SKIPS: AtomicUsize = AtomicUsize::new(0);
+
+    fn count_skips(skipped: SystemSkipped) {
+        assert_eq!((skipped.system, skipped.param), ("bump_tagged", "tagged"));
+        SKIPS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[system]
+    fn bump_tagged(mut tagged: Single<&mut Pos, With<Tag>>) {
+        tagged.0 += 1;
+    }
+
+    #[test]
+    fn single_params_skip_the_system_unless_one_entity_matches() {
+        let mut world = populated(&[3, 4]);
+        world.set_skip_handler(count_skips);
+        world.add_system(Update, bump_tagged);
+        world.run_schedule(Update);
+        assert_eq!(SKIPS.load(Ordering::Relaxed), 1);
+
+        let fourth = world.entity_at(4);
+        world.despawn(fourth);
+        world.run_schedule(Update);
+        assert_eq!(SKIPS.load(Ordering::Relaxed), 1);
+        assert_eq!(world.query_filtered::<&Pos, With<Tag>>().single().ok(), Some(&Pos(4)));
+    }
+
+    #[system]
+    fn count(mut counter: Local<u32>, mut runs: ResMut<Runs>) {
+        *counter += 1;
+        runs.0.push(*counter).expect("room");
+    }
+
+    #[system]
+    fn count_for<T: Component>(mut counter: Local<u32>, mut runs: ResMut<Runs>) {
+        *counter += 1;
+        runs.0.push(*counter).expect("room");
+    }
+
+    #[system]
+    fn count_exclusive(mut counter: Local<u32>, world: &mut World) {
+        *counter += 1;
+        let mut runs = world.get_resource_mut::<Runs>().expect("inserted");
+        runs.0.push(*counter).expect("room");
+    }
+
+    /// A world with `Runs` and `systems` in its `Update` schedule.
+    fn counting<M>(systems: impl IntoSystemConfigs<World, M>) -> World {
+        let mut world = World::new();
+        world.insert_resource(Runs::default());
+        world.add_systems(Update, systems);
+        world
+    }
+
+    #[test]
+    fn locals_persist_per_world() {
+        let (mut first, mut second) = (counting((count,)), counting((count,)));
+        first.run_schedule(Update);
+        first.run_schedule(Update);
+        second.run_schedule(Update);
+        assert_eq!(first.get_resource::<Runs>().expect("inserted").0, [1, 2]);
+        assert_eq!(second.get_resource::<Runs>().expect("inserted").0, [1]);
+    }
+
+    #[test]
+    fn generic_instantiations_keep_their_own_locals() {
+        let mut world = counting((count_for::<Pos>, count_for::<Tag>).chain());
+        world.run_schedule(Update);
+        world.run_schedule(Update);
+        assert_eq!(world.get_resource::<Runs>().expect("inserted").0, [1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn exclusive_systems_keep_their_locals() {
+        let mut world = counting((count_exclusive,));
+        world.run_schedule(Update);
+        world.run_schedule(Update);
+        assert_eq!(world.get_resource::<Runs>().expect("inserted").0, [1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "no room for the state of system `count`")]
+    fn locals_beyond_the_store_panic() {
+        let mut world = Other::new();
+        world.insert_resource(Runs::default());
+        world.add_system(Update, count);
+        world.run_schedule(Update);
+    }
+
+    #[system]
+    fn despawn_all<T: Component>(mut commands: Commands, all: Query<Entity, With<T>>) {
+        all.iter().for_each(|entity| commands.entity(entity).despawn());
+    }
+
+    #[test]
+    fn generic_systems_run_per_instantiation() {
+        let mut world = populated(&[1, 2]);
+        world.add_system(Update, despawn_all::<Tag>);
+        world.run_schedule(Update);
+        world.flush_commands();
+        assert_eq!(world.entity_count(), 31);
+
+        world.add_system(Update, despawn_all::<Pos>);
+        world.run_schedule(Update);
+        world.flush_commands();
+        assert_eq!(world.entity_count(), 0);
+    }
+}
@@ -0,0 +1,572 @@
+//! The [`SystemParam`] trait behind custom system params, mirroring
+//! `bevy_ecs::system::SystemParam`.
+//!
+//! `#[system]` expands its built-in params (`Res`, `Query`, `Commands`, ...)
+//! inline. Any other param type is built through this trait instead, so a
+//! struct of params made with `#[derive(SystemParam)]` can stand in for a
+//! group of params systems keep repeating:
+//!
+//! ```ignore
+//! #[derive(SystemParam)]
+//! struct Round<'w> {
+//!     input: Res<'w, ButtonInput<GameButton>>,
+//!     time: Res<'w, Time>,
+//!     state: Res<'w, GameState>,
+//! }
+//!
+//! #[system]
+//! fn player_input(round: Round<'_>, player: Single<&mut Player>) { .. }
+//! ```
+//!
+//! A custom param spells out its lifetimes as `'_` in the system signature.
+//! Its fields may be any param that does not name the world type: `Res`,
+//! `ResMut`, `Option<Res>`, `Option<ResMut>`, `EventReader`, `EventWriter`,
+//! `RemovedComponents`, `Trigger`, `Local` and other custom params. The
+//! system's world bounds are the union of the fields' bounds.
+//!
+//! # Aliasing
+//!
+//! A system's params must not borrow the same data with one of them borrowing
+//! it mutably. `#[system]` rejects built-in params that alias; what a custom
+//! param borrows is its [`SystemParam::ACCESS`], checked when the system is
+//! compiled. Reading the score twice is fine:
+//!
+//! ```
+//! use tiny_ecs::prelude::*;
+//!
+//! #[derive(Resource)]
+//! struct Score(u32);
+//!
+//! define_world! {
+//!     struct World {
+//!         entities: 1,
+//!         schedules: 1,
+//!         resources { score: Score, }
+//!     }
+//! }
+//!
+//! #[derive(SystemParam)]
+//! struct Round<'w> {
+//!     score: Res<'w, Score>,
+//! }
+//!
+//! #[system]
+//! fn show(round: Round<'_>, score: Res<Score>) {
+//!     assert_eq!(round.score.0, score.0);
+//! }
+//!
+//! let mut world = World::new();
+//! world.add_system(Update, show);
+//! ```
+//!
+//! but writing it through either param does not compile:
+//!
+//! ```compile_fail
+//! # use tiny_ecs::prelude::*;
+//! # #[derive(Resource)]
+//! # struct Score(u32);
+//! # define_world! {
+//! #     struct World {
+//! #         entities: 1,
+//! #         schedules: 1,
+//! #         resources { score: Score, }
+//! #     }
+//! # }
+//! # #[derive(SystemParam)]
+//! # struct Round<'w> {
+//! #     score: Res<'w, Score>,
+//! # }
+//! #[system]
+//! fn reset(round: Round<'_>, mut score: ResMut<Score>) {
+//!     score.0 = 0;
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! # use tiny_ecs::prelude::*;
+//! # #[derive(Resource)]
+//! # struct Score(u32);
+//! # define_world! {
+//! #     struct World {
+//! #         entities: 1,
+//! #         schedules: 1,
+//! #         resources { score: Score, }
+//! #     }
+//! # }
+//! #[derive(SystemParam)]
+//! struct Round<'w> {
+//!     score: ResMut<'w, Score>,
+//! }
+//!
+//! #[system]
+//! fn show(round: Round<'_>, score: Res<Score>) {}
+//! ```
+//!
+//! The same holds between the fields of a custom param, and between two
+//! built-in params:
+//!
+//! ```compile_fail
+//! # use tiny_ecs::prelude::*;
+//! # #[derive(Resource)]
+//! # struct Score(u32);
+//! # define_world! {
+//! #     struct World {
+//! #         entities: 1,
+//! #         schedules: 1,
+//! #         resources { score: Score, }
+//! #     }
+//! # }
+//! #[system]
+//! fn reset(score: Res<Score>, mut again: ResMut<Score>) {}
+//! ```
+//!
+//! Run conditions may not take a custom param that writes:
+//!
+//! ```compile_fail
+//! # use tiny_ecs::prelude::*;
+//! # #[derive(Resource)]
+//! # struct Score(u32);
+//! # define_world! {
+//! #     struct World {
+//! #         entities: 1,
+//! #         schedules: 1,
+//! #         resources { score: Score, }
+//! #     }
+//! # }
+//! #[derive(SystemParam)]
+//! struct Round<'w> {
+//!     score: ResMut<'w, Score>,
+//! }
+//!
+//! #[system]
+//! fn scored(round: Round<'_>) -> bool {
+//!     round.score.0 > 0
+//! }
+//! ```
+
+use crate::change_detection::ComponentTicks;
+use crate::event::{EventReader, EventWriter};
+use crate::observer::Trigger;
+use crate::removal_detection::RemovedComponents;
+use crate::system::{ChangeTicksRef, EventsRef, HasResource, Local, RemovedRef, Res, ResMut, ResourceRef, TriggerRef};
+use crate::Resource;
+
+/// A type a `#[system]` can take as a param, with the per-system state it
+/// keeps across runs.
+///
+/// The state lives in the world's [`SystemLocals`](crate::system::SystemLocals),
+/// one per system; building the param from a world is [`SystemParamFetch`].
+pub trait SystemParam: Sized {
+    /// The state persisting across runs of one system (`()` for most params).
+    type State: Send + 'static;
+
+    /// What the param borrows from the world, one entry per field, checked
+    /// against a system's other params at compile time. Built-in params leave
+    /// it empty, as `#[system]` sees their borrows directly.
+    const ACCESS: &'static [Access] = &[];
+
+    /// Creates the state before the system's first run.
+    fn init_state() -> Self::State;
+}
+
+/// Builds a [`SystemParam`] from a world `W`.
+///
+/// # Safety
+/// [`SystemParamFetch::get_param`] must only touch the parts of the world its
+/// bounds on `W` grant.
+pub unsafe trait SystemParamFetch<W>: SystemParam {
+    /// Builds the param for one run of a system.
+    ///
+    /// # Safety
+    /// `world` must be a valid `*mut W` and `state` a valid pointer to this
+    /// param's state, both for the lifetimes of `Self`, and no other param of
+    /// the system may alias what this one borrows.
+    unsafe fn get_param(world: *mut W, state: *mut Self::State) -> Self;
+}
+
+// =====================================================================
+// --- Access -----------------------------------------------------------
+
+/// One entry of [`SystemParam::ACCESS`]. Types are named by their tokens, as
+/// `#[system]` compares them; a borrowed type named `_` mentions a generic
+/// type param of the system, so it may be any type.
+#[derive(Clone, Copy)]
+pub enum Access {
+    /// What a built-in param borrows.
+    Param(ParamAccess),
+    /// The fields of a custom param.
+    Group(&'static [Access]),
+}
+
+/// What a built-in param borrows, mirroring the aliasing check of `#[system]`.
+#[derive(Clone, Copy)]
+pub struct ParamAccess {
+    /// Resources, and whether each is borrowed mutably.
+    pub resources: &'static [(&'static str, bool)],
+    /// Component columns borrowed by query data, and whether each is `&mut`.
+    pub columns: &'static [(&'static str, bool)],
+    /// Columns whose ticks a query filter (`Added` / `Changed`) reads.
+    pub filtered: &'static [&'static str],
+    /// Columns every matched entity has.
+    pub with: &'static [&'static str],
+    /// Columns no matched entity has.
+    pub without: &'static [&'static str],
+    /// Event buffers, and whether each is written.
+    pub events: &'static [(&'static str, bool)],
+    /// Whether the param holds the command buffer.
+    pub commands: bool,
+}
+
+impl ParamAccess {
+    /// Whether both params borrow something, one of them mutably.
+    const fn conflicts(&self, other: &Self) -> bool {
+        if clash(self.resources, other.resources) || clash(self.events, other.events) {
+            return true;
+        }
+        if self.commands && other.commands {
+            return true;
+        }
+        // No entity matches both queries when one excludes what the other
+        // requires; filters only read ticks, so they clash with `&mut` data.
+        let disjoint = overlap(self.with, other.without) || overlap(other.with, self.without);
+        !disjoint
+            && (clash(self.columns, other.columns)
+                || writes_any(self.columns, other.filtered)
+                || writes_any(other.columns, self.filtered))
+    }
+
+    /// Whether the param can write to the world.
+    const fn writes(&self) -> bool {
+        self.commands || writes_any(self.resources, &[]) || writes_any(self.columns, &[]) || writes_any(self.events, &[])
+    }
+}
+
+/// Whether any entry of `a` aliases any entry of `b`; evaluated at compile
+/// time by `#[system]` for params involving a custom one.
+pub const fn conflicts(a: &[Access], b: &[Access]) -> bool {
+    let mut i = 0;
+    while i < a.len() {
+        let mut j = 0;
+        while j < b.len() {
+            let clashes = match (&a[i], &b[j]) {
+                (Access::Group(group), _) => conflicts(group, core::slice::from_ref(&b[j])),
+                (_, Access::Group(group)) => conflicts(core::slice::from_ref(&a[i]), group),
+                (Access::Param(ours), Access::Param(theirs)) => ours.conflicts(theirs),
+            };
+            if clashes {
+                return true;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Whether any entry of `access` can write to the world; evaluated at
+/// compile time for the custom params of run conditions.
+pub const fn writes(access: &[Access]) -> bool {
+    let mut i = 0;
+    while i < access.len() {
+        let writes = match &access[i] {
+            Access::Group(group) => writes(group),
+            Access::Param(param) => param.writes(),
+        };
+        if writes {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Whether `ours` and `theirs` borrow one type, one of them mutably.
+const fn clash(ours: &[(&str, bool)], theirs: &[(&str, bool)]) -> bool {
+    let mut i = 0;
+    while i < ours.len() {
+        let mut j = 0;
+        while j < theirs.len() {
+            if (ours[i].1 || theirs[j].1) && may_be_same(ours[i].0, theirs[j].0) {
+                return true;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Whether `borrows` writes a type listed in `names`; any write when `names`
+/// is empty.
+const fn writes_any(borrows: &[(&str, bool)], names: &[&str]) -> bool {
+    let mut i = 0;
+    while i < borrows.len() {
+        if borrows[i].1 && (names.is_empty() || may_contain(names, borrows[i].0)) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Whether `a` and `b` share a name.
+const fn overlap(a: &[&str], b: &[&str]) -> bool {
+    let mut i = 0;
+    while i < a.len() {
+        if contains(b, a[i]) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Whether `names` lists `name`.
+const fn contains(names: &[&str], name: &str) -> bool {
+    let mut i = 0;
+    while i < names.len() {
+        if same(names[i], name) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Whether `names` may list `name`, counting the wildcard `_`.
+const fn may_contain(names: &[&str], name: &str) -> bool {
+    let mut i = 0;
+    while i < names.len() {
+        if may_be_same(names[i], name) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Whether the types named `a` and `b` may be the same: equal, or either is
+/// the wildcard `_`.
+const fn may_be_same(a: &str, b: &str) -> bool {
+    same(a, b) || same(a, "_") || same(b, "_")
+}
+
+/// `a == b`, usable in `const` code.
+const fn same(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+// =====================================================================
+// --- Resources --------------------------------------------------------
+
+impl<R: Resource> SystemParam for Res<'_, R> {
+    type State = ();
+    fn init_state() {}
+}
+
+unsafe impl<W: HasResource<R> + ChangeTicksRef, R: Resource> SystemParamFetch<W> for Res<'_, R> {
+    unsafe fn get_param(world: *mut W, _state: *mut ()) -> Self {
+        // SAFETY: the caller guarantees `world` is valid.
+        let ptr = unsafe { W::res_ref_raw(world) };
+        assert!(!ptr.is_null(), "resource not inserted: {}", core::any::type_name::<R>());
+        // SAFETY: as above; the tick slots are disjoint from the values.
+        let ticks = unsafe { *<W as ResourceRef<R>>::res_ticks_raw(world) };
+        let system = unsafe { W::system_ticks(world) };
+        // SAFETY: the non-null check above guarantees validity.
+        Res::new(unsafe { &*ptr }, ticks, system)
+    }
+}
+
+impl<R: Resource> SystemParam for ResMut<'_, R> {
+    type State = ();
+    fn init_state() {}
+}
+
+unsafe impl<W: HasResource<R> + ChangeTicksRef, R: Resource> SystemParamFetch<W> for ResMut<'_, R> {
+    unsafe fn get_param(world: *mut W, _state: *mut ()) -> Self {
+        // SAFETY: the caller guarantees `world` is valid.
+        let ptr = unsafe { W::res_mut_raw(world) };
+        assert!(!ptr.is_null(), "resource not inserted: {}", core::any::type_name::<R>());
+        // SAFETY: as above; the tick slots are disjoint from the values.
+        let ticks: *mut ComponentTicks = unsafe { <W as ResourceRef<R>>::res_ticks_raw(world) };
+        let system = unsafe { W::system_ticks(world) };
+        // SAFETY: the non-null check above guarantees validity.
+        ResMut::new(unsafe { &mut *ptr }, unsafe { &mut *ticks }, system)
+    }
+}
+
+impl<R: Resource> SystemParam for Option<Res<'_, R>> {
+    type State = ();
+    fn init_state() {}
+}
+
+unsafe impl<W: HasResource<R> + ChangeTicksRef, R: Resource> SystemParamFetch<W> for Option<Res<'_, R>> {
+    unsafe fn get_param(world: *mut W, state: *mut ()) -> Self {
+        // SAFETY: the caller guarantees `world` is valid.
+        let present = unsafe { !W::res_ref_raw(world).is_null() };
+        // SAFETY: as above.
+        present.then(|| unsafe { <Res<'_, R> as SystemParamFetch<W>>::get_param(world, state) })
+    }
+}
+
+impl<R: Resource> SystemParam for Option<ResMut<'_, R>> {
+    type State = ();
+    fn init_state() {}
+}
+
+unsafe impl<W: HasResource<R> + ChangeTicksRef, R: Resource> SystemParamFetch<W> for Option<ResMut<'_, R>> {
+    unsafe fn get_param(world: *mut W, state: *mut ()) -> Self {
+        // SAFETY: the caller guarantees `world` is valid.
+        let present = unsafe { !W::res_ref_raw(world).is_null() };
+        // SAFETY: as above.
+        present.then(|| unsafe { <ResMut<'_, R> as SystemParamFetch<W>>::get_param(world, state) })
+    }
+}
+
+// =====================================================================
+// --- Events, removals and triggers -----------------------------------
+
+impl<E: 'static> SystemParam for EventWriter<'_, E> {
+    type State = ();
+    fn init_state() {}
+}
+
+unsafe impl<W: EventsRef<E>, E: 'static> SystemParamFetch<W> for EventWriter<'_, E> {
+    unsafe fn get_param(world: *mut W, _state: *mut ()) -> Self {
+        // SAFETY: the caller guarantees `world` is valid; the buffers are a
+        // distinct field.
+        EventWriter::new(unsafe { &mut *W::events_raw(world) })
+    }
+}
+
+impl<E: 'static> SystemParam for EventReader<'_, '_, E> {
+    /// The id of the next unread event.
+    type State = usize;
+    fn init_state() -> usize {
+        0
+    }
+}
+
+unsafe impl<W: EventsRef<E>, E: 'static> SystemParamFetch<W> for EventReader<'_, '_, E> {
+    unsafe fn get_param(world: *mut W, state: *mut usize) -> Self {
+        // SAFETY: the caller guarantees `world` and `state` are valid.
+        EventReader::new(unsafe { &*W::events_raw(world) }, unsafe { &mut *state })
+    }
+}
+
+impl<T: 'static> SystemParam for RemovedComponents<'_, T> {
+    type State = ();
+    fn init_state() {}
+}
+
+unsafe impl<W: RemovedRef<T> + ChangeTicksRef, T: 'static> SystemParamFetch<W> for RemovedComponents<'_, T> {
+    unsafe fn get_param(world: *mut W, _state: *mut ()) -> Self {
+        // SAFETY: the caller guarantees `world` is valid; the log is only
+        // written by flushes.
+        let log = unsafe { &*W::removed_raw(world) };
+        RemovedComponents::new(log, unsafe { W::system_ticks(world) })
+    }
+}
+
+impl<E: 'static> SystemParam for Trigger<E> {
+    type State = ();
+    fn init_state() {}
+}
+
+unsafe impl<W: TriggerRef, E: 'static> SystemParamFetch<W> for Trigger<E> {
+    unsafe fn get_param(world: *mut W, _state: *mut ()) -> Self {
+        // SAFETY: the caller guarantees `world` is valid.
+        Trigger::new(unsafe { W::trigger_entity(world) })
+    }
+}
+
+// =====================================================================
+// --- Local ------------------------------------------------------------
+
+impl<T: Default + Send + 'static> SystemParam for Local<'_, T> {
+    type State = T;
+    fn init_state() -> T {
+        T::default()
+    }
+}
+
+unsafe impl<W, T: Default + Send + 'static> SystemParamFetch<W> for Local<'_, T> {
+    unsafe fn get_param(_world: *mut W, state: *mut T) -> Self {
+        // SAFETY: the caller guarantees `state` is valid and unaliased.
+        Local::new(unsafe { &mut *state })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Resource)]
+    struct Score(u32);
+
+    #[derive(Resource, Default)]
+    struct Seen(heapless::Vec<u8, 4>);
+
+    /// A pickup worth its points.
+    struct Pickup(u8);
+
+    define_world! {
+        struct World {
+            entities: 1,
+            schedules: 1,
+            resources { score: Score, seen: Seen, }
+            events { pickups: Pickup [4], }
+        }
+    }
+
+    /// The pickups not yet scored.
+    #[derive(SystemParam)]
+    struct Pickups<'w, 's> {
+        events: EventReader<'w, 's, Pickup>,
+        runs: Local<'s, u8>,
+    }
+
+    /// Scores pickups, nesting [`Pickups`].
+    #[derive(SystemParam)]
+    struct Scoring<'w, 's> {
+        pickups: Pickups<'w, 's>,
+        score: ResMut<'w, Score>,
+    }
+
+    #[system]
+    fn score_pickups(mut scoring: Scoring<'_, '_>, mut seen: ResMut<Seen>) {
+        *scoring.pickups.runs += 1;
+        for pickup in scoring.pickups.events.read() {
+            scoring.score.0 += pickup.0 as u32;
+        }
+        seen.0.push(*scoring.pickups.runs).expect("room");
+    }
+
+    #[test]
+    fn derived_params_keep_their_fields_state() {
+        let mut world = World::new();
+        world.insert_resource(Score(0));
+        world.insert_resource(Seen::default());
+        world.add_system(Update, score_pickups);
+        world.send_event(Pickup(2));
+        world.send_event(Pickup(3));
+        world.run_schedule(Update);
+        world.send_event(Pickup(5));
+        world.run_schedule(Update);
+
+        // Each pickup scores once; the local counts both runs.
+        assert_eq!(world.get_resource::<Score>().map(|score| score.0), Some(10));
+        assert_eq!(world.get_resource::<Seen>().expect("inserted").0, [1, 2]);
+    }
+}
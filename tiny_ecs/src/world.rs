@@ -9,10 +9,17 @@
 //! [`TypeId`](core::any::TypeId), so any crate can mint a label via
 //! `#[derive(ScheduleLabel)]` without a central enum.
 
+use crate::bundle::Bundle;
 use crate::commands_buffer::CommandBuffer;
 use crate::entity::Entity;
-use crate::schedule::ScheduleLabel;
-use crate::system::{ResourceInsRef, System};
+use crate::error::SystemFailed;
+use crate::filter::QueryFilter;
+use crate::observer::ComponentEvent;
+use crate::schedule::{IntoSetConfig, IntoSystemConfig, IntoSystemConfigs, ScheduleLabel};
+use crate::system::{
+    ChangeTicksRef, Fetch, HasResource, Query, ResMut, ResourceInsRef, ResourceRef, System, SystemSkipped,
+};
+use crate::Resource;
 
 /// Maximum number of schedules that may be registered.
 pub const MAX_SCHEDULES: usize = 12;
@@ -22,35 +29,389 @@ pub const MAX_SCHEDULES: usize = 12;
 /// Schedules are keyed by [`TypeId`](core::any::TypeId) of the label type `L`,
 /// so the methods below are generic over `L: ScheduleLabel` and take the label
 /// as a zero-sized marker value (e.g. `world.run_schedule(Update)`).
-pub trait WorldApi {
+///
+/// Every world keeps change ticks, so [`ChangeTicksRef`] is a supertrait:
+/// plugins generic over `W: WorldApi` can register systems taking `Res`,
+/// `ResMut` or `&mut T` queries without spelling out the bound.
+pub trait WorldApi: ChangeTicksRef {
     /// Adds a schedule under label `L` if it does not already exist.
+    ///
+    /// # Panics
+    /// When the label is new and the world holds `schedules` schedules
+    /// already. The methods adding systems and sets below add their schedule
+    /// the same way.
     fn add_schedule<L: ScheduleLabel>(&mut self, label: L);
 
-    /// Adds a `system` to the schedule identified by label `L`.
-    fn add_system<L: ScheduleLabel>(&mut self, label: L, system: System);
+    /// Adds a system, optionally with run conditions and a set, to the
+    /// schedule identified by label `L`.
+    ///
+    /// # Panics
+    /// When the schedule has no room for the system, its set or its run
+    /// conditions, see [`Schedule::add`](crate::schedule::Schedule::add).
+    fn add_system<L: ScheduleLabel, M, S: IntoSystemConfig<Self, M>>(&mut self, label: L, system: S);
+
+    /// Adds a tuple of systems, e.g. `(a, b, c).chain()`, to the schedule
+    /// identified by label `L`.
+    ///
+    /// # Panics
+    /// As [`add_system`](Self::add_system), for each of the systems.
+    fn add_systems<L: ScheduleLabel, M, S: IntoSystemConfigs<Self, M>>(&mut self, label: L, systems: S);
 
-    /// Runs the schedule identified by label `L`, if it exists.
+    /// Gives the system set `S` run conditions shared by all its systems in
+    /// the schedule identified by label `L`.
+    ///
+    /// # Panics
+    /// When the schedule has no room for the set or its run conditions.
+    fn configure_set<L: ScheduleLabel, S: IntoSetConfig<Self>>(&mut self, label: L, set: S);
+
+    /// Runs the schedule identified by label `L`, if it exists. The world's
+    /// change tick advances once per system run.
+    ///
+    /// An exclusive system may call this to run another schedule nested in
+    /// its own, and sees its own change ticks again once it returns; running
+    /// the schedule it belongs to does nothing.
+    ///
+    /// # Panics
+    /// When a system adds systems to, or configures sets of, a schedule that
+    /// is running.
     fn run_schedule<L: ScheduleLabel>(&mut self, label: L);
 
+    /// Registers `system` as an observer of the lifecycle event `E` (e.g.
+    /// `OnRemove<Debris>`).
+    ///
+    /// # Panics
+    /// When [`MAX_OBSERVERS`](crate::observer::MAX_OBSERVERS) observers are
+    /// already registered.
+    fn add_observer<E: ComponentEvent>(&mut self, system: System);
+
     /// Allocates a fresh entity id; returns `None` if the entity budget is
     /// exhausted.
     fn spawn_empty(&mut self) -> Option<Entity>;
 
+    /// Spawns an entity carrying every component of `bundle`, applying them
+    /// (and any other pending commands) in one flush. Returns `None` if the
+    /// entity budget is exhausted.
+    fn spawn<B: Bundle<Self>>(&mut self, bundle: B) -> Option<Entity>
+    where
+        Self: Sized,
+    {
+        let entity = self.spawn_empty()?;
+        // SAFETY: the pointer comes from `&mut self`, valid for the call.
+        unsafe { bundle.enqueue_insert(self as *mut Self, entity) };
+        self.flush_commands();
+        Some(entity)
+    }
+
     /// Inserts a resource of type `R`, replacing any previous instance.
     fn insert_resource<R: 'static>(&mut self, resource: R)
     where
         Self: ResourceInsRef<R>;
 
-    /// Marks `entity` as dead, removing it from every column and freeing its
-    /// id for reuse.
-    fn despawn(&mut self, entity: Entity);
+    /// Takes the resource `R` out of the world, returning it if it was
+    /// inserted.
+    fn remove_resource<R: 'static>(&mut self) -> Option<R>
+    where
+        Self: ResourceInsRef<R>;
+
+    /// Returns the resource `R`, or `None` when it is not inserted.
+    fn get_resource<R: 'static>(&self) -> Option<&R>
+    where
+        Self: ResourceRef<R>,
+    {
+        // SAFETY: the pointer comes from `&self` and is only read through,
+        // for the borrow's duration.
+        unsafe { <Self as ResourceRef<R>>::res_ref_raw(self as *const Self as *mut Self).as_ref() }
+    }
+
+    /// Returns the resource `R` for writing, or `None` when it is not
+    /// inserted. Writing through it marks `R` changed at the current tick.
+    fn get_resource_mut<R: Resource>(&mut self) -> Option<ResMut<'_, R>>
+    where
+        Self: ResourceRef<R>,
+    {
+        let world = self as *mut Self;
+        // SAFETY: `world` comes from `&mut self`, so nothing else borrows the
+        // slot; the tick slot is disjoint from the value.
+        unsafe {
+            let value = <Self as ResourceRef<R>>::res_mut_raw(world).as_mut()?;
+            let ticks = &mut *<Self as ResourceRef<R>>::res_ticks_raw(world);
+            Some(ResMut::new(value, ticks, Self::system_ticks(world)))
+        }
+    }
+
+    /// Builds a [`Query`] over the world, mirroring Bevy's `World::query`.
+    fn query<'w, D: Fetch<'w, Self> + 'w>(&'w mut self) -> Query<'w, D, Self>
+    where
+        Self: Sized,
+    {
+        // SAFETY: `&'w mut self` keeps every other borrow out for `'w`.
+        unsafe { Query::from_world(self as *mut Self) }
+    }
+
+    /// Builds a filtered [`Query`] over the world, mirroring Bevy's
+    /// `World::query_filtered`.
+    fn query_filtered<'w, D: Fetch<'w, Self> + 'w, F: QueryFilter<Self>>(&'w mut self) -> Query<'w, D, Self, F>
+    where
+        Self: Sized,
+    {
+        // SAFETY: `&'w mut self` keeps every other borrow out for `'w`.
+        unsafe { Query::from_world(self as *mut Self) }
+    }
+
+    /// Inserts `R` built by [`FromWorld`] (e.g. its `Default`) unless the
+    /// resource is already present.
+    fn init_resource<R: FromWorld<Self> + 'static>(&mut self)
+    where
+        Self: HasResource<R> + Sized,
+    {
+        // SAFETY: the pointer comes from `&mut self`, valid for the call.
+        let present = unsafe { !<Self as ResourceRef<R>>::res_ref_raw(self as *mut Self).is_null() };
+        if !present {
+            let resource = R::from_world(self);
+            self.insert_resource(resource);
+        }
+    }
+
+    /// Marks `entity` as dead, removing it from every column (firing each
+    /// component's `OnRemove` hook and observers first) and freeing its id for
+    /// reuse. Returns `false` (and does nothing) when the handle is stale,
+    /// i.e. the entity was already despawned, or when an `OnRemove` hook or
+    /// observer despawns an entity whose despawn is already under way.
+    fn despawn(&mut self, entity: Entity) -> bool;
+
+    /// Returns `true` when `entity` refers to a live entity (its slot is
+    /// allocated and, for generational worlds, the generations match).
+    fn is_alive(&self, entity: Entity) -> bool;
 
     /// Returns the number of live entities.
     fn entity_count(&self) -> usize;
 
-    /// Drains the pending command buffer and applies it.
+    /// Swaps the double buffers of every registered event type: events sent
+    /// since the previous call become readable-only, and those sent before it
+    /// are dropped. Runners call this once per frame.
+    fn update_events(&mut self);
+
+    /// Drains the pending command buffer and applies it, firing the lifecycle
+    /// hooks and observers of every component added, inserted or removed.
+    /// Also runs [`WorldApi::check_change_ticks`].
     fn flush_commands(&mut self);
 
+    /// Clamps every stored change tick (components, resources, removal logs
+    /// and the last runs of systems in idle schedules) to at most
+    /// [`MAX_CHANGE_AGE`](crate::change_detection::MAX_CHANGE_AGE) ticks
+    /// old, once the change tick has advanced
+    /// [`CHECK_TICK_THRESHOLD`](crate::change_detection::CHECK_TICK_THRESHOLD)
+    /// since the previous pass; a no-op otherwise.
+    fn check_change_ticks(&mut self);
+
     /// Returns a raw pointer to the command buffer (for the `Commands` param).
     fn commands_ptr(&mut self) -> *mut CommandBuffer;
-}
\ No newline at end of file
+
+    /// Installs the handler receiving a [`SystemSkipped`] diagnostic whenever
+    /// a system is skipped because a `Single` param found no unique match.
+    /// Without a handler, skips are silent.
+    fn set_skip_handler(&mut self, handler: fn(SystemSkipped));
+
+    /// Installs the handler receiving a [`SystemFailed`] report whenever a
+    /// fallible system returns an error, e.g. [`error::count`](crate::error::count).
+    /// Without a handler, a failing system panics, mirroring Bevy.
+    fn set_error_handler(&mut self, handler: fn(&mut Self, SystemFailed<'_>));
+}
+
+/// Creates a value from the world, mirroring Bevy's `FromWorld`; used by
+/// [`WorldApi::init_resource`] and `App::init_resource`.
+///
+/// Implemented for every `Default` type; implement it by hand for resources
+/// that are built from other resources.
+pub trait FromWorld<W> {
+    /// Creates `Self` from `world`.
+    fn from_world(world: &mut W) -> Self;
+}
+
+impl<W, T: Default> FromWorld<W> for T {
+    fn from_world(_world: &mut W) -> Self {
+        T::default()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Health(u8);
+
+    #[derive(Component)]
+    struct Boss;
+
+    #[derive(Resource, Debug, PartialEq)]
+    struct Score(u32);
+
+    /// Built from the `Score` when initialised.
+    #[derive(Resource, Debug, PartialEq)]
+    struct Level(u32);
+
+    impl FromWorld<World> for Level {
+        fn from_world(world: &mut World) -> Self {
+            Level(world.get_resource::<Score>().map_or(1, |score| score.0 / 10 + 1))
+        }
+    }
+
+    define_world! {
+        struct World {
+            entities: 2,
+            schedules: 2,
+            components { health: Health [2], boss: Boss [1, sparse], }
+            resources { score: Score, level: Level, }
+        }
+    }
+
+    #[test]
+    fn stale_handles_are_not_alive() {
+        let mut world = World::new();
+        let first = world.spawn(Health(1)).expect("budget");
+        assert!(world.despawn(first));
+        assert!(!world.is_alive(first));
+        assert!(!world.despawn(first));
+
+        // The recycled slot is alive under its new handle only.
+        let second = world.spawn(Health(2)).expect("budget");
+        assert_eq!(second.index(), first.index());
+        assert!(world.is_alive(second));
+        assert!(!world.is_alive(first));
+    }
+
+    #[test]
+    fn exhausted_budget_hands_out_a_dead_handle() {
+        let mut world = World::new();
+        let first = world.spawn(Health(1)).expect("budget");
+        world.spawn(Health(2)).expect("budget");
+        assert_eq!(world.spawn_empty(), None);
+
+        // A reservation past the budget never aliases a live entity.
+        let overflow = world.alloc_entity();
+        assert!(!world.is_alive(overflow));
+        world.set_component(overflow, Health(9));
+        assert_eq!(world.query::<&Health>().get(first).ok(), Some(&Health(1)));
+        assert_eq!(world.entity_count(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "component column `boss` has no room for entity 1")]
+    fn flushing_into_a_full_column_panics() {
+        let mut world = World::new();
+        world.spawn(Boss).expect("budget");
+        // `spawn` flushes, applying the second `Boss` right away.
+        world.spawn(Boss);
+    }
+
+    #[system]
+    fn bump_score(score: Option<ResMut<Score>>) {
+        if let Some(mut score) = score {
+            score.0 += 1;
+        }
+    }
+
+    #[system]
+    fn retire(mut commands: Commands, score: Res<Score>) {
+        assert_eq!(score.0, 7);
+        commands.remove_resource::<Score>();
+    }
+
+    #[test]
+    fn optional_resources_skip_nothing_when_absent() {
+        let mut world = World::new();
+        world.add_system(Update, bump_score);
+        world.run_schedule(Update);
+        assert_eq!(world.get_resource::<Score>(), None);
+
+        world.insert_resource(Score(0));
+        world.run_schedule(Update);
+        assert_eq!(world.get_resource::<Score>(), Some(&Score(1)));
+    }
+
+    #[test]
+    fn resource_removal_is_deferred_to_the_flush() {
+        let mut world = World::new();
+        world.insert_resource(Score(7));
+        world.add_system(Update, retire);
+        world.run_schedule(Update);
+        assert_eq!(world.get_resource::<Score>(), Some(&Score(7)));
+        world.flush_commands();
+        assert_eq!(world.get_resource::<Score>(), None);
+
+        world.insert_resource(Score(8));
+        assert_eq!(world.remove_resource::<Score>(), Some(Score(8)));
+        assert_eq!(world.remove_resource::<Score>(), None);
+    }
+
+    #[test]
+    fn init_resource_builds_from_the_world_once() {
+        let mut world = World::new();
+        world.insert_resource(Score(25));
+        world.init_resource::<Level>();
+        assert_eq!(world.get_resource::<Level>(), Some(&Level(3)));
+
+        // An inserted resource is kept.
+        world.insert_resource(Score(0));
+        world.init_resource::<Level>();
+        assert_eq!(world.get_resource::<Level>(), Some(&Level(3)));
+    }
+
+    #[system]
+    fn spawn_health(mut commands: Commands, healthy: Query<&Health>) {
+        if healthy.iter().next().is_none() {
+            commands.spawn(Health(3));
+        }
+    }
+
+    #[system]
+    fn cull(world: &mut World) {
+        // The spawn queued by the previous system was flushed.
+        assert_eq!(world.entity_count(), 1);
+        let entity = world.entity_at(0);
+        assert!(world.despawn(entity));
+        world.spawn(Boss).expect("budget");
+    }
+
+    #[system]
+    fn run_inner(world: &mut World) {
+        let outer = world.ticks;
+        world.run_schedule(PostUpdate);
+        world.run_schedule(Update);
+        assert_eq!(world.ticks.last_run, outer.last_run);
+        assert!(world.ticks.this_run.get() > outer.this_run.get());
+    }
+
+    #[system]
+    fn grow(world: &mut World) {
+        world.add_system(Update, bump_score);
+    }
+
+    #[test]
+    fn exclusive_systems_see_and_leave_flushed_commands() {
+        let mut world = World::new();
+        world.add_systems(Update, (spawn_health, cull).chain());
+        world.run_schedule(Update);
+        assert_eq!(world.query::<&Health>().iter().count(), 0);
+        assert_eq!(world.query::<&Boss>().iter().count(), 1);
+    }
+
+    #[test]
+    fn nested_runs_hand_the_ticks_back() {
+        let mut world = World::new();
+        world.insert_resource(Score(0));
+        world.add_system(PostUpdate, bump_score);
+        world.add_system(Update, run_inner);
+        world.run_schedule(Update);
+        world.run_schedule(Update);
+        assert_eq!(world.get_resource::<Score>(), Some(&Score(2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "added to its schedule while the schedule runs")]
+    fn adding_to_a_running_schedule_panics() {
+        let mut world = World::new();
+        world.add_system(Update, grow);
+        world.run_schedule(Update);
+    }
+}
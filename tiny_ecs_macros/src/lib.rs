@@ -1,18 +1,22 @@
 //! Procedural macros for `tiny_ecs`: the marker derives
 //! ([`Component`](macro.Component.html), [`Resource`](macro.Resource.html),
-//! [`ScheduleLabel`](macro.ScheduleLabel.html)), the [`system`](macro.system.html)
+//! [`ScheduleLabel`](macro.ScheduleLabel.html),
+//! [`SystemSet`](macro.SystemSet.html)), the
+//! [`Bundle`](macro.Bundle.html) and [`SystemParam`](macro.SystemParam.html)
+//! derives, the [`system`](macro.system.html)
 //! attribute that expands Bevy-style system functions, and the
 //! [`define_world!`](macro.define_world.html) builder that lays out the concrete
 //! `World` struct per application.
 //!
-//! The derive macros are deliberately thin: `Component`/`Resource` are plain
-//! marker impls, and `ScheduleLabel` just seals the blanketed trait. `system`
+//! The derive macros are deliberately thin: `Resource` is a plain marker
+//! impl, `Component` adds only its required components and hooks, and
+//! `ScheduleLabel` just seals the blanketed trait. `system`
 //! and `define_world` carry the real work.
 
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
-use syn::{parse_macro_input, DeriveInput, ItemFn};
+use syn::{parse_macro_input, parse_quote, DeriveInput, ItemFn};
 
 mod world_macro;
 use world_macro::WorldInput;
@@ -20,16 +24,129 @@ use world_macro::WorldInput;
 // ---------------------------------------------------------------------
 // --- Marker derives --------------------------------------------------
 
-/// Derives [`tiny_ecs::Component`] for the annotated type.
-#[proc_macro_derive(Component)]
+/// Derives [`tiny_ecs::Component`] for the annotated type, together with
+/// `tiny_ecs::component::RequiredComponents` and
+/// `tiny_ecs::component::ComponentHooks`.
+///
+/// Required components are listed as
+/// `#[component(requires(MoveCooldown = default_cooldown, Other))]`: each is
+/// inserted with its constructor (a `fn() -> T` path, or `Default::default`
+/// when omitted) whenever the annotated component lands on an entity lacking
+/// it. Lifecycle hooks are named as `#[component(on_remove = crash_sound)]`
+/// (likewise `on_add` / `on_insert`), each a `#[system]`.
+#[proc_macro_derive(Component, attributes(component))]
 pub fn derive_component(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    expand_component(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// One `requires(..)` entry: the required type and its optional constructor.
+struct Required {
+    /// The required component type.
+    ty: syn::Type,
+    /// The `fn() -> T` constructor; `Default::default` when `None`.
+    ctor: Option<syn::Path>,
+}
+
+fn expand_component(input: DeriveInput) -> syn::Result<TokenStream2> {
     let name = &input.ident;
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // Collect `#[component(requires(..), on_add = ..)]` entries.
+    let mut required: Vec<Required> = Vec::new();
+    let mut hooks: Vec<(syn::Ident, syn::Path)> = Vec::new();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("component")) {
+        attr.parse_nested_meta(|meta| {
+            for hook in ["on_add", "on_insert", "on_remove"] {
+                if meta.path.is_ident(hook) {
+                    hooks.push((format_ident!("{}", hook), meta.value()?.parse()?));
+                    return Ok(());
+                }
+            }
+            if !meta.path.is_ident("requires") {
+                return Err(meta.error("expected `requires(..)`, `on_add`, `on_insert` or `on_remove`"));
+            }
+            meta.parse_nested_meta(|entry| {
+                let ty: syn::Type = syn::Type::Path(syn::TypePath {
+                    qself: None,
+                    path: entry.path.clone(),
+                });
+                let ctor = if entry.input.peek(syn::Token![=]) {
+                    Some(entry.value()?.parse()?)
+                } else {
+                    None
+                };
+                required.push(Required { ty, ctor });
+                Ok(())
+            })
+        })?;
+    }
+
+    // `RequiredComponents<__W>` bounds `__W` by a queue for each requirement.
+    let w = format_ident!("__W");
+    let mut generics = input.generics.clone();
+    generics.params.push(parse_quote!(#w));
+    let req_where = generics.make_where_clause();
+    for r in &required {
+        let ty = &r.ty;
+        req_where
+            .predicates
+            .push(parse_quote!(#w: ::tiny_ecs::system::EntityCommandsRef<#ty>));
+    }
+    let (req_impl_generics, _, req_where_clause) = generics.split_for_impl();
+    let enqueues = required.iter().map(|r| {
+        let ty = &r.ty;
+        let ctor = match &r.ctor {
+            Some(path) => quote!(#path),
+            None => quote!(<#ty as ::core::default::Default>::default),
+        };
+        quote! {
+            // SAFETY: the caller guarantees `world` is valid.
+            unsafe {
+                <#w as ::tiny_ecs::system::EntityCommandsRef<#ty>>::enqueue_require(world, entity, #ctor);
+            }
+        }
+    });
+    let enqueue_fn = (!required.is_empty()).then(|| {
+        quote! {
+            unsafe fn enqueue_required(world: *mut #w, entity: ::tiny_ecs::entity::Entity) {
+                #( #enqueues )*
+            }
+        }
+    });
+
+    // `ComponentHooks<__W>` bounds `__W` by the world each hook system needs.
+    let mut generics = input.generics.clone();
+    generics.params.push(parse_quote!(#w));
+    let hook_where = generics.make_where_clause();
+    for (_, path) in &hooks {
+        hook_where
+            .predicates
+            .push(parse_quote!(#path: ::tiny_ecs::system::IntoSystem<#w>));
+    }
+    let (hook_impl_generics, _, hook_where_clause) = generics.split_for_impl();
+    let hook_fns = hooks.iter().map(|(hook, path)| {
+        quote! {
+            fn #hook() -> ::core::option::Option<::tiny_ecs::system::System> {
+                ::core::option::Option::Some(::tiny_ecs::system::IntoSystem::<#w>::into_system(#path))
+            }
+        }
+    });
+
     let expanded = quote! {
         impl #impl_generics ::tiny_ecs::Component for #name #ty_generics #where_clause {}
+
+        impl #req_impl_generics ::tiny_ecs::component::RequiredComponents<#w> for #name #ty_generics #req_where_clause {
+            #enqueue_fn
+        }
+
+        impl #hook_impl_generics ::tiny_ecs::component::ComponentHooks<#w> for #name #ty_generics #hook_where_clause {
+            #( #hook_fns )*
+        }
     };
-    expanded.into()
+    Ok(expanded)
 }
 
 /// Derives [`tiny_ecs::Resource`] for the annotated type.
@@ -61,13 +178,164 @@ pub fn derive_schedule_label(input: TokenStream) -> TokenStream {
     expanded.into()
 }
 
+/// Derives [`tiny_ecs::schedule::SystemSet`] for the annotated type.
+///
+/// Like a schedule label, a set is identified by its
+/// [`TypeId`](core::any::TypeId): derive this on a unit struct, then pass the
+/// value to `in_set` and `App::configure_set`.
+#[proc_macro_derive(SystemSet)]
+pub fn derive_system_set(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let expanded = quote! {
+        impl #impl_generics ::tiny_ecs::schedule::SystemSet for #name #ty_generics #where_clause {}
+    };
+    expanded.into()
+}
+
+// ---------------------------------------------------------------------
+// --- `#[derive(Bundle)]` ---------------------------------------------
+
+/// Derives [`tiny_ecs::Bundle`] for a struct whose fields are all bundles
+/// (components or nested bundles), inserting each field in declaration order.
+#[proc_macro_derive(Bundle)]
+pub fn derive_bundle(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_bundle(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_bundle(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let syn::Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(&input, "Bundle can only be derived for structs"));
+    };
+    let w = format_ident!("__W");
+
+    // Every field must itself be a bundle for the same world.
+    let mut generics = input.generics.clone();
+    generics.params.push(parse_quote!(#w));
+    let where_clause = generics.make_where_clause();
+    for field in &data.fields {
+        let ty = &field.ty;
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: ::tiny_ecs::bundle::Bundle<#w>));
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let members = data.fields.members();
+    let expanded = quote! {
+        impl #impl_generics ::tiny_ecs::bundle::Bundle<#w> for #name #ty_generics #where_clause {
+            unsafe fn enqueue_insert(self, world: *mut #w, entity: ::tiny_ecs::entity::Entity) {
+                // SAFETY: the caller guarantees `world` is valid.
+                #( unsafe { ::tiny_ecs::bundle::Bundle::<#w>::enqueue_insert(self.#members, world, entity) }; )*
+            }
+        }
+    };
+    Ok(expanded)
+}
+
+// ---------------------------------------------------------------------
+// --- `#[derive(SystemParam)]` ----------------------------------------
+
+/// Derives `tiny_ecs::SystemParam` and `tiny_ecs::system_param::SystemParamFetch`
+/// for a struct whose fields are all system params, so `#[system]`s can take
+/// the struct in their place. The struct may be generic over lifetimes only;
+/// its state is the tuple of its fields' states. Fields that alias each other
+/// are rejected as in `#[system]`, and what the fields borrow is exported as
+/// `SystemParam::ACCESS` for the systems taking the struct to check.
+#[proc_macro_derive(SystemParam)]
+pub fn derive_system_param(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_system_param(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_system_param(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let syn::Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(&input, "SystemParam can only be derived for structs"));
+    };
+    if let Some(param) = input.generics.type_params().next() {
+        return Err(syn::Error::new_spanned(
+            param,
+            "SystemParam structs may only be generic over lifetimes",
+        ));
+    }
+    // The fields are fetched from one world, so they must not alias either.
+    let fields: Vec<(SystemParam, String, &syn::Field)> = data
+        .fields
+        .iter()
+        .zip(data.fields.members())
+        .map(|(field, member)| {
+            let name = match member {
+                syn::Member::Named(ident) => ident.to_string(),
+                syn::Member::Unnamed(index) => index.index.to_string(),
+            };
+            parse_param_type(&field.ty).map(|param| (param, name, field))
+        })
+        .collect::<syn::Result<_>>()?;
+    check_access(fields.iter().map(|(param, name, field)| (param, name.clone(), *field)), &[])?;
+    let checks = custom_checks(fields.iter().map(|(param, name, _)| (param, name.clone())), &[]);
+    let access = fields.iter().map(|(param, _, _)| match param {
+        SystemParam::Custom(ty) => quote!(::tiny_ecs::system_param::Access::Group(<#ty as ::tiny_ecs::system_param::SystemParam>::ACCESS)),
+        param => ParamAccess::of(param).to_tokens(&[]),
+    });
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let types: Vec<&syn::Type> = data.fields.iter().map(|f| &f.ty).collect();
+    let members = data.fields.members();
+    let indices = (0..types.len()).map(syn::Index::from);
+
+    // Building the struct needs every field buildable from the same world.
+    let w = format_ident!("__W");
+    let mut generics = input.generics.clone();
+    generics.params.push(parse_quote!(#w));
+    let fetch_where = generics.make_where_clause();
+    for ty in &types {
+        fetch_where
+            .predicates
+            .push(parse_quote!(#ty: ::tiny_ecs::system_param::SystemParamFetch<#w>));
+    }
+    let (fetch_impl_generics, _, fetch_where_clause) = generics.split_for_impl();
+
+    let expanded = quote! {
+        impl #impl_generics ::tiny_ecs::system_param::SystemParam for #name #ty_generics #where_clause {
+            type State = ( #( <#types as ::tiny_ecs::system_param::SystemParam>::State, )* );
+            const ACCESS: &'static [::tiny_ecs::system_param::Access] = &[ #(#access),* ];
+            fn init_state() -> Self::State {
+                ( #( <#types as ::tiny_ecs::system_param::SystemParam>::init_state(), )* )
+            }
+        }
+
+        unsafe impl #fetch_impl_generics ::tiny_ecs::system_param::SystemParamFetch<#w> for #name #ty_generics #fetch_where_clause {
+            unsafe fn get_param(world: *mut #w, state: *mut Self::State) -> Self {
+                Self {
+                    // SAFETY: the caller guarantees `world` and `state` are
+                    // valid; each field gets its own slot of the state.
+                    #( #members: unsafe {
+                        <#types as ::tiny_ecs::system_param::SystemParamFetch<#w>>::get_param(world, &raw mut (*state).#indices)
+                    }, )*
+                }
+            }
+        }
+
+        #checks
+    };
+    Ok(expanded)
+}
+
 // ---------------------------------------------------------------------
 // --- `define_world!` -------------------------------------------------
 
 /// Generates the concrete `World` struct for an application, together with the
-/// `ColumnRef`/`ResourceRef`/`ResourceInsRef`/`SpawnRef`/`CommandsRef` impls
-/// for every registered component/resource, and the `WorldApi` impl driving
-/// schedules and entity lifetimes.
+/// `ColumnRef`/`ResourceRef`/`ResourceInsRef`/`EntityCommandsRef`/
+/// `EntitiesRef`/`CommandsRef` impls for every registered component/resource, and the
+/// `WorldApi` impl driving schedules and entity lifetimes.
 #[proc_macro]
 pub fn define_world(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as WorldInput);
@@ -86,13 +354,63 @@ enum SystemParam {
     Res(syn::Type),
     /// `ResMut<T>`: exclusive mutable resource.
     ResMut(syn::Type),
-    /// `Query<P>`: a query over component columns.
-    Query(syn::Type),
+    /// `Option<Res<T>>`: shared resource, `None` when not inserted.
+    OptRes(syn::Type),
+    /// `Option<ResMut<T>>`: mutable resource, `None` when not inserted.
+    OptResMut(syn::Type),
+    /// `Query<D>` / `Query<D, F>`: a query over component columns, with an
+    /// optional filter.
+    Query(syn::Type, Option<Box<syn::Type>>),
+    /// `Single<D>` / `Single<D, F>`: the one entity matching a query; the
+    /// system is skipped otherwise.
+    Single(syn::Type, Option<Box<syn::Type>>),
     /// `Commands`: spawn / despawn / insert_resource handle.
     Commands,
+    /// `Trigger<E>`: the entity a hook or observer runs for.
+    Trigger(syn::Type),
+    /// `RemovedComponents<T>`: entities that lost a `T` since the last run.
+    RemovedComponents(syn::Type),
+    /// `EventWriter<E>`: sends events of type `E`.
+    EventWriter(syn::Type),
+    /// `EventReader<E>`: reads the events of type `E` not yet seen.
+    EventReader(syn::Type),
+    /// `Local<T>`: private state persisting across runs of the system.
+    Local(syn::Type),
+    /// `&mut W`: exclusive access to the whole world `W`.
+    World(syn::Type),
+    /// Any other type, built through `tiny_ecs::system_param::SystemParamFetch`
+    /// (e.g. a `#[derive(SystemParam)]` struct).
+    Custom(syn::Type),
 }
 
 /// The `#[system]` attribute macro.
+///
+/// Any param type other than the built-in ones must implement
+/// `tiny_ecs::SystemParam`, typically via `#[derive(SystemParam)]`, and spell
+/// out its lifetimes as `'_` (`round: Round<'_>`).
+///
+/// A system may be generic over types and consts, e.g.
+/// `fn despawn_all<T: Component>(..)`, and is then added as
+/// `despawn_all::<Debris>`; its bounds carry over to the generated
+/// `IntoSystem` impl. Each instantiation keeps its own `Local`s, so the type
+/// params of a system with state must be `'static`.
+///
+/// A system may return `Result<(), E>` for any `E: tiny_ecs::error::SystemError`;
+/// an `Err` goes to the handler installed with `App::set_error_handler`.
+///
+/// A system returning `bool` is a run condition, passed to `run_if` rather
+/// than added to a schedule. It must be read-only: no `ResMut`, `&mut`
+/// queries, `Commands`, `EventWriter` or `&mut World`.
+///
+/// Params that alias each other are a compile error: the same resource as
+/// `Res` and `ResMut`, a component borrowed `&mut` by one query and at all by
+/// another (unless a `Without<T>` filter makes them disjoint), `EventReader`
+/// and `EventWriter` of one event, or two `Commands`.
+///
+/// A system taking `world: &mut World` is *exclusive*: it runs with the
+/// world's commands flushed before and after it, may call `spawn`,
+/// `despawn`, `run_schedule` and friends directly, and accepts no other
+/// param besides `Local<T>`. It is registered like any other system.
 #[proc_macro_attribute]
 pub fn system(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let item = parse_macro_input!(item as ItemFn);
@@ -106,12 +424,6 @@ fn expand_system(item: ItemFn) -> syn::Result<TokenStream2> {
     if sig.asyncness.is_some() {
         return Err(syn::Error::new_spanned(sig, "async systems are not supported"));
     }
-    if !sig.generics.params.is_empty() {
-        return Err(syn::Error::new_spanned(
-            &sig.generics,
-            "system functions may not declare their own generic parameters",
-        ));
-    }
     let inputs = &sig.inputs;
     let body = &item.block;
     let vis = &item.vis;
@@ -130,41 +442,94 @@ fn expand_system(item: ItemFn) -> syn::Result<TokenStream2> {
                 let pat = &pt.pat;
                 let ty = &pt.ty;
                 let (ident, mut_b) = pat_ident(pat)?;
-                let parsed = parse_param_type(ty)
-                    .ok_or_else(|| syn::Error::new_spanned(ty, "unsupported system parameter type"))?;
+                let parsed = parse_param_type(ty)?;
                 params.push((parsed, ident, mut_b));
             }
         }
     }
+    // A generic type may be instantiated as any type, so it aliases them all.
+    let type_params: Vec<syn::Ident> = sig.generics.type_params().map(|t| t.ident.clone()).collect();
+    check_access(params.iter().zip(inputs).map(|((p, ident, _), arg)| (p, ident.to_string(), arg)), &type_params)?;
+    let mut checks = custom_checks(params.iter().map(|(p, ident, _)| (p, ident.to_string())), &type_params);
+    let condition = is_condition(&sig.output);
+    if condition {
+        check_read_only(&params, inputs)?;
+        checks.extend(custom_read_only_checks(&params));
+    }
+    let generics = SystemGenerics::new(sig)?;
+    if params.iter().any(|(p, _, _)| matches!(p, SystemParam::World(_))) {
+        return expand_exclusive_system(&item, &params, &generics);
+    }
 
     let w = format_ident!("__W");
-    // Build generic bounds: `__W: ColumnRef<T> + SpawnRef<T> + HasResource<R> +
-    // ...`. `HasResource<R>` bundles `ResourceRef<R> + ResourceInsRef<R>`, so a
+    // Build generic bounds: `__W: ColumnRef<T> + EntityCommandsRef<T> +
+    // HasResource<R> + ...`. `HasResource<R>` bundles `ResourceRef<R> + ResourceInsRef<R>`, so a
     // system can both read (`Res`/`ResMut`) and insert a resource it touches.
-    // `SpawnRef<T>` next to every queried component lets a system
-    // `commands.spawn::<T>(value)` for a component it also queries.
+    // `EntityCommandsRef<T>` next to every queried component lets a system
+    // `commands.spawn(bundle)` or `commands.entity(e).insert(bundle)` /
+    // `.remove::<T>()` for components it also queries.
     let mut bounds: Vec<syn::TypeParamBound> = Vec::new();
     bounds.push(parse_quote!(Sized));
+    // Custom params bound the world through their `SystemParamFetch` impl,
+    // which carries the union of their fields' bounds.
+    let mut predicates: Vec<syn::WherePredicate> = Vec::new();
     for (p, _, _) in &params {
         match p {
-            SystemParam::Query(ty) => {
-                for comp in components_in_fetch(ty) {
+            SystemParam::Query(ty, filter) | SystemParam::Single(ty, filter) => {
+                if matches!(p, SystemParam::Single(..)) {
+                    bounds.push(parse_quote!(::tiny_ecs::system::DiagnosticsRef));
+                }
+                bounds.push(parse_quote!(::tiny_ecs::system::EntitiesRef));
+                bounds.push(parse_quote!(::tiny_ecs::system::ChangeTicksRef));
+                let filtered = filter.iter().flat_map(|f| components_in_fetch(f));
+                for comp in components_in_fetch(ty).into_iter().chain(filtered) {
                     bounds.push(parse_quote!(::tiny_ecs::system::ColumnRef<#comp>));
-                    bounds.push(parse_quote!(::tiny_ecs::system::SpawnRef<#comp>));
+                    bounds.push(parse_quote!(::tiny_ecs::system::EntityCommandsRef<#comp>));
                 }
             }
-            SystemParam::Res(ty) | SystemParam::ResMut(ty) => {
+            SystemParam::Res(ty)
+            | SystemParam::ResMut(ty)
+            | SystemParam::OptRes(ty)
+            | SystemParam::OptResMut(ty) => {
                 bounds.push(parse_quote!(::tiny_ecs::system::HasResource<#ty>));
+                bounds.push(parse_quote!(::tiny_ecs::system::ChangeTicksRef));
             }
             SystemParam::Commands => {
                 bounds.push(parse_quote!(::tiny_ecs::system::CommandsRef));
             }
+            SystemParam::Trigger(_) => {
+                bounds.push(parse_quote!(::tiny_ecs::system::TriggerRef));
+            }
+            SystemParam::RemovedComponents(ty) => {
+                bounds.push(parse_quote!(::tiny_ecs::system::RemovedRef<#ty>));
+                bounds.push(parse_quote!(::tiny_ecs::system::ChangeTicksRef));
+            }
+            SystemParam::EventWriter(ty) => {
+                bounds.push(parse_quote!(::tiny_ecs::system::EventsRef<#ty>));
+            }
+            SystemParam::EventReader(ty) => {
+                bounds.push(parse_quote!(::tiny_ecs::system::EventsRef<#ty>));
+                bounds.push(parse_quote!(::tiny_ecs::system::LocalsRef));
+            }
+            SystemParam::Local(_) => {
+                bounds.push(parse_quote!(::tiny_ecs::system::LocalsRef));
+            }
+            SystemParam::World(_) => unreachable!("exclusive systems are expanded separately"),
+            SystemParam::Custom(ty) => {
+                bounds.push(parse_quote!(::tiny_ecs::system::LocalsRef));
+                let lt: syn::Lifetime = parse_quote!('__p);
+                let (ty, elided) = replace_elided(ty, &lt);
+                let hrtb = elided.then(|| quote!(for<#lt>));
+                predicates.push(parse_quote!(#hrtb #ty: ::tiny_ecs::system_param::SystemParamFetch<#w>));
+            }
         }
     }
 
     // Build parameter bindings emitted at the top of the generated fn body.
     let mut bindings: Vec<TokenStream2> = Vec::new();
-    for (p, ident, mut_b) in &params {
+    let system_name = user_name.to_string();
+    for (index, (p, ident, mut_b)) in params.iter().enumerate() {
+        let key = generics.local_key(&sys_struct_name, index);
         let mut_kw = if *mut_b { quote!(mut) } else { quote!() };
         match p {
             SystemParam::Res(ty) => {
@@ -173,8 +538,11 @@ fn expand_system(item: ItemFn) -> syn::Result<TokenStream2> {
                         // SAFETY: `__w` is valid for this system's duration.
                         let __ptr = unsafe { <#w as ::tiny_ecs::system::ResourceRef<#ty>>::res_ref_raw(__w) };
                         assert!(!__ptr.is_null(), concat!("resource not inserted: ", stringify!(#ty)));
+                        // SAFETY: as above; the tick slots are disjoint from the values.
+                        let __ticks = unsafe { *<#w as ::tiny_ecs::system::ResourceRef<#ty>>::res_ticks_raw(__w) };
+                        let __system = unsafe { <#w as ::tiny_ecs::system::ChangeTicksRef>::system_ticks(__w) };
                         // SAFETY: the non-null check above guarantees validity.
-                        unsafe { ::tiny_ecs::system::Res::new(&*__ptr) }
+                        unsafe { ::tiny_ecs::system::Res::new(&*__ptr, __ticks, __system) }
                     };
                 });
             }
@@ -184,18 +552,83 @@ fn expand_system(item: ItemFn) -> syn::Result<TokenStream2> {
                         // SAFETY: `__w` is valid for this system's duration.
                         let __ptr = unsafe { <#w as ::tiny_ecs::system::ResourceRef<#ty>>::res_mut_raw(__w) };
                         assert!(!__ptr.is_null(), concat!("resource not inserted: ", stringify!(#ty)));
+                        // SAFETY: as above; the tick slots are disjoint from the values.
+                        let __ticks = unsafe { <#w as ::tiny_ecs::system::ResourceRef<#ty>>::res_ticks_raw(__w) };
+                        let __system = unsafe { <#w as ::tiny_ecs::system::ChangeTicksRef>::system_ticks(__w) };
                         // SAFETY: the non-null check above guarantees validity.
-                        unsafe { ::tiny_ecs::system::ResMut::new(&mut *__ptr) }
+                        unsafe { ::tiny_ecs::system::ResMut::new(&mut *__ptr, &mut *__ticks, __system) }
                     };
                 });
             }
-            SystemParam::Query(ty) => {
+            SystemParam::OptRes(ty) => {
                 bindings.push(quote! {
-                    let #mut_kw #ident: ::tiny_ecs::system::Query<'_, #ty, #w> =
+                    let #mut_kw #ident: ::core::option::Option<::tiny_ecs::system::Res<'_, #ty>> = {
+                        // SAFETY: `__w` is valid for this system's duration.
+                        let __ptr = unsafe { <#w as ::tiny_ecs::system::ResourceRef<#ty>>::res_ref_raw(__w) };
+                        if __ptr.is_null() {
+                            ::core::option::Option::None
+                        } else {
+                            // SAFETY: as above; the tick slots are disjoint from the values.
+                            let __ticks = unsafe { *<#w as ::tiny_ecs::system::ResourceRef<#ty>>::res_ticks_raw(__w) };
+                            let __system = unsafe { <#w as ::tiny_ecs::system::ChangeTicksRef>::system_ticks(__w) };
+                            // SAFETY: the non-null check above guarantees validity.
+                            ::core::option::Option::Some(unsafe { ::tiny_ecs::system::Res::new(&*__ptr, __ticks, __system) })
+                        }
+                    };
+                });
+            }
+            SystemParam::OptResMut(ty) => {
+                bindings.push(quote! {
+                    let #mut_kw #ident: ::core::option::Option<::tiny_ecs::system::ResMut<'_, #ty>> = {
+                        // SAFETY: `__w` is valid for this system's duration.
+                        let __ptr = unsafe { <#w as ::tiny_ecs::system::ResourceRef<#ty>>::res_mut_raw(__w) };
+                        if __ptr.is_null() {
+                            ::core::option::Option::None
+                        } else {
+                            // SAFETY: as above; the tick slots are disjoint from the values.
+                            let __ticks = unsafe { <#w as ::tiny_ecs::system::ResourceRef<#ty>>::res_ticks_raw(__w) };
+                            let __system = unsafe { <#w as ::tiny_ecs::system::ChangeTicksRef>::system_ticks(__w) };
+                            // SAFETY: the non-null check above guarantees validity.
+                            ::core::option::Option::Some(unsafe { ::tiny_ecs::system::ResMut::new(&mut *__ptr, &mut *__ticks, __system) })
+                        }
+                    };
+                });
+            }
+            SystemParam::Query(ty, filter) => {
+                let filter = filter.clone().unwrap_or_else(|| Box::new(parse_quote!(())));
+                bindings.push(quote! {
+                    let #mut_kw #ident: ::tiny_ecs::system::Query<'_, #ty, #w, #filter> =
                         // SAFETY: `__w` is a valid `*mut #w`.
                         unsafe { ::tiny_ecs::system::Query::from_world(__w) };
                 });
             }
+            SystemParam::Single(ty, filter) => {
+                let filter = filter.clone().unwrap_or_else(|| Box::new(parse_quote!(())));
+                let system = user_name.to_string();
+                let param = ident.to_string();
+                // A condition whose `Single` finds no match does not hold.
+                let skipped = condition.then(|| quote!(false));
+                bindings.push(quote! {
+                    let #mut_kw #ident: ::tiny_ecs::system::Single<'_, #ty, #w, #filter> = {
+                        // SAFETY: `__w` is a valid `*mut #w`.
+                        let mut __query: ::tiny_ecs::system::Query<'_, #ty, #w, #filter> =
+                            unsafe { ::tiny_ecs::system::Query::from_world(__w) };
+                        match __query.single_mut() {
+                            ::core::result::Result::Ok(__item) => ::tiny_ecs::system::Single::new(__item),
+                            ::core::result::Result::Err(__error) => {
+                                let __skipped = ::tiny_ecs::system::SystemSkipped {
+                                    system: #system,
+                                    param: #param,
+                                    error: __error,
+                                };
+                                // SAFETY: `__w` is valid for this system's duration.
+                                unsafe { <#w as ::tiny_ecs::system::DiagnosticsRef>::report_skip(__w, __skipped) };
+                                return #skipped;
+                            }
+                        }
+                    };
+                });
+            }
             SystemParam::Commands => {
                 bindings.push(quote! {
                     let #mut_kw #ident: ::tiny_ecs::commands::Commands<'_, #w> = {
@@ -206,31 +639,402 @@ fn expand_system(item: ItemFn) -> syn::Result<TokenStream2> {
                     };
                 });
             }
+            SystemParam::Trigger(ty) => {
+                bindings.push(quote! {
+                    let #mut_kw #ident: ::tiny_ecs::observer::Trigger<#ty> =
+                        // SAFETY: `__w` is valid for this system's duration.
+                        ::tiny_ecs::observer::Trigger::new(unsafe { <#w as ::tiny_ecs::system::TriggerRef>::trigger_entity(__w) });
+                });
+            }
+            SystemParam::RemovedComponents(ty) => {
+                bindings.push(quote! {
+                    let #mut_kw #ident: ::tiny_ecs::removal_detection::RemovedComponents<'_, #ty> = {
+                        // SAFETY: `__w` is valid; the log is only written by flushes.
+                        let __log = unsafe { <#w as ::tiny_ecs::system::RemovedRef<#ty>>::removed_raw(__w) };
+                        let __system = unsafe { <#w as ::tiny_ecs::system::ChangeTicksRef>::system_ticks(__w) };
+                        // SAFETY: the log lives as long as the world.
+                        ::tiny_ecs::removal_detection::RemovedComponents::new(unsafe { &*__log }, __system)
+                    };
+                });
+            }
+            SystemParam::EventWriter(ty) => {
+                bindings.push(quote! {
+                    let #mut_kw #ident: ::tiny_ecs::event::EventWriter<'_, #ty> = {
+                        // SAFETY: `__w` is valid; the buffers are a distinct field.
+                        let __events = unsafe { <#w as ::tiny_ecs::system::EventsRef<#ty>>::events_raw(__w) };
+                        ::tiny_ecs::event::EventWriter::new(unsafe { &mut *__events })
+                    };
+                });
+            }
+            SystemParam::EventReader(ty) => {
+                bindings.push(quote! {
+                    let #mut_kw #ident: ::tiny_ecs::event::EventReader<'_, '_, #ty> = {
+                        // SAFETY: `__w` is valid; the buffers are a distinct field.
+                        let __events = unsafe { <#w as ::tiny_ecs::system::EventsRef<#ty>>::events_raw(__w) };
+                        // The cursor persists across runs of this system;
+                        // systems never run re-entrantly.
+                        let __cursor = unsafe { <#w as ::tiny_ecs::system::LocalsRef>::local_raw::<usize>(__w, #key, #system_name, || 0) };
+                        ::tiny_ecs::event::EventReader::new(unsafe { &*__events }, unsafe { &mut *__cursor })
+                    };
+                });
+            }
+            SystemParam::Local(ty) => {
+                let local = local_slot(&w, ty, &key, &system_name);
+                bindings.push(quote! {
+                    // SAFETY: systems never run re-entrantly.
+                    let #mut_kw #ident: ::tiny_ecs::system::Local<'_, #ty> = ::tiny_ecs::system::Local::new(unsafe { &mut *#local });
+                });
+            }
+            SystemParam::World(_) => unreachable!("exclusive systems are expanded separately"),
+            SystemParam::Custom(ty) => {
+                let (static_ty, _) = replace_elided(ty, &parse_quote!('static));
+                bindings.push(quote! {
+                    let #mut_kw #ident: #ty = {
+                        // SAFETY: `__w` is valid; systems never run re-entrantly.
+                        let __state = unsafe {
+                            <#w as ::tiny_ecs::system::LocalsRef>::local_raw(
+                                __w,
+                                #key,
+                                #system_name,
+                                <#static_ty as ::tiny_ecs::system_param::SystemParam>::init_state,
+                            )
+                        };
+                        // SAFETY: `__w` is valid for this system's duration.
+                        unsafe { <#ty as ::tiny_ecs::system_param::SystemParamFetch<#w>>::get_param(__w, __state) }
+                    };
+                });
+            }
         }
     }
 
+    // A fallible body runs in a closure, so its `return`s and `?`s yield the
+    // result to hand to the error handler; a condition's body is the result.
+    let run = match &sig.output {
+        syn::ReturnType::Default => quote!(#body),
+        syn::ReturnType::Type(..) if condition => quote!(#body),
+        syn::ReturnType::Type(_, ret) => {
+            bounds.push(parse_quote!(::tiny_ecs::system::DiagnosticsRef));
+            let report = report_error(&user_name, &quote!(#w));
+            quote! {
+                let __result: #ret = (move || -> #ret #body)();
+                #report
+            }
+        }
+    };
+
+    let SystemGenerics { params: user_params, args, where_preds } = &generics;
+    let sys_struct = generics.struct_item(vis, &sys_struct_name);
+    let static_pred = generics.static_pred(&sys_struct_name, &params);
+    let (output, into_trait) = if condition {
+        (quote!(-> bool), quote!(::tiny_ecs::system::IntoCondition<#w>))
+    } else {
+        (quote!(), quote!(::tiny_ecs::system::IntoSystem<#w>))
+    };
+    let into_fn = if condition {
+        quote!(fn into_condition(self) -> ::tiny_ecs::system::Condition)
+    } else {
+        quote!(fn into_system(self) -> ::tiny_ecs::system::System)
+    };
     let expanded = quote! {
         #[doc(hidden)]
-        #vis fn #sys_fn_name<#w: #(#bounds)+*>(__world: *mut ()) {
+        #vis fn #sys_fn_name<#(#user_params,)* #w: #(#bounds)+*>(__world: *mut ()) #output
+        where
+            #(#where_preds,)*
+            #(#predicates,)*
+            #static_pred
+        {
             // SAFETY: the runner guarantees `__world` is a valid `*mut #w`.
             let __w: *mut #w = __world as *mut #w;
             #(#bindings)*
-            #body
+            #run
+        }
+
+        #sys_struct
+
+        impl<#(#user_params,)* #w: #(#bounds)+*> #into_trait for #sys_struct_name<#(#args),*>
+        where
+            #(#where_preds,)*
+            #(#predicates,)*
+            #static_pred
+        {
+            #into_fn {
+                #sys_fn_name::<#(#args,)* #w>
+            }
+        }
+
+        #checks
+    };
+    Ok(expanded)
+}
+
+/// Expands an exclusive system: the user's function becomes an inner fn
+/// called with the concrete world and its `Local`s, between two flushes.
+fn expand_exclusive_system(
+    item: &ItemFn,
+    params: &[(SystemParam, syn::Ident, bool)],
+    generics: &SystemGenerics,
+) -> syn::Result<TokenStream2> {
+    let sig = &item.sig;
+    let vis = &item.vis;
+    let sys_fn_name = format_ident!("{}_sys", sig.ident);
+    let sys_struct_name = format_ident!("{}", sig.ident);
+
+    // Exactly one `&mut W`, alongside `Local`s only.
+    let mut worlds = params.iter().zip(&sig.inputs).filter_map(|((p, _, _), arg)| match p {
+        SystemParam::World(ty) => Some((ty, arg)),
+        _ => None,
+    });
+    let (world_ty, _) = worlds.next().expect("caller checked for a world param");
+    if let Some((_, arg)) = worlds.next() {
+        return Err(syn::Error::new_spanned(arg, "an exclusive system takes the world only once"));
+    }
+    // The `&mut W` covers the world's state store, so each `Local` is moved
+    // out of it for the run and back afterwards.
+    let mut call_args: Vec<TokenStream2> = Vec::new();
+    let mut takes: Vec<TokenStream2> = Vec::new();
+    let system_name = sig.ident.to_string();
+    for (index, ((p, _, _), arg)) in params.iter().zip(&sig.inputs).enumerate() {
+        match p {
+            // SAFETY: the runner guarantees `__w` is valid and no other param
+            // borrows the world.
+            SystemParam::World(_) => call_args.push(quote!(unsafe { &mut *__w })),
+            SystemParam::Local(ty) => {
+                let taken = format_ident!("__local_{}", index);
+                let key = generics.local_key(&sys_struct_name, index);
+                let slot = local_slot(&quote!(#world_ty), ty, &key, &system_name);
+                // SAFETY: `__w` is valid, and the slot is only reached through
+                // this system, which never runs re-entrantly.
+                takes.push(quote!(let mut #taken = unsafe { ::tiny_ecs::system::TakenLocal::take(#slot) };));
+                call_args.push(quote!(#taken.local()));
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    arg,
+                    "an exclusive system accepts no param besides `&mut World` and `Local<T>`",
+                ));
+            }
         }
+    }
+    let inputs = &sig.inputs;
+    let output = &sig.output;
+    let body = &item.block;
+
+    let (result, report) = match output {
+        syn::ReturnType::Default => (None, None),
+        syn::ReturnType::Type(..) => (Some(quote!(let __result =)), Some(report_error(&sig.ident, &quote!(#world_ty)))),
+    };
+
+    let SystemGenerics { params: user_params, args, where_preds } = generics;
+    let sys_struct = generics.struct_item(vis, &sys_struct_name);
+    let static_pred = generics.static_pred(&sys_struct_name, params);
 
+    let expanded = quote! {
         #[doc(hidden)]
-        #[allow(non_camel_case_types)]
-        #vis struct #sys_struct_name;
+        #vis fn #sys_fn_name<#(#user_params),*>(__world: *mut ())
+        where
+            #(#where_preds,)*
+            #static_pred
+        {
+            fn __run<#(#user_params),*>(#inputs) #output
+            where
+                #(#where_preds,)*
+            #body
+
+            // SAFETY: the runner guarantees `__world` is a valid `*mut #world_ty`.
+            let __w: *mut #world_ty = __world as *mut #world_ty;
+            // Apply what earlier systems queued, so the system sees it.
+            ::tiny_ecs::world::WorldApi::flush_commands(unsafe { &mut *__w });
+            #result {
+                #(#takes)*
+                __run::<#(#args),*>(#(#call_args),*)
+            };
+            // Apply what the system queued, before the next system runs.
+            ::tiny_ecs::world::WorldApi::flush_commands(unsafe { &mut *__w });
+            #report
+        }
+
+        #sys_struct
 
-        impl<#w: #(#bounds)+*> ::tiny_ecs::system::IntoSystem<#w> for #sys_struct_name {
+        impl<#(#user_params),*> ::tiny_ecs::system::IntoSystem<#world_ty> for #sys_struct_name<#(#args),*>
+        where
+            #(#where_preds,)*
+            #static_pred
+        {
             fn into_system(self) -> ::tiny_ecs::system::System {
-                #sys_fn_name::<#w>
+                #sys_fn_name::<#(#args),*>
             }
         }
     };
     Ok(expanded)
 }
 
+/// Whether a system returning `output` is a run condition: it returns
+/// `bool`.
+fn is_condition(output: &syn::ReturnType) -> bool {
+    match output {
+        syn::ReturnType::Type(_, ty) => matches!(&**ty, syn::Type::Path(p) if p.qself.is_none() && p.path.is_ident("bool")),
+        syn::ReturnType::Default => false,
+    }
+}
+
+/// Rejects the params of a run condition that could write to the world:
+/// conditions run before the systems they guard and must not change what
+/// those systems see.
+fn check_read_only(params: &[(SystemParam, syn::Ident, bool)], inputs: &syn::punctuated::Punctuated<syn::FnArg, syn::Token![,]>) -> syn::Result<()> {
+    for ((param, ident, _), arg) in params.iter().zip(inputs) {
+        let access = ParamAccess::of(param);
+        let writes = access.resources.iter().chain(&access.columns).chain(&access.events).any(|(_, write)| *write);
+        if writes || access.commands || matches!(param, SystemParam::World(_)) {
+            return Err(syn::Error::new_spanned(
+                arg,
+                format!("run conditions must be read-only, but `{ident}` can write to the world"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Hands an `Err` in `__result` to the world's error handler, naming the
+/// system.
+fn report_error(system: &syn::Ident, world_ty: &TokenStream2) -> TokenStream2 {
+    let system = system.to_string();
+    quote! {
+        if let ::core::result::Result::Err(__error) = __result {
+            let __failed = ::tiny_ecs::error::SystemFailed::new(#system, &__error);
+            // SAFETY: `__w` is valid, and every param borrow ended with the body.
+            unsafe { <#world_ty as ::tiny_ecs::system::DiagnosticsRef>::report_error(__w, __failed) };
+        }
+    }
+}
+
+/// The generic parameters a system function declares, threaded through the
+/// generated fn, struct and `IntoSystem` impl.
+struct SystemGenerics {
+    /// The type and const params, with their inline bounds.
+    params: Vec<syn::GenericParam>,
+    /// The params as arguments (`T`, `N`).
+    args: Vec<syn::Ident>,
+    /// The `where` clause predicates.
+    where_preds: Vec<syn::WherePredicate>,
+}
+
+impl SystemGenerics {
+    /// Collects the generics of `sig`, rejecting lifetime params.
+    fn new(sig: &syn::Signature) -> syn::Result<Self> {
+        let generics = &sig.generics;
+        if let Some(lifetime) = generics.lifetimes().next() {
+            return Err(syn::Error::new_spanned(lifetime, "system functions may not declare lifetime parameters"));
+        }
+        let params: Vec<syn::GenericParam> = generics
+            .params
+            .iter()
+            .cloned()
+            .map(|mut param| {
+                match &mut param {
+                    syn::GenericParam::Type(t) => t.default = None,
+                    syn::GenericParam::Const(c) => c.default = None,
+                    syn::GenericParam::Lifetime(_) => {}
+                }
+                param
+            })
+            .collect();
+        let args = params
+            .iter()
+            .filter_map(|param| match param {
+                syn::GenericParam::Type(t) => Some(t.ident.clone()),
+                syn::GenericParam::Const(c) => Some(c.ident.clone()),
+                syn::GenericParam::Lifetime(_) => None,
+            })
+            .collect();
+        let where_preds = generics
+            .where_clause
+            .iter()
+            .flat_map(|w| w.predicates.iter().cloned())
+            .collect();
+        Ok(Self { params, args, where_preds })
+    }
+
+    /// Emits the `LocalKey` of the state of the system's param at `index`,
+    /// naming the system by the `TypeId` of its struct `name`.
+    fn local_key(&self, name: &syn::Ident, index: usize) -> TokenStream2 {
+        let args = &self.args;
+        let index = index as u8;
+        quote! {
+            ::tiny_ecs::system::LocalKey {
+                system: ::core::any::TypeId::of::<#name<#(#args),*>>,
+                param: #index,
+            }
+        }
+    }
+
+    /// The `'static` bound a generic system keeping state needs to key its
+    /// state by `TypeId`, if any.
+    fn static_pred(&self, name: &syn::Ident, params: &[(SystemParam, syn::Ident, bool)]) -> Option<TokenStream2> {
+        let stateful = params
+            .iter()
+            .any(|(p, _, _)| matches!(p, SystemParam::Local(_) | SystemParam::EventReader(_) | SystemParam::Custom(_)));
+        let args = &self.args;
+        (stateful && !args.is_empty()).then(|| quote!(#name<#(#args),*>: 'static,))
+    }
+
+    /// Emits the struct standing for the system: a unit struct, or for a
+    /// generic system a braced struct plus a same-named constructor fn, the
+    /// value `name::<T>` passed to `add_system`.
+    fn struct_item(&self, vis: &syn::Visibility, name: &syn::Ident) -> TokenStream2 {
+        if self.params.is_empty() {
+            return quote! {
+                #[doc(hidden)]
+                #[allow(non_camel_case_types)]
+                #vis struct #name;
+            };
+        }
+        // Bounds stay on the fn and the impl; the struct only needs the names.
+        let decls: Vec<TokenStream2> = self
+            .params
+            .iter()
+            .map(|param| match param {
+                syn::GenericParam::Type(t) => {
+                    let ident = &t.ident;
+                    quote!(#ident)
+                }
+                other => quote!(#other),
+            })
+            .collect();
+        let args = &self.args;
+        // Only type params need a marker; unused const params are allowed.
+        let markers = self.params.iter().filter_map(|param| match param {
+            syn::GenericParam::Type(t) => Some(&t.ident),
+            _ => None,
+        });
+        quote! {
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            #vis struct #name<#(#decls),*> {
+                /// Ties the struct to the system's generics.
+                _generics: ::core::marker::PhantomData<fn() -> (#(#markers,)*)>,
+            }
+
+            #[doc(hidden)]
+            #vis fn #name<#(#decls),*>(_: ::tiny_ecs::system::GenericSystem) -> #name<#(#args),*> {
+                #name { _generics: ::core::marker::PhantomData }
+            }
+        }
+    }
+}
+
+/// Builds the `*mut T` to the value of a `Local<T>` param under `key` in the
+/// state store of the world `__w` points to.
+fn local_slot(world_ty: &impl quote::ToTokens, ty: &syn::Type, key: &TokenStream2, system: &str) -> TokenStream2 {
+    quote! {
+        <#world_ty as ::tiny_ecs::system::LocalsRef>::local_raw::<#ty>(
+            __w,
+            #key,
+            #system,
+            <#ty as ::core::default::Default>::default,
+        )
+    }
+}
+
 /// Extracts the binding ident and `mut`-ness from a function argument pattern.
 fn pat_ident(pat: &syn::Pat) -> syn::Result<(syn::Ident, bool)> {
     if let syn::Pat::Ident(pi) = pat {
@@ -239,37 +1043,145 @@ fn pat_ident(pat: &syn::Pat) -> syn::Result<(syn::Ident, bool)> {
     Err(syn::Error::new_spanned(pat, "expected a simple identifier pattern"))
 }
 
+/// The built-in params taking type arguments, with the shape each expects.
+/// A path type ending in one of these names must be that param; any other
+/// path type is a custom param.
+const BUILTIN_PARAMS: &[(&str, &str)] = &[
+    ("Option", "`Option<Res<T>>` or `Option<ResMut<T>>`"),
+    ("Res", "`Res<T>`"),
+    ("ResMut", "`ResMut<T>`"),
+    ("Query", "`Query<D>` or `Query<D, F>`"),
+    ("Single", "`Single<D>` or `Single<D, F>`"),
+    ("Trigger", "`Trigger<E>`"),
+    ("RemovedComponents", "`RemovedComponents<T>`"),
+    ("EventWriter", "`EventWriter<E>`"),
+    ("EventReader", "`EventReader<E>`"),
+    ("Local", "`Local<T>`"),
+];
+
 /// Recognises a system param type and returns its [`SystemParam`] kind.
-fn parse_param_type(ty: &syn::Type) -> Option<SystemParam> {
+fn parse_param_type(ty: &syn::Type) -> syn::Result<SystemParam> {
     let path = match ty {
+        // `&mut W`: an exclusive system.
+        syn::Type::Reference(r) if r.mutability.is_some() => return Ok(SystemParam::World((*r.elem).clone())),
+        syn::Type::Reference(_) => {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "unsupported system parameter type: an exclusive system takes the world as `&mut World`",
+            ));
+        }
+        // A type passed through a `macro_rules!` fragment.
+        syn::Type::Group(g) => return parse_param_type(&g.elem),
         syn::Type::Path(p) if p.qself.is_none() => &p.path,
-        _ => return None,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "unsupported system parameter type: expected a param such as `Res<T>` or `Query<D>`, or a type implementing `SystemParam`",
+            ));
+        }
     };
-    let seg = path.segments.last()?;
+    let seg = path.segments.last().expect("a path has at least one segment");
     let ident = seg.ident.to_string();
     if ident == "Commands" {
-        return Some(SystemParam::Commands);
+        return Ok(SystemParam::Commands);
     }
-    // Single-segment generic: `Res<...>`, `ResMut<...>`, `Query<...>`.
-    let args = match &seg.arguments {
-        syn::PathArguments::AngleBracketed(a) => a,
-        _ => return None,
+    let Some((_, shape)) = BUILTIN_PARAMS.iter().find(|(name, _)| *name == ident) else {
+        return Ok(SystemParam::Custom(ty.clone()));
     };
-    let inner = args.args.first()?;
-    let inner_ty = match inner {
-        syn::GenericArgument::Type(t) => t.clone(),
-        _ => return None,
+    parse_builtin_param(seg, &ident)?.ok_or_else(|| {
+        syn::Error::new_spanned(ty, format!("unsupported system parameter type: expected {shape}"))
+    })
+}
+
+/// Recognises the built-in generic params of [`BUILTIN_PARAMS`]; `Ok(None)`
+/// when `seg` names one with the wrong type arguments.
+fn parse_builtin_param(seg: &syn::PathSegment, ident: &str) -> syn::Result<Option<SystemParam>> {
+    let types = generic_types(seg).unwrap_or_default();
+    let (inner_ty, filter) = match types.as_slice() {
+        [inner] => (inner.clone(), None),
+        [inner, filter] if matches!(ident, "Query" | "Single") => (inner.clone(), Some(Box::new(filter.clone()))),
+        _ => return Ok(None),
     };
-    match ident.as_str() {
+    Ok(match ident {
+        "Option" => match parse_param_type(&inner_ty)? {
+            SystemParam::Res(ty) => Some(SystemParam::OptRes(ty)),
+            SystemParam::ResMut(ty) => Some(SystemParam::OptResMut(ty)),
+            _ => None,
+        },
         "Res" => Some(SystemParam::Res(inner_ty)),
         "ResMut" => Some(SystemParam::ResMut(inner_ty)),
-        "Query" => Some(SystemParam::Query(inner_ty)),
+        "Query" => Some(SystemParam::Query(inner_ty, filter)),
+        "Single" => Some(SystemParam::Single(inner_ty, filter)),
+        "Trigger" => Some(SystemParam::Trigger(inner_ty)),
+        "RemovedComponents" => Some(SystemParam::RemovedComponents(inner_ty)),
+        "EventWriter" => Some(SystemParam::EventWriter(inner_ty)),
+        "EventReader" => Some(SystemParam::EventReader(inner_ty)),
+        "Local" => Some(SystemParam::Local(inner_ty)),
         _ => None,
+    })
+}
+
+/// Replaces every `'_` in `ty` with `lifetime`, reporting whether any was
+/// found.
+fn replace_elided(ty: &syn::Type, lifetime: &syn::Lifetime) -> (syn::Type, bool) {
+    let mut found = false;
+    let ty = replace_lifetimes(ty, lifetime, |name| {
+        let elided = name == "_";
+        found |= elided;
+        elided
+    });
+    (ty, found)
+}
+
+/// Replaces the lifetimes in `ty` whose name (without the `'`) matches
+/// `replace` with `lifetime`.
+fn replace_lifetimes(ty: &syn::Type, lifetime: &syn::Lifetime, mut replace: impl FnMut(&proc_macro2::Ident) -> bool) -> syn::Type {
+    fn walk(tokens: TokenStream2, lifetime: &syn::Lifetime, replace: &mut dyn FnMut(&proc_macro2::Ident) -> bool) -> TokenStream2 {
+        let mut out = TokenStream2::new();
+        let mut iter = tokens.into_iter().peekable();
+        while let Some(tt) = iter.next() {
+            match tt {
+                proc_macro2::TokenTree::Punct(p) if p.as_char() == '\'' => {
+                    let matched = matches!(iter.peek(), Some(proc_macro2::TokenTree::Ident(i)) if replace(i));
+                    if matched {
+                        iter.next();
+                        out.extend(quote!(#lifetime));
+                    } else {
+                        out.extend([proc_macro2::TokenTree::Punct(p)]);
+                    }
+                }
+                proc_macro2::TokenTree::Group(g) => {
+                    let mut group = proc_macro2::Group::new(g.delimiter(), walk(g.stream(), lifetime, replace));
+                    group.set_span(g.span());
+                    out.extend([proc_macro2::TokenTree::Group(group)]);
+                }
+                other => out.extend([other]),
+            }
+        }
+        out
     }
+    let tokens = walk(quote!(#ty), lifetime, &mut replace);
+    syn::parse2(tokens).expect("replacing lifetimes keeps the type well-formed")
 }
 
-/// Walks a query parameter type and yields the component types referenced via
-/// `&T` / `&mut T` (those are the columns that must be borrowable).
+/// Returns the type arguments of a `Name<A, B, ...>` path segment (lifetimes
+/// skipped); `None` when the segment has no angle-bracketed arguments.
+fn generic_types(seg: &syn::PathSegment) -> Option<Vec<syn::Type>> {
+    let args = match &seg.arguments {
+        syn::PathArguments::AngleBracketed(a) => a,
+        _ => return None,
+    };
+    let types = args.args.iter().filter_map(|arg| match arg {
+        syn::GenericArgument::Type(t) => Some(t.clone()),
+        _ => None,
+    });
+    Some(types.collect())
+}
+
+/// Walks a query data or filter type and yields the component types it
+/// references — via `&T` / `&mut T` (also inside `Option<..>`), or via the
+/// `With<T>` / `Without<T>` / `Added<T>` / `Changed<T>` / `Or<(..)>` filters
+/// (those are the columns that must be borrowable).
 fn components_in_fetch(ty: &syn::Type) -> Vec<syn::Type> {
     let mut out = Vec::new();
     walk_refs(ty, &mut out);
@@ -288,9 +1200,447 @@ fn walk_refs(ty: &syn::Type, out: &mut Vec<syn::Type>) {
                 walk_refs(e, out);
             }
         }
-        _ => {} // Entity, etc. — not a column.
+        syn::Type::Path(p) if p.qself.is_none() => walk_path(&p.path, out),
+        _ => {} // Anything else — not a column.
+    }
+}
+
+/// Collects the columns named by a path: `With<T>` / `Without<T>` /
+/// `Added<T>` / `Changed<T>` name `T`, `Or<(..)>` and `Option<..>` recurse
+/// into their arguments. Other paths (`Entity`, etc.) name no column.
+fn walk_path(path: &syn::Path, out: &mut Vec<syn::Type>) {
+    let Some(seg) = path.segments.last() else {
+        return;
+    };
+    let Some(types) = generic_types(seg) else {
+        return;
+    };
+    match seg.ident.to_string().as_str() {
+        "With" | "Without" | "Added" | "Changed" => out.extend(types),
+        "Or" | "Option" => types.iter().for_each(|t| walk_refs(t, out)),
+        _ => {}
+    }
+}
+
+// ---------------------------------------------------------------------
+// --- Param aliasing ---------------------------------------------------
+
+/// What one system param borrows from the world, for rejecting params whose
+/// raw-pointer fetches would hand out overlapping `&`/`&mut` references.
+///
+/// Types are compared by their tokens, so `Score` and `crate::Score` are not
+/// caught. A type mentioning one of the system's generic type params may be
+/// instantiated as any type, so it clashes with every type of its kind; only
+/// `With` / `Without` filters naming the very same types prove it disjoint.
+#[derive(Default)]
+struct ParamAccess {
+    /// Resources, and whether each is borrowed mutably.
+    resources: Vec<(String, bool)>,
+    /// Component columns borrowed by query data, and whether each is `&mut`.
+    columns: Vec<(String, bool)>,
+    /// Columns whose ticks a query filter (`Added` / `Changed`) reads.
+    filtered: Vec<String>,
+    /// Columns every matched entity has: required data and `With`-like
+    /// filters.
+    with: Vec<String>,
+    /// Columns no matched entity has (`Without` filters).
+    without: Vec<String>,
+    /// Event buffers, and whether each is written.
+    events: Vec<(String, bool)>,
+    /// Whether the param holds the command buffer.
+    commands: bool,
+}
+
+impl ParamAccess {
+    /// Collects what `param` borrows.
+    fn of(param: &SystemParam) -> Self {
+        let mut access = Self::default();
+        match param {
+            SystemParam::Res(ty) | SystemParam::OptRes(ty) => access.resources.push((type_key(ty), false)),
+            SystemParam::ResMut(ty) | SystemParam::OptResMut(ty) => access.resources.push((type_key(ty), true)),
+            SystemParam::Query(data, filter) | SystemParam::Single(data, filter) => {
+                access.data(data, true);
+                if let Some(filter) = filter {
+                    access.filter(filter, true);
+                }
+            }
+            SystemParam::EventWriter(ty) => access.events.push((type_key(ty), true)),
+            SystemParam::EventReader(ty) => access.events.push((type_key(ty), false)),
+            // `Commands` only reserves ids and pushes onto pending queues, so
+            // it is compatible with any query; two of them share one buffer.
+            SystemParam::Commands => access.commands = true,
+            // Triggers and removal logs are read-only, `Local`s are private,
+            // exclusive systems take nothing else, and custom params export
+            // their fields' borrows for `custom_checks`.
+            SystemParam::Trigger(_)
+            | SystemParam::RemovedComponents(_)
+            | SystemParam::Local(_)
+            | SystemParam::World(_)
+            | SystemParam::Custom(_) => {}
+        }
+        access
+    }
+
+    /// Records the columns of query data `ty`; `required` is false inside
+    /// `Option<..>`, whose column a matched entity may lack.
+    fn data(&mut self, ty: &syn::Type, required: bool) {
+        match ty {
+            syn::Type::Reference(r) => {
+                let column = type_key(&r.elem);
+                if required {
+                    self.with.push(column.clone());
+                }
+                self.columns.push((column, r.mutability.is_some()));
+            }
+            syn::Type::Paren(p) => self.data(&p.elem, required),
+            syn::Type::Tuple(t) => t.elems.iter().for_each(|e| self.data(e, required)),
+            syn::Type::Path(p) if p.qself.is_none() => {
+                let Some(seg) = p.path.segments.last() else {
+                    return;
+                };
+                if seg.ident == "Option" {
+                    let types = generic_types(seg).unwrap_or_default();
+                    types.iter().for_each(|t| self.data(t, false));
+                }
+            }
+            _ => {} // `Entity` and the like borrow no column.
+        }
+    }
+
+    /// Records the columns of query filter `ty`; `required` is false inside
+    /// `Or<..>`, any one branch of which may fail.
+    fn filter(&mut self, ty: &syn::Type, required: bool) {
+        match ty {
+            syn::Type::Paren(p) => self.filter(&p.elem, required),
+            syn::Type::Tuple(t) => t.elems.iter().for_each(|e| self.filter(e, required)),
+            syn::Type::Path(p) if p.qself.is_none() => {
+                let Some(seg) = p.path.segments.last() else {
+                    return;
+                };
+                let Some(types) = generic_types(seg) else {
+                    return;
+                };
+                let columns = types.iter().map(type_key);
+                match seg.ident.to_string().as_str() {
+                    "With" if required => self.with.extend(columns),
+                    "Without" if required => self.without.extend(columns),
+                    "Added" | "Changed" => {
+                        let columns: Vec<String> = columns.collect();
+                        if required {
+                            self.with.extend(columns.iter().cloned());
+                        }
+                        self.filtered.extend(columns);
+                    }
+                    "Or" => types.iter().for_each(|t| self.filter(t, false)),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether no entity can match both queries: one requires a column the
+    /// other excludes.
+    fn disjoint(&self, other: &Self) -> bool {
+        self.with.iter().any(|c| other.without.contains(c)) || other.with.iter().any(|c| self.without.contains(c))
+    }
+
+    /// Describes how `self` and `other` alias, if they do: both borrow
+    /// something with at least one of them mutably. `generics` are the
+    /// system's generic type params.
+    fn conflict(&self, other: &Self, generics: &[syn::Ident]) -> Option<String> {
+        let clash = |ours: &[(String, bool)], theirs: &[(String, bool)]| {
+            ours.iter().find_map(|(ty, write)| {
+                theirs
+                    .iter()
+                    .find(|(t, w)| (*write || *w) && may_be_same(ty, t, generics))
+                    .map(|(t, _)| describe(ty, t, generics))
+            })
+        };
+        if let Some(ty) = clash(&self.resources, &other.resources) {
+            return Some(format!("both borrow the resource {ty}, one of them mutably"));
+        }
+        if !self.disjoint(other) {
+            // Filters only read ticks, so they clash with `&mut` data alone.
+            let reads = |access: &Self| -> Vec<(String, bool)> {
+                let filtered = access.filtered.iter().map(|ty| (ty.clone(), false));
+                access.columns.iter().cloned().chain(filtered).collect()
+            };
+            let column = clash(&self.columns, &reads(other)).or_else(|| clash(&reads(self), &other.columns));
+            if let Some(ty) = column {
+                return Some(format!("both borrow the component {ty}, one of them mutably"));
+            }
+        }
+        if let Some(ty) = clash(&self.events, &other.events) {
+            return Some(format!("both borrow the events {ty}, one of them mutably"));
+        }
+        if self.commands && other.commands {
+            return Some("both take the command buffer".into());
+        }
+        None
+    }
+
+    /// The `tiny_ecs::system_param::Access` entry mirroring `self`, with
+    /// the borrowed types mentioning one of `generics` written as the
+    /// wildcard `_`.
+    fn to_tokens(&self, generics: &[syn::Ident]) -> TokenStream2 {
+        let key = |ty: &String| if mentions_generic(ty, generics) { "_".to_string() } else { ty.clone() };
+        let pairs = |list: &[(String, bool)]| {
+            let (names, writes): (Vec<String>, Vec<bool>) = list.iter().map(|(ty, write)| (key(ty), *write)).unzip();
+            quote!(&[ #( (#names, #writes) ),* ])
+        };
+        let names = |list: &[String]| quote!(&[ #(#list),* ]);
+        let filtered: Vec<String> = self.filtered.iter().map(key).collect();
+        let (resources, columns, events) = (pairs(&self.resources), pairs(&self.columns), pairs(&self.events));
+        let (filtered, with, without) = (names(&filtered), names(&self.with), names(&self.without));
+        let commands = self.commands;
+        quote! {
+            ::tiny_ecs::system_param::Access::Param(::tiny_ecs::system_param::ParamAccess {
+                resources: #resources,
+                columns: #columns,
+                filtered: #filtered,
+                with: #with,
+                without: #without,
+                events: #events,
+                commands: #commands,
+            })
+        }
+    }
+
+    /// Names a column the param's own query data borrows twice with at least
+    /// one `&mut`, as in `Query<(&mut A, &A)>`.
+    fn self_conflict(&self, generics: &[syn::Ident]) -> Option<String> {
+        self.columns.iter().enumerate().find_map(|(i, (ty, write))| {
+            let again = self.columns[i + 1..].iter().find(|(t, w)| (*write || *w) && may_be_same(ty, t, generics));
+            again.map(|(t, _)| format!("the component {}", describe(ty, t, generics)))
+        })
+    }
+}
+
+/// Rejects params that alias each other (or themselves), each given with its
+/// name and the tokens to point the error at; `generics` are the system's
+/// generic type params.
+fn check_access<'a, T: quote::ToTokens + 'a>(
+    params: impl IntoIterator<Item = (&'a SystemParam, String, &'a T)>,
+    generics: &[syn::Ident],
+) -> syn::Result<()> {
+    let mut seen: Vec<(ParamAccess, String)> = Vec::new();
+    for (param, name, tokens) in params {
+        let access = ParamAccess::of(param);
+        if let Some(what) = access.self_conflict(generics) {
+            return Err(syn::Error::new_spanned(tokens, format!("`{name}` borrows {what} twice, once mutably")));
+        }
+        for (earlier, earlier_name) in &seen {
+            if let Some(what) = access.conflict(earlier, generics) {
+                return Err(syn::Error::new_spanned(
+                    tokens,
+                    format!("`{name}` aliases `{earlier_name}`: {what}"),
+                ));
+            }
+        }
+        seen.push((access, name));
+    }
+    Ok(())
+}
+
+/// The `&[Access]` of `param` in a `const` item: custom params export theirs,
+/// with their lifetimes made `'static`.
+fn access_slice(param: &SystemParam, generics: &[syn::Ident]) -> TokenStream2 {
+    match param {
+        SystemParam::Custom(ty) => {
+            let ty = replace_lifetimes(ty, &parse_quote!('static), |_| true);
+            quote!(<#ty as ::tiny_ecs::system_param::SystemParam>::ACCESS)
+        }
+        param => {
+            let access = ParamAccess::of(param).to_tokens(generics);
+            quote!(&[#access])
+        }
+    }
+}
+
+/// Rejects at compile time the params aliasing a custom one, which
+/// `check_access` cannot see into; each param is given with its name.
+fn custom_checks<'a>(params: impl IntoIterator<Item = (&'a SystemParam, String)>, generics: &[syn::Ident]) -> TokenStream2 {
+    let params: Vec<(&SystemParam, String)> = params.into_iter().collect();
+    let mut asserts = Vec::new();
+    for (i, (later, later_name)) in params.iter().enumerate() {
+        for (earlier, earlier_name) in &params[..i] {
+            if !matches!(later, SystemParam::Custom(_)) && !matches!(earlier, SystemParam::Custom(_)) {
+                continue;
+            }
+            let (ours, theirs) = (access_slice(later, generics), access_slice(earlier, generics));
+            let message = format!("`{later_name}` aliases `{earlier_name}`: both borrow the same data, one of them mutably");
+            asserts.push(quote!(::core::assert!(!::tiny_ecs::system_param::conflicts(#ours, #theirs), #message);));
+        }
+    }
+    if asserts.is_empty() {
+        return TokenStream2::new();
     }
+    quote!(const _: () = { #(#asserts)* };)
+}
+
+/// Rejects at compile time the custom params of a run condition that could
+/// write to the world, as `check_read_only` does for the built-in ones.
+fn custom_read_only_checks(params: &[(SystemParam, syn::Ident, bool)]) -> TokenStream2 {
+    let asserts = params.iter().filter(|(p, _, _)| matches!(p, SystemParam::Custom(_))).map(|(param, ident, _)| {
+        let access = access_slice(param, &[]);
+        let message = format!("run conditions must be read-only, but `{ident}` can write to the world");
+        quote!(::core::assert!(!::tiny_ecs::system_param::writes(#access), #message);)
+    });
+    let asserts: Vec<TokenStream2> = asserts.collect();
+    if asserts.is_empty() {
+        return TokenStream2::new();
+    }
+    quote!(const _: () = { #(#asserts)* };)
+}
+
+/// Whether the types keyed `a` and `b` may be the same once the system's
+/// generic type params `generics` are instantiated.
+fn may_be_same(a: &str, b: &str, generics: &[syn::Ident]) -> bool {
+    a == b || mentions_generic(a, generics) || mentions_generic(b, generics)
+}
+
+/// Whether the type keyed `key` mentions one of `generics`.
+fn mentions_generic(key: &str, generics: &[syn::Ident]) -> bool {
+    key.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .any(|word| generics.iter().any(|generic| generic == word))
+}
+
+/// Names the type two params both borrow, as `a` and `b`.
+fn describe(a: &str, b: &str, generics: &[syn::Ident]) -> String {
+    match (a == b, mentions_generic(a, generics)) {
+        (true, _) => format!("`{a}`"),
+        (false, true) => format!("`{a}` (which may be `{b}`)"),
+        (false, false) => format!("`{b}` (which may be `{a}`)"),
+    }
+}
+
+/// The key a type is compared by when checking params for aliasing.
+fn type_key(ty: &syn::Type) -> String {
+    quote!(#ty).to_string()
 }
 
-use syn::parse_quote;
-// a tiny re-export so the `parse_quote!` calls above resolve.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The message of the error parsing `ty` as a param.
+    fn error(ty: syn::Type) -> String {
+        match parse_param_type(&ty) {
+            Ok(_) => panic!("`{}` parsed as a param", quote!(#ty)),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    /// The message of the error checking `params` of a system generic over
+    /// `T` for aliasing, if any.
+    fn aliasing(params: &[(&str, syn::Type)]) -> Option<String> {
+        let parsed: Vec<(SystemParam, String, &syn::Type)> = params
+            .iter()
+            .map(|(name, ty)| (parse_param_type(ty).expect("a param"), name.to_string(), ty))
+            .collect();
+        check_access(parsed.iter().map(|(param, name, ty)| (param, name.clone(), *ty)), &[parse_quote!(T)])
+            .err()
+            .map(|error| error.to_string())
+    }
+
+    #[test]
+    fn aliasing_params_are_rejected() {
+        assert_eq!(
+            aliasing(&[("score", parse_quote!(Res<Score>)), ("again", parse_quote!(ResMut<Score>))]).as_deref(),
+            Some("`again` aliases `score`: both borrow the resource `Score`, one of them mutably"),
+        );
+        assert_eq!(
+            aliasing(&[("pos", parse_quote!(Query<&mut Pos>)), ("changed", parse_quote!(Query<Entity, Changed<Pos>>))]).as_deref(),
+            Some("`changed` aliases `pos`: both borrow the component `Pos`, one of them mutably"),
+        );
+        assert_eq!(
+            aliasing(&[("both", parse_quote!(Query<(&mut Pos, &Pos)>))]).as_deref(),
+            Some("`both` borrows the component `Pos` twice, once mutably"),
+        );
+        assert_eq!(
+            aliasing(&[("a", parse_quote!(Commands)), ("b", parse_quote!(Commands))]).as_deref(),
+            Some("`b` aliases `a`: both take the command buffer"),
+        );
+    }
+
+    #[test]
+    fn generic_params_alias_every_type_of_their_kind() {
+        // Registered as `f::<Pos>`, both queries would borrow the `Pos` column.
+        assert_eq!(
+            aliasing(&[("a", parse_quote!(Query<&mut T>)), ("b", parse_quote!(Query<&mut Pos>))]).as_deref(),
+            Some("`b` aliases `a`: both borrow the component `T` (which may be `Pos`), one of them mutably"),
+        );
+        assert_eq!(
+            aliasing(&[("a", parse_quote!(Res<Wrapper<T>>)), ("b", parse_quote!(ResMut<Score>))]).as_deref(),
+            Some("`b` aliases `a`: both borrow the resource `Wrapper < T >` (which may be `Score`), one of them mutably"),
+        );
+        assert_eq!(
+            aliasing(&[("both", parse_quote!(Query<(&mut T, &Pos)>))]).as_deref(),
+            Some("`both` borrows the component `T` (which may be `Pos`) twice, once mutably"),
+        );
+        // Only filters naming the same types prove the queries disjoint.
+        assert_eq!(
+            aliasing(&[("a", parse_quote!(Query<&mut T, With<Tag>>)), ("b", parse_quote!(Query<&mut Pos, Without<Tag>>))]),
+            None,
+        );
+        assert!(aliasing(&[("a", parse_quote!(Query<&mut Pos, With<T>>)), ("b", parse_quote!(Query<&mut Pos, Without<Tag>>))]).is_some());
+        assert_eq!(aliasing(&[("a", parse_quote!(Query<&T>)), ("b", parse_quote!(Query<&Pos>))]), None);
+    }
+
+    #[test]
+    fn disjoint_params_are_accepted() {
+        assert_eq!(aliasing(&[("a", parse_quote!(Res<Score>)), ("b", parse_quote!(Res<Score>))]), None);
+        assert_eq!(
+            aliasing(&[("tagged", parse_quote!(Query<&mut Pos, With<Tag>>)), ("rest", parse_quote!(Query<&Pos, Without<Tag>>))]),
+            None,
+        );
+        assert_eq!(aliasing(&[("commands", parse_quote!(Commands)), ("pos", parse_quote!(Query<&mut Pos>))]), None);
+    }
+
+    #[test]
+    fn only_custom_params_are_checked_at_compile_time() {
+        let builtin = [parse_param_type(&parse_quote!(ResMut<Score>)).expect("a param"), SystemParam::Commands];
+        assert!(custom_checks(builtin.iter().map(|p| (p, "p".to_string())), &[]).is_empty());
+
+        let mixed = [
+            parse_param_type(&parse_quote!(Round<'_>)).expect("a param"),
+            parse_param_type(&parse_quote!(Res<Score>)).expect("a param"),
+        ];
+        let checks = custom_checks([(&mixed[0], "round".to_string()), (&mixed[1], "score".to_string())], &[]).to_string();
+        assert!(checks.contains("Round < 'static > as :: tiny_ecs :: system_param :: SystemParam > :: ACCESS"));
+        assert!(checks.contains("`score` aliases `round`"));
+    }
+
+    #[test]
+    fn only_unknown_path_types_are_custom() {
+        assert!(matches!(parse_param_type(&parse_quote!(Round<'_>)), Ok(SystemParam::Custom(_))));
+        assert!(matches!(parse_param_type(&parse_quote!(game::Round<'_>)), Ok(SystemParam::Custom(_))));
+        assert!(matches!(parse_param_type(&parse_quote!(tiny_ecs::system::Res<'_, Score>)), Ok(SystemParam::Res(_))));
+        assert!(matches!(parse_param_type(&parse_quote!(Option<ResMut<Score>>)), Ok(SystemParam::OptResMut(_))));
+        assert!(matches!(parse_param_type(&parse_quote!(Query<&Pos, With<Tag>>)), Ok(SystemParam::Query(_, Some(_)))));
+        assert!(matches!(parse_param_type(&parse_quote!(&mut World)), Ok(SystemParam::World(_))));
+    }
+
+    #[test]
+    fn builtin_names_with_the_wrong_shape_are_rejected() {
+        assert_eq!(error(parse_quote!(Res)), "unsupported system parameter type: expected `Res<T>`");
+        assert_eq!(error(parse_quote!(Local<u8, u8>)), "unsupported system parameter type: expected `Local<T>`");
+        assert_eq!(
+            error(parse_quote!(Query<&Pos, With<Tag>, ()>)),
+            "unsupported system parameter type: expected `Query<D>` or `Query<D, F>`",
+        );
+        assert_eq!(
+            error(parse_quote!(Option<Query<&Pos>>)),
+            "unsupported system parameter type: expected `Option<Res<T>>` or `Option<ResMut<T>>`",
+        );
+    }
+
+    #[test]
+    fn references_and_non_path_types_are_rejected() {
+        assert!(error(parse_quote!(&World)).contains("`&mut World`"));
+        assert!(error(parse_quote!((Res<Score>, Res<Time>))).starts_with("unsupported system parameter type"));
+        assert!(error(parse_quote!([u8; 4])).starts_with("unsupported system parameter type"));
+        assert!(error(parse_quote!(<T as Trait>::Param)).starts_with("unsupported system parameter type"));
+    }
+}
@@ -7,23 +7,40 @@
 //!     pub struct World {
 //!         entities: 64,
 //!         schedules: 8,
+//!         generations: true, // optional, defaults to `true`
+//!         locals: 64, // optional bytes of per-system state, defaults to 16 words
+//!         systems: 8, // optional per-schedule pools, defaulting to the
+//!         sets: 2,    // `tiny_ecs::schedule::MAX_*_PER` constants
+//!         conditions: 4,
+//!         orderings: 8,
 //!         components {
-//!             player: Player [64],
-//!             debris: Debris [64],
+//!             player: Player [4, sparse],
+//!             debris: Debris [64, removed: 8],
 //!         }
 //!         resources {
 //!             frame: FrameBuffer,
 //!             time: Time,
 //!         }
+//!         events { // optional
+//!             crashed: Crashed [4, overflow: DropNewest],
+//!         }
 //!     }
 //! }
 //! ```
 //!
 //! The emitted `World` is a concrete, non-generic struct with one field per
-//! component column (`Column<T, N>`) and per resource (`Option<R>`), plus the
-//! entity free-list, the bounded schedule map (keyed by `TypeId`), and the
-//! command buffer. The companion `ColumnRef`/`ResourceRef`/`ResourceInsRef`/
-//! `SpawnRef`/`CommandsRef` and `WorldApi` impls are generated so the
+//! component column (`Column<T, N, W>`, or `SparseColumn<T, N, ENTITIES, W>`
+//! for entries marked `sparse`, where `W` is the occupancy word count), a
+//! `RemovalLog<M>` for entries declaring `removed: M`, one field per
+//! resource (`Option<R>` and its change ticks) and per event (`Events<E, N>`),
+//! plus the
+//! entity free-list and liveness bitset, the per-slot generation counters (unless
+//! `generations: false`), the bounded schedule map (keyed by `TypeId`, each
+//! schedule sized by `systems`/`sets`/`conditions`/`orderings`), the
+//! per-system state store, and the command buffer, change ticks and skip and
+//! error handlers. The companion `ColumnRef`/`ResourceRef`/`ResourceInsRef`/
+//! `EntityCommandsRef`/`CommandsRef`/`EntitiesRef`/`ChangeTicksRef`/
+//! `DiagnosticsRef`/`LocalsRef` and `WorldApi` impls are generated so the
 //! `#[system]` macro can split borrows and the app can drive schedules.
 
 use proc_macro2::TokenStream as TokenStream2;
@@ -36,7 +53,8 @@ use syn::{
 // on `tiny_ecs` for sharing types; the generated code references `::tiny_ecs`
 // by absolute path, which resolves in the app crate.
 
-/// One `components { ... }` entry: `field: Type [capacity]`.
+/// One `components { ... }` entry: `field: Type [capacity]`, optionally
+/// followed by the `sparse` and `removed: M` column options.
 struct ComponentEntry {
     /// The snake_case field name on the `World` struct.
     field: syn::Ident,
@@ -44,6 +62,10 @@ struct ComponentEntry {
     ty: syn::Type,
     /// The column capacity (`N` const generic).
     capacity: syn::Expr,
+    /// Whether the column uses the sparse-set layout.
+    sparse: bool,
+    /// The removal-log capacity, when `removed: M` is declared.
+    removed: Option<syn::Expr>,
 }
 
 /// One `resources { ... }` entry: `field: Type`.
@@ -54,6 +76,19 @@ struct ResourceEntry {
     ty: syn::Type,
 }
 
+/// One `events { ... }` entry: `field: Type [capacity]` or
+/// `field: Type [capacity, overflow: Policy]`.
+struct EventEntry {
+    /// The snake_case field name on the `World` struct.
+    field: syn::Ident,
+    /// The event type.
+    ty: syn::Type,
+    /// The capacity of each of the two buffers.
+    capacity: syn::Expr,
+    /// The `Overflow` variant applied when the current buffer is full.
+    overflow: syn::Ident,
+}
+
 /// The whole `define_world!` input.
 pub(crate) struct WorldInput {
     /// Visibility of the generated `World` struct.
@@ -64,10 +99,19 @@ pub(crate) struct WorldInput {
     entities: syn::Expr,
     /// The maximum number of schedules.
     schedules: syn::Expr,
+    /// Whether every entity slot carries a generation counter.
+    generations: bool,
+    /// The bytes reserved for per-system state.
+    locals: syn::Expr,
+    /// The pool sizes of every schedule: systems, sets, run conditions and
+    /// ordering constraints.
+    schedule_pools: [syn::Expr; 4],
     /// Registered component columns.
     components: Vec<ComponentEntry>,
     /// Registered resources.
     resources: Vec<ResourceEntry>,
+    /// Registered event types.
+    events: Vec<EventEntry>,
 }
 
 impl Parse for WorldInput {
@@ -80,8 +124,18 @@ impl Parse for WorldInput {
 
         let mut entities: Option<syn::Expr> = None;
         let mut schedules: Option<syn::Expr> = None;
+        let mut generations = true;
+        // Room for a few small `Local`s: 64 bytes on the micro:bit.
+        let mut locals: syn::Expr = syn::parse_quote!(16 * ::core::mem::size_of::<usize>());
+        let mut schedule_pools: [syn::Expr; 4] = [
+            syn::parse_quote!(::tiny_ecs::schedule::MAX_SYSTEMS_PER),
+            syn::parse_quote!(::tiny_ecs::schedule::MAX_SETS_PER),
+            syn::parse_quote!(::tiny_ecs::schedule::MAX_CONDITIONS_PER),
+            syn::parse_quote!(::tiny_ecs::schedule::MAX_ORDERINGS_PER),
+        ];
         let mut components: Vec<ComponentEntry> = Vec::new();
         let mut resources: Vec<ResourceEntry> = Vec::new();
+        let mut events: Vec<EventEntry> = Vec::new();
 
         while !body.is_empty() {
             // Distinguish `components {` / `resources {` from `key: value`.
@@ -91,10 +145,12 @@ impl Parse for WorldInput {
                     components = parse_component_entries(&body)?;
                 } else if section == "resources" {
                     resources = parse_resource_entries(&body)?;
+                } else if section == "events" {
+                    events = parse_event_entries(&body)?;
                 } else {
                     return Err(syn::Error::new(
                         section.span(),
-                        "expected `components` or `resources`",
+                        "expected `components`, `resources` or `events`",
                     ));
                 }
                 let _ = body.parse::<Token![,]>();
@@ -107,10 +163,16 @@ impl Parse for WorldInput {
                 entities = Some(body.parse()?);
             } else if key == "schedules" {
                 schedules = Some(body.parse()?);
+            } else if key == "generations" {
+                generations = body.parse::<syn::LitBool>()?.value;
+            } else if key == "locals" {
+                locals = body.parse()?;
+            } else if let Some(pool) = ["systems", "sets", "conditions", "orderings"].iter().position(|pool| key == pool) {
+                schedule_pools[pool] = body.parse()?;
             } else {
                 return Err(syn::Error::new(
                     key.span(),
-                    "unknown field; expected `entities`, `schedules`, `components`, or `resources`",
+                    "unknown field; expected `entities`, `schedules`, `generations`, `locals`, `systems`, `sets`, `conditions`, `orderings`, `components`, `resources`, or `events`",
                 ));
             }
             let _ = body.parse::<Token![,]>();
@@ -126,8 +188,12 @@ impl Parse for WorldInput {
             name,
             entities,
             schedules,
+            generations,
+            locals,
+            schedule_pools,
             components,
             resources,
+            events,
         })
     }
 }
@@ -144,16 +210,42 @@ fn parse_component_entries(body: ParseStream) -> syn::Result<Vec<ComponentEntry>
         let cap_content;
         syn::bracketed!(cap_content in inner);
         let capacity: syn::Expr = cap_content.parse()?;
+        let (sparse, removed) = parse_column_options(&cap_content)?;
         out.push(ComponentEntry {
             field,
             ty,
             capacity,
+            sparse,
+            removed,
         });
         let _ = inner.parse::<Token![,]>();
     }
     Ok(out)
 }
 
+/// Parses the options following the capacity in `[capacity, ...]`; returns
+/// whether the `sparse` layout was requested and the `removed: M` log
+/// capacity, if any.
+fn parse_column_options(content: ParseStream) -> syn::Result<(bool, Option<syn::Expr>)> {
+    let mut sparse = false;
+    let mut removed = None;
+    while content.parse::<Token![,]>().is_ok() && !content.is_empty() {
+        let option: syn::Ident = content.parse()?;
+        if option == "sparse" {
+            sparse = true;
+        } else if option == "removed" {
+            let _colon: Token![:] = content.parse()?;
+            removed = Some(content.parse()?);
+        } else {
+            return Err(syn::Error::new(
+                option.span(),
+                "unknown column option; expected `sparse` or `removed: <MAX>`",
+            ));
+        }
+    }
+    Ok((sparse, removed))
+}
+
 /// Parses `field: Type` entries inside a `resources { ... }` block.
 fn parse_resource_entries(body: ParseStream) -> syn::Result<Vec<ResourceEntry>> {
     let inner;
@@ -169,6 +261,45 @@ fn parse_resource_entries(body: ParseStream) -> syn::Result<Vec<ResourceEntry>>
     Ok(out)
 }
 
+/// Parses `field: Type [capacity]` entries inside an `events { ... }` block,
+/// each optionally followed by `overflow: DropOldest | DropNewest`.
+fn parse_event_entries(body: ParseStream) -> syn::Result<Vec<EventEntry>> {
+    let inner;
+    syn::braced!(inner in body);
+    let mut out = Vec::new();
+    while !inner.is_empty() {
+        let field: syn::Ident = inner.parse()?;
+        let _colon: Token![:] = inner.parse()?;
+        let ty: syn::Type = inner.parse()?;
+        let cap_content;
+        syn::bracketed!(cap_content in inner);
+        let capacity: syn::Expr = cap_content.parse()?;
+        let mut overflow = format_ident!("DropOldest");
+        while cap_content.parse::<Token![,]>().is_ok() && !cap_content.is_empty() {
+            let option: syn::Ident = cap_content.parse()?;
+            if option != "overflow" {
+                return Err(syn::Error::new(option.span(), "unknown event option; expected `overflow`"));
+            }
+            let _colon: Token![:] = cap_content.parse()?;
+            overflow = cap_content.parse()?;
+            if overflow != "DropOldest" && overflow != "DropNewest" {
+                return Err(syn::Error::new(
+                    overflow.span(),
+                    "unknown overflow policy; expected `DropOldest` or `DropNewest`",
+                ));
+            }
+        }
+        out.push(EventEntry {
+            field,
+            ty,
+            capacity,
+            overflow,
+        });
+        let _ = inner.parse::<Token![,]>();
+    }
+    Ok(out)
+}
+
 impl WorldInput {
     /// Emits the `World` struct and all associated trait impls.
     pub(crate) fn expand(&self) -> syn::Result<TokenStream2> {
@@ -177,41 +308,98 @@ impl WorldInput {
             name,
             entities,
             schedules,
+            generations,
+            locals,
+            schedule_pools: [systems, sets, conditions, orderings],
             components,
             resources,
+            events,
         } = self;
 
-        // Field declarations: one `Column<T, N>` per component.
+        // Field declarations: one `Column<T, N>` (or sparse-set column
+        // addressing the whole entity budget) per component.
         let comp_fields = components.iter().map(|c| {
             let f = &c.field;
             let t = &c.ty;
             let cap = &c.capacity;
-            quote! { #f: ::tiny_ecs::column::Column<#t, #cap> }
+            if c.sparse {
+                quote! { #f: ::tiny_ecs::column::SparseColumn<#t, #cap, { #entities }, { ::tiny_ecs::bitset::words_for(#entities) }> }
+            } else {
+                quote! { #f: ::tiny_ecs::column::Column<#t, #cap, { ::tiny_ecs::bitset::words_for(#cap) }> }
+            }
         });
-        // Resource fields: `Option<R>` (None until inserted).
+        // Resource fields: `Option<R>` (None until inserted), its change
+        // ticks, and the insert (`Some`) or removal (`None`) queued by
+        // `Commands`.
         let res_fields = resources.iter().map(|r| {
             let f = &r.field;
+            let tf = format_ident!("ticks_{}", r.field);
+            let pf = format_ident!("pending_{}", r.field);
             let t = &r.ty;
-            quote! { #f: ::core::option::Option<#t> }
+            quote! {
+                #f: ::core::option::Option<#t>,
+                #tf: ::tiny_ecs::change_detection::ComponentTicks,
+                #pf: ::core::option::Option<::core::option::Option<#t>>
+            }
         });
 
         // Initialise columns and resources in `new()`.
         let comp_inits = components.iter().map(|c| {
             let f = &c.field;
-            quote! { #f: ::tiny_ecs::column::Column::new() }
+            if c.sparse {
+                quote! { #f: ::tiny_ecs::column::SparseColumn::new() }
+            } else {
+                quote! { #f: ::tiny_ecs::column::Column::new() }
+            }
         });
         let res_inits = resources.iter().map(|r| {
             let f = &r.field;
-            quote! { #f: ::core::option::Option::None }
+            let tf = format_ident!("ticks_{}", r.field);
+            let pf = format_ident!("pending_{}", r.field);
+            quote! {
+                #f: ::core::option::Option::None,
+                #tf: ::tiny_ecs::change_detection::ComponentTicks::new(::tiny_ecs::change_detection::Tick::new(0)),
+                #pf: ::core::option::Option::None
+            }
+        });
+
+        // Double-buffered event fields.
+        let event_fields = events.iter().map(|e| {
+            let f = &e.field;
+            let t = &e.ty;
+            let cap = &e.capacity;
+            quote! { #f: ::tiny_ecs::event::Events<#t, { #cap }> }
+        });
+        let event_inits = events.iter().map(|e| {
+            let f = &e.field;
+            let overflow = &e.overflow;
+            quote! { #f: ::tiny_ecs::event::Events::new(::tiny_ecs::event::Overflow::#overflow) }
+        });
+        let event_updates = events.iter().map(|e| {
+            let f = &e.field;
+            quote! { ::tiny_ecs::event::EventsOps::update(&mut self.#f); }
         });
 
-        // Per-component pending-spawn queue fields.
+        // Per-component pending-operation queue fields (spawns, inserts and
+        // removes share one queue so they apply in issue order).
         let comp_pendings = components.iter().map(|c| {
             let pf = format_ident!("pending_{}", c.field);
             let t = &c.ty;
             let cap = &c.capacity;
-            quote! { #pf: ::heapless::Vec<(u32, #t), #cap> }
+            quote! { #pf: ::heapless::Vec<(::tiny_ecs::entity::Entity, ::tiny_ecs::commands_buffer::ComponentOp<#t>), #cap> }
         });
+        // Removal-log fields, for components declaring `removed: M`.
+        let removed_comps: Vec<&ComponentEntry> = components.iter().filter(|c| c.removed.is_some()).collect();
+        let comp_removed_fields = removed_comps.iter().map(|c| {
+            let rf = format_ident!("removed_{}", c.field);
+            let cap = &c.removed;
+            quote! { #rf: ::tiny_ecs::removal_detection::RemovalLog<{ #cap }> }
+        });
+        let comp_removed_inits = removed_comps.iter().map(|c| {
+            let rf = format_ident!("removed_{}", c.field);
+            quote! { #rf: ::tiny_ecs::removal_detection::RemovalLog::new() }
+        });
+
         let comp_pending_inits = components.iter().map(|c| {
             let pf = format_ident!("pending_{}", c.field);
             quote! { #pf: ::heapless::Vec::new() }
@@ -239,6 +427,7 @@ impl WorldInput {
         // `ResourceRef<R>` impls.
         let resource_ref_impls = resources.iter().map(|r| {
             let f = &r.field;
+            let tf = format_ident!("ticks_{}", r.field);
             let t = &r.ty;
             quote! {
                 impl ::tiny_ecs::system::ResourceRef<#t> for #name {
@@ -248,6 +437,9 @@ impl WorldInput {
                     unsafe fn res_mut_raw(world: *mut Self) -> *mut #t {
                         unsafe { (*world).#f.as_mut().map_or(::core::ptr::null_mut(), |r| r as *mut #t) }
                     }
+                    unsafe fn res_ticks_raw(world: *mut Self) -> *mut ::tiny_ecs::change_detection::ComponentTicks {
+                        unsafe { &mut (*world).#tf as *mut _ }
+                    }
                 }
             }
         });
@@ -255,58 +447,328 @@ impl WorldInput {
         // `ResourceInsRef<R>` impls.
         let resource_ins_impls = resources.iter().map(|r| {
             let f = &r.field;
+            let tf = format_ident!("ticks_{}", r.field);
+            let pf = format_ident!("pending_{}", r.field);
             let t = &r.ty;
             quote! {
                 impl ::tiny_ecs::system::ResourceInsRef<#t> for #name {
                     unsafe fn insert_resource(world: *mut Self, value: #t) {
-                        unsafe { (*world).#f = ::core::option::Option::Some(value); }
+                        unsafe {
+                            (*world).#f = ::core::option::Option::Some(value);
+                            (*world).#tf = ::tiny_ecs::change_detection::ComponentTicks::new((*world).ticks.write_tick());
+                        }
+                    }
+                    unsafe fn remove_resource(world: *mut Self) -> ::core::option::Option<#t> {
+                        unsafe { (*world).#f.take() }
+                    }
+                    unsafe fn enqueue_resource(world: *mut Self, value: ::core::option::Option<#t>) {
+                        unsafe { (*world).#pf = ::core::option::Option::Some(value) }
                     }
                 }
             }
         });
 
-        // `SpawnRef<T>` impls (per-component pending queue).
-        let spawn_ref_impls = components.iter().map(|c| {
+        // `EntityCommandsRef<T>` impls (per-component pending queue).
+        let entity_commands_ref_impls = components.iter().map(|c| {
             let pf = format_ident!("pending_{}", c.field);
             let t = &c.ty;
+            let name_str = c.field.to_string();
             quote! {
-                impl ::tiny_ecs::system::SpawnRef<#t> for #name {
-                    unsafe fn enqueue_spawn(world: *mut Self, value: #t) -> ::tiny_ecs::entity::Entity {
-                        // SAFETY: caller guarantees `world` is valid.
-                        let w = unsafe { &mut *world };
-                        let idx = w.alloc_entity();
-                        let _ = w.#pf.push((idx, value));
-                        ::tiny_ecs::entity::Entity::new(idx)
+                impl ::tiny_ecs::system::EntityCommandsRef<#t> for #name {
+                    unsafe fn enqueue_insert(world: *mut Self, entity: ::tiny_ecs::entity::Entity, value: #t) {
+                        // SAFETY: caller guarantees `world` is valid; only the
+                        // pending queue is borrowed.
+                        let queue = unsafe { &mut (*world).#pf };
+                        ::tiny_ecs::commands_buffer::enqueue(queue, entity, ::tiny_ecs::commands_buffer::ComponentOp::Insert(value), #name_str);
+                    }
+                    unsafe fn enqueue_require(world: *mut Self, entity: ::tiny_ecs::entity::Entity, ctor: fn() -> #t) {
+                        // SAFETY: caller guarantees `world` is valid; only the
+                        // pending queue is borrowed.
+                        let queue = unsafe { &mut (*world).#pf };
+                        ::tiny_ecs::commands_buffer::enqueue(queue, entity, ::tiny_ecs::commands_buffer::ComponentOp::Require(ctor), #name_str);
+                    }
+                    unsafe fn enqueue_remove(world: *mut Self, entity: ::tiny_ecs::entity::Entity) {
+                        // SAFETY: caller guarantees `world` is valid; only the
+                        // pending queue is borrowed.
+                        let queue = unsafe { &mut (*world).#pf };
+                        ::tiny_ecs::commands_buffer::enqueue(queue, entity, ::tiny_ecs::commands_buffer::ComponentOp::Remove, #name_str);
                     }
                 }
             }
         });
 
+        // `RemovedRef<T>` impls (per-component removal log).
+        let removed_ref_impls = removed_comps.iter().map(|c| {
+            let rf = format_ident!("removed_{}", c.field);
+            let t = &c.ty;
+            quote! {
+                impl ::tiny_ecs::system::RemovedRef<#t> for #name {
+                    unsafe fn removed_raw(world: *mut Self) -> *const dyn ::tiny_ecs::removal_detection::RemovalLogOps {
+                        unsafe { &(*world).#rf as &dyn ::tiny_ecs::removal_detection::RemovalLogOps as *const _ }
+                    }
+                }
+            }
+        });
+
+        // `EventsRef<E>` impls.
+        let events_ref_impls = events.iter().map(|e| {
+            let f = &e.field;
+            let t = &e.ty;
+            quote! {
+                impl ::tiny_ecs::system::EventsRef<#t> for #name {
+                    unsafe fn events_raw(world: *mut Self) -> *mut dyn ::tiny_ecs::event::EventsOps<#t> {
+                        unsafe { &mut (*world).#f as &mut dyn ::tiny_ecs::event::EventsOps<#t> as *mut _ }
+                    }
+                }
+            }
+        });
+
+        // `ChangeTicksRef` impl.
+        let change_ticks_ref_impl = quote! {
+            impl ::tiny_ecs::system::ChangeTicksRef for #name {
+                unsafe fn system_ticks(world: *mut Self) -> ::tiny_ecs::change_detection::SystemTicks {
+                    // SAFETY: caller guarantees `world` is valid.
+                    unsafe { (*world).ticks }
+                }
+            }
+        };
+
+        // `DiagnosticsRef` impl, forwarding to the installed skip and error
+        // handlers.
+        let diagnostics_ref_impl = quote! {
+            impl ::tiny_ecs::system::DiagnosticsRef for #name {
+                unsafe fn report_skip(world: *mut Self, skipped: ::tiny_ecs::system::SystemSkipped) {
+                    // SAFETY: caller guarantees `world` is valid.
+                    if let ::core::option::Option::Some(handler) = unsafe { (*world).skip_handler } {
+                        handler(skipped);
+                    }
+                }
+
+                unsafe fn report_error(world: *mut Self, failed: ::tiny_ecs::error::SystemFailed<'_>) {
+                    // SAFETY: caller guarantees `world` is valid and unborrowed.
+                    let world = unsafe { &mut *world };
+                    let handler = world.error_handler.unwrap_or(::tiny_ecs::error::panic::<Self>);
+                    handler(world, failed);
+                }
+            }
+        };
+
+        // `LocalsRef` impl, handing out per-system state.
+        let locals_ref_impl = quote! {
+            impl ::tiny_ecs::system::LocalsRef for #name {
+                unsafe fn local_raw<T: Send + 'static>(
+                    world: *mut Self,
+                    key: ::tiny_ecs::system::LocalKey,
+                    system: &str,
+                    init: fn() -> T,
+                ) -> *mut T {
+                    // SAFETY: caller guarantees `world` is valid; only the
+                    // store is touched, through a raw pointer.
+                    unsafe { ::tiny_ecs::system::SystemLocals::get_or_init(&raw mut (*world).locals, key, system, init) }
+                }
+            }
+        };
+
+        // `TriggerRef` impl, exposing the entity of the event being fired.
+        let trigger_ref_impl = quote! {
+            impl ::tiny_ecs::system::TriggerRef for #name {
+                unsafe fn trigger_entity(world: *mut Self) -> ::tiny_ecs::entity::Entity {
+                    // SAFETY: caller guarantees `world` is valid.
+                    unsafe { (*world).trigger }
+                }
+            }
+        };
+
+        // A slot's generation, read through a raw `world` pointer.
+        let generation_idx = if *generations {
+            quote! { (*world).generations[idx as usize] }
+        } else {
+            quote! { 0 }
+        };
+
         // `CommandsRef` impl.
         let commands_ref_impl = quote! {
             impl ::tiny_ecs::system::CommandsRef for #name {
                 unsafe fn commands_raw(world: *mut Self) -> *mut ::tiny_ecs::commands_buffer::CommandBuffer {
                     unsafe { &mut (*world).commands as *mut _ }
                 }
+                unsafe fn reserve_entity(world: *mut Self) -> ::tiny_ecs::entity::Entity {
+                    // Only the allocator fields are borrowed, never the whole
+                    // world, so a spawning system's query items stay valid.
+                    // SAFETY: caller guarantees `world` is valid.
+                    unsafe {
+                        let idx = match (*world).free_list.pop() {
+                            ::core::option::Option::Some(idx) => idx,
+                            // Entity budget exhausted; hand out a slot past the
+                            // budget, which is never alive, so every command
+                            // queued against it is dropped by the flush.
+                            ::core::option::Option::None if (*world).next_index >= #entities => {
+                                return ::tiny_ecs::entity::Entity::new(#entities as u32);
+                            }
+                            ::core::option::Option::None => {
+                                (*world).next_index += 1;
+                                (*world).next_index - 1
+                            }
+                        };
+                        (*world).alive += 1;
+                        (*world).live.insert(idx as usize);
+                        ::tiny_ecs::entity::Entity::from_raw_parts(idx, #generation_idx)
+                    }
+                }
+            }
+        };
+
+        // Per-slot generation counters, only laid out for generational worlds.
+        let generation_field = generations.then(|| quote! {
+            /// Per-slot generation, bumped on every despawn of that slot.
+            generations: [u32; #entities],
+        });
+        let generation_init = generations.then(|| quote! { generations: [0; #entities], });
+        // Generational worlds need handles that can carry the generation.
+        let generation_check = generations.then(|| quote! {
+            const _: () = ::core::assert!(
+                ::tiny_ecs::entity::GENERATIONAL,
+                "this world tracks generations but tiny_ecs was built without the `generations` feature; declare `generations: false`",
+            );
+        });
+        let generation_of = if *generations {
+            quote! { self.generations[idx as usize] }
+        } else {
+            quote! { 0 }
+        };
+        let generation_bump = generations.then(|| quote! {
+            self.generations[idx as usize] = self.generations[idx as usize].wrapping_add(1);
+        });
+
+        // `EntitiesRef` impl, resolving slots through the inherent helpers.
+        let entities_ref_impl = quote! {
+            impl ::tiny_ecs::system::EntitiesRef for #name {
+                unsafe fn resolve_entity(world: *mut Self, index: u32) -> ::tiny_ecs::entity::Entity {
+                    // SAFETY: caller guarantees `world` is valid.
+                    unsafe { (*world).entity_at(index) }
+                }
+                unsafe fn entity_alive(world: *mut Self, entity: ::tiny_ecs::entity::Entity) -> bool {
+                    // SAFETY: caller guarantees `world` is valid.
+                    unsafe { <Self as ::tiny_ecs::world::WorldApi>::is_alive(&*world, entity) }
+                }
+                unsafe fn entity_bound(world: *mut Self) -> usize {
+                    // SAFETY: caller guarantees `world` is valid.
+                    unsafe { (*world).next_index as usize }
+                }
+                unsafe fn alive_word(world: *mut Self, word: usize) -> u32 {
+                    // SAFETY: caller guarantees `world` is valid.
+                    unsafe { (*world).live.words().get(word).copied().unwrap_or(0) }
+                }
             }
         };
 
+        // Records a removal in the component's log, if it keeps one.
+        let log_removal = |c: &ComponentEntry| {
+            c.removed.as_ref().map(|_| {
+                let rf = format_ident!("removed_{}", c.field);
+                quote! { self.#rf.push(entity, self.ticks.write_tick()); }
+            })
+        };
         // Despawn touches every column.
         let comp_despawns = components.iter().map(|c| {
             let f = &c.field;
-            quote! { self.#f.remove(idx as usize); }
+            let t = &c.ty;
+            let log_removal = log_removal(c);
+            quote! {
+                if self.#f.get(idx as usize).is_some() {
+                    self.trigger::<::tiny_ecs::observer::OnRemove<#t>>(
+                        entity,
+                        <#t as ::tiny_ecs::component::ComponentHooks<Self>>::on_remove(),
+                    );
+                    self.#f.remove(idx as usize);
+                    #log_removal
+                }
+            }
         });
-        // Flush inserts pending spawns into their columns.
+        // Flush applies each pending queue in issue order, skipping stale
+        // handles. Every component that lands queues its required components,
+        // which a later pass of the flush loop applies.
         let comp_flushes = components.iter().map(|c| {
             let f = &c.field;
+            let t = &c.ty;
             let pf = format_ident!("pending_{}", c.field);
+            let name_str = f.to_string();
+            let log_removal = log_removal(c);
             quote! {
-                while let ::core::option::Option::Some((idx, value)) = self.#pf.pop() {
-                    self.#f.insert(idx as usize, value);
+                if !self.#pf.is_empty() {
+                    pending = true;
+                }
+                for (entity, op) in ::core::mem::take(&mut self.#pf) {
+                    if !<Self as ::tiny_ecs::world::WorldApi>::is_alive(self, entity) {
+                        continue;
+                    }
+                    let idx = entity.index() as usize;
+                    let value = match op {
+                        ::tiny_ecs::commands_buffer::ComponentOp::Insert(value) => value,
+                        ::tiny_ecs::commands_buffer::ComponentOp::Require(ctor) if self.#f.get(idx).is_none() => ctor(),
+                        ::tiny_ecs::commands_buffer::ComponentOp::Require(_) => continue,
+                        ::tiny_ecs::commands_buffer::ComponentOp::Remove => {
+                            if self.#f.get(idx).is_some() {
+                                self.trigger::<::tiny_ecs::observer::OnRemove<#t>>(
+                                    entity,
+                                    <#t as ::tiny_ecs::component::ComponentHooks<Self>>::on_remove(),
+                                );
+                                self.#f.remove(idx);
+                                #log_removal
+                            }
+                            continue;
+                        }
+                    };
+                    let added = self.#f.get(idx).is_none();
+                    // A column out of room is a sizing bug in `define_world!`;
+                    // fail loudly instead of losing the component.
+                    if self.#f.insert(idx, value, self.ticks.write_tick()).is_err() {
+                        ::core::panic!("component column `{}` has no room for entity {}", #name_str, idx);
+                    }
+                    // SAFETY: `self` is valid; this only pushes onto the
+                    // pending queues of the required components.
+                    unsafe {
+                        <#t as ::tiny_ecs::component::RequiredComponents<Self>>::enqueue_required(self as *mut Self, entity);
+                    }
+                    if added {
+                        self.trigger::<::tiny_ecs::observer::OnAdd<#t>>(
+                            entity,
+                            <#t as ::tiny_ecs::component::ComponentHooks<Self>>::on_add(),
+                        );
+                    }
+                    self.trigger::<::tiny_ecs::observer::OnInsert<#t>>(
+                        entity,
+                        <#t as ::tiny_ecs::component::ComponentHooks<Self>>::on_insert(),
+                    );
+                }
+            }
+        });
+        // Resource commands apply the last insert or removal queued for each
+        // resource; neither queues further work.
+        let res_flushes = resources.iter().map(|r| {
+            let f = &r.field;
+            let t = &r.ty;
+            let pf = format_ident!("pending_{}", r.field);
+            quote! {
+                match self.#pf.take() {
+                    ::core::option::Option::Some(::core::option::Option::Some(value)) => {
+                        // SAFETY: `self` is valid and no system is running.
+                        unsafe { <Self as ::tiny_ecs::system::ResourceInsRef<#t>>::insert_resource(self as *mut Self, value) }
+                    }
+                    ::core::option::Option::Some(::core::option::Option::None) => self.#f = ::core::option::Option::None,
+                    ::core::option::Option::None => {}
                 }
             }
         });
 
+        // Every stored tick, for `check_change_ticks`.
+        let comp_field_names = components.iter().map(|c| &c.field);
+        let res_tick_names = resources.iter().map(|r| format_ident!("ticks_{}", r.field));
+        let removed_names = components
+            .iter()
+            .filter(|c| c.removed.is_some())
+            .map(|c| format_ident!("removed_{}", c.field));
+
         let expanded = quote! {
             /// Auto-generated `World` produced by `tiny_ecs::define_world!`.
             #vis struct #name {
@@ -316,13 +778,38 @@ impl WorldInput {
                 free_list: ::heapless::Vec<u32, { #entities }>,
                 /// Live entity count.
                 alive: u32,
+                /// One bit per live slot; drives `Entity` and `Option<..>` fetches.
+                live: ::tiny_ecs::bitset::BitSet<{ ::tiny_ecs::bitset::words_for(#entities) }>,
+                /// Slots whose `OnRemove` hooks are running inside `despawn`.
+                despawning: ::tiny_ecs::bitset::BitSet<{ ::tiny_ecs::bitset::words_for(#entities) }>,
+                #generation_field
                 #( #comp_fields, )*
                 #( #comp_pendings, )*
+                #( #comp_removed_fields, )*
                 #( #res_fields, )*
+                #( #event_fields, )*
                 /// Bounded schedule registry, keyed by `TypeId` of the label.
-                schedules: ::heapless::LinearMap<::core::any::TypeId, ::tiny_ecs::schedule::Schedule, { #schedules }>,
+                schedules: ::heapless::LinearMap<
+                    ::core::any::TypeId,
+                    ::tiny_ecs::schedule::Schedule<{ #systems }, { #sets }, { #conditions }, { #orderings }>,
+                    { #schedules },
+                >,
                 /// Deferred despawn queue and pending-spawn drain buffer.
                 commands: ::tiny_ecs::commands_buffer::CommandBuffer,
+                /// The change tick and the running system's previous run.
+                ticks: ::tiny_ecs::change_detection::SystemTicks,
+                /// The change tick of the last `check_change_ticks` pass.
+                last_check_tick: ::tiny_ecs::change_detection::Tick,
+                /// Receives a diagnostic for every skipped system, if installed.
+                skip_handler: ::core::option::Option<fn(::tiny_ecs::system::SystemSkipped)>,
+                /// Receives every error a fallible system returns; panics if unset.
+                error_handler: ::core::option::Option<fn(&mut Self, ::tiny_ecs::error::SystemFailed<'_>)>,
+                /// Registered observers, keyed by `TypeId` of their event.
+                observers: ::heapless::Vec<(::core::any::TypeId, ::tiny_ecs::system::System), { ::tiny_ecs::observer::MAX_OBSERVERS }>,
+                /// The entity of the event being fired, read by `Trigger`.
+                trigger: ::tiny_ecs::entity::Entity,
+                /// The state systems keep across runs (`Local`s, reader cursors).
+                locals: ::tiny_ecs::system::SystemLocals<{ #locals }>,
             }
 
             impl #name {
@@ -332,11 +819,26 @@ impl WorldInput {
                         next_index: 0,
                         free_list: ::heapless::Vec::new(),
                         alive: 0,
+                        live: ::tiny_ecs::bitset::BitSet::new(),
+                        despawning: ::tiny_ecs::bitset::BitSet::new(),
+                        #generation_init
                         #( #comp_inits, )*
                         #( #comp_pending_inits, )*
+                        #( #comp_removed_inits, )*
                         #( #res_inits, )*
+                        #( #event_inits, )*
                         schedules: ::heapless::LinearMap::new(),
                         commands: ::tiny_ecs::commands_buffer::CommandBuffer::new(),
+                        ticks: ::tiny_ecs::change_detection::SystemTicks {
+                            last_run: ::tiny_ecs::change_detection::Tick::new(0),
+                            this_run: ::tiny_ecs::change_detection::Tick::new(0),
+                        },
+                        last_check_tick: ::tiny_ecs::change_detection::Tick::new(0),
+                        skip_handler: ::core::option::Option::None,
+                        error_handler: ::core::option::Option::None,
+                        observers: ::heapless::Vec::new(),
+                        trigger: ::tiny_ecs::entity::Entity::new(0),
+                        locals: ::tiny_ecs::system::SystemLocals::new(),
                     }
                 }
 
@@ -350,32 +852,88 @@ impl WorldInput {
                     unsafe { <Self as ::tiny_ecs::system::ResourceInsRef<R>>::insert_resource(world_ptr, resource); }
                 }
 
-                /// Allocates a fresh entity id, reusing a recycled one when
-                /// available; returns `None` when the entity budget is full.
-                #vis fn alloc_entity(&mut self) -> u32 {
-                    if let ::core::option::Option::Some(idx) = self.free_list.pop() {
-                        self.alive += 1;
-                        return idx;
+                /// Takes a resource out of the world, returning it if it was
+                /// inserted.
+                #vis fn remove_resource<R: 'static>(&mut self) -> ::core::option::Option<R>
+                where
+                    Self: ::tiny_ecs::system::ResourceInsRef<R>,
+                {
+                    <Self as ::tiny_ecs::world::WorldApi>::remove_resource(self)
+                }
+
+                /// Returns the schedule under label `L`, adding it when new.
+                ///
+                /// # Panics
+                /// When the label is new and the schedule table is full.
+                fn schedule_mut<L: ::tiny_ecs::schedule::ScheduleLabel>(
+                    &mut self,
+                    label: L,
+                ) -> &mut ::tiny_ecs::schedule::Schedule<{ #systems }, { #sets }, { #conditions }, { #orderings }> {
+                    let id = ::core::any::TypeId::of::<L>();
+                    <Self as ::tiny_ecs::world::WorldApi>::add_schedule(self, label);
+                    self.schedules.get_mut(&id).expect("just added")
+                }
+
+                /// Allocates a fresh entity, reusing a recycled slot when
+                /// available (at its bumped generation).
+                #vis fn alloc_entity(&mut self) -> ::tiny_ecs::entity::Entity {
+                    // SAFETY: `self` is a valid, exclusively borrowed world.
+                    unsafe { <Self as ::tiny_ecs::system::CommandsRef>::reserve_entity(self) }
+                }
+
+                /// Runs `hook` (if any), then every observer of the event `E`,
+                /// each with `entity` as its trigger.
+                fn trigger<E: 'static>(&mut self, entity: ::tiny_ecs::entity::Entity, hook: ::core::option::Option<::tiny_ecs::system::System>) {
+                    // A hook or observer may fire events of its own (an
+                    // exclusive one can flush), so hand the outer event's
+                    // entity back once this one is done.
+                    let outer = ::core::mem::replace(&mut self.trigger, entity);
+                    let world_ptr = self as *mut Self as *mut ();
+                    if let ::core::option::Option::Some(hook) = hook {
+                        hook(world_ptr);
                     }
-                    if self.next_index >= #entities {
-                        // Entity budget exhausted; reuse slot 0 defensively.
-                        return 0;
+                    let id = ::core::any::TypeId::of::<E>();
+                    for i in 0..self.observers.len() {
+                        let (event, observer) = self.observers[i];
+                        if event == id {
+                            observer(world_ptr);
+                        }
                     }
-                    let idx = self.next_index;
-                    self.next_index += 1;
-                    self.alive += 1;
-                    idx
+                    self.trigger = outer;
+                }
+
+                /// Returns the handle for slot `idx` at the slot's current
+                /// generation.
+                #vis fn entity_at(&self, idx: u32) -> ::tiny_ecs::entity::Entity {
+                    ::tiny_ecs::entity::Entity::from_raw_parts(idx, #generation_of)
+                }
+
+                /// Sends `event` to every `EventReader<E>`.
+                #vis fn send_event<E: 'static>(&mut self, event: E)
+                where
+                    Self: ::tiny_ecs::system::EventsRef<E>,
+                {
+                    let world_ptr: *mut Self = self as *mut Self;
+                    // SAFETY: the bound guarantees the buffers exist; world_ptr valid.
+                    unsafe { (*<Self as ::tiny_ecs::system::EventsRef<E>>::events_raw(world_ptr)).send(event) };
                 }
 
                 /// Inserts a component onto `entity`'s slot of column `T`.
+                /// Stale handles are ignored; panics when the column is full.
                 #vis fn set_component<T: 'static>(&mut self, entity: ::tiny_ecs::entity::Entity, value: T)
                 where
                     Self: ::tiny_ecs::system::ColumnRef<T>,
                 {
+                    if !<Self as ::tiny_ecs::world::WorldApi>::is_alive(self, entity) {
+                        return;
+                    }
                     let world_ptr: *mut Self = self as *mut Self;
                     // SAFETY: bound guarantees the column exists; world_ptr valid.
                     let col = unsafe { <Self as ::tiny_ecs::system::ColumnRef<T>>::col_mut_raw(world_ptr) };
-                    unsafe { (*col).insert(entity.index() as usize, value); }
+                    let inserted = unsafe { (*col).insert(entity.index() as usize, value, self.ticks.write_tick()) };
+                    if inserted.is_err() {
+                        ::core::panic!("component column of `{}` has no room for entity {}", ::core::any::type_name::<T>(), entity.index());
+                    }
                 }
             }
 
@@ -387,27 +945,65 @@ impl WorldInput {
 
             impl ::tiny_ecs::world::WorldApi for #name {
                 fn add_schedule<L: ::tiny_ecs::schedule::ScheduleLabel>(&mut self, _label: L) {
-                    let _ = self.schedules.insert(::core::any::TypeId::of::<L>(), ::tiny_ecs::schedule::Schedule::new());
-                }
-
-                fn add_system<L: ::tiny_ecs::schedule::ScheduleLabel>(&mut self, _label: L, system: ::tiny_ecs::system::System) {
                     let id = ::core::any::TypeId::of::<L>();
-                    if let ::core::option::Option::Some(sched) = self.schedules.get_mut(&id) {
-                        sched.add(system);
+                    if self.schedules.contains_key(&id) {
                         return;
                     }
-                    let mut sched = ::tiny_ecs::schedule::Schedule::new();
-                    sched.add(system);
-                    let _ = self.schedules.insert(id, sched);
+                    if self.schedules.insert(id, ::tiny_ecs::schedule::Schedule::new()).is_err() {
+                        ::core::panic!(
+                            "no room for schedule `{}`; raise `schedules:` in `define_world!`",
+                            ::core::any::type_name::<L>(),
+                        );
+                    }
+                }
+
+                fn add_system<L, M, S>(&mut self, label: L, system: S)
+                where
+                    L: ::tiny_ecs::schedule::ScheduleLabel,
+                    S: ::tiny_ecs::schedule::IntoSystemConfig<Self, M>,
+                {
+                    self.schedule_mut(label).add(system.into_config());
+                }
+
+                fn add_systems<L, M, S>(&mut self, label: L, systems: S)
+                where
+                    L: ::tiny_ecs::schedule::ScheduleLabel,
+                    S: ::tiny_ecs::schedule::IntoSystemConfigs<Self, M>,
+                {
+                    let sched = self.schedule_mut(label);
+                    systems.into_configs().into_iter().for_each(|config| sched.add(config));
+                }
+
+                fn configure_set<L, S>(&mut self, label: L, set: S)
+                where
+                    L: ::tiny_ecs::schedule::ScheduleLabel,
+                    S: ::tiny_ecs::schedule::IntoSetConfig<Self>,
+                {
+                    self.schedule_mut(label).configure_set(set.into_set_config());
                 }
 
                 fn run_schedule<L: ::tiny_ecs::schedule::ScheduleLabel>(&mut self, _label: L) {
-                    // Derive the raw world pointer first, releasing any borrow
-                    // before the immutable `&self.schedules` lookup below.
-                    let world_ptr: *mut () = self as *mut Self as *mut ();
                     let id = ::core::any::TypeId::of::<L>();
-                    if let ::core::option::Option::Some(sched) = self.schedules.get(&id) {
-                        sched.run(world_ptr);
+                    // Take the schedule out of the world while it runs, so an
+                    // exclusive system can borrow the whole world (and run
+                    // other schedules). Its stand-in makes a nested run of
+                    // this one a no-op, and adding systems to it a panic.
+                    let ::core::option::Option::Some(slot) = self.schedules.get_mut(&id) else {
+                        return;
+                    };
+                    let mut sched = ::core::mem::replace(slot, ::tiny_ecs::schedule::Schedule::running());
+                    // A nested run overwrites the running system's ticks; hand
+                    // its last run back afterwards (the change tick only ever
+                    // moves forward).
+                    let outer = self.ticks.last_run;
+                    let world_ptr: *mut Self = self as *mut Self;
+                    // SAFETY: `world_ptr` is valid; systems never borrow `ticks`.
+                    let ticks = unsafe { &mut (*world_ptr).ticks as *mut ::tiny_ecs::change_detection::SystemTicks };
+                    // SAFETY: both pointers come from `self`, which outlives the run.
+                    unsafe { sched.run(world_ptr as *mut (), ticks) };
+                    self.ticks.last_run = outer;
+                    if let ::core::option::Option::Some(slot) = self.schedules.get_mut(&id) {
+                        *slot = sched;
                     }
                 }
 
@@ -415,7 +1011,7 @@ impl WorldInput {
                     if self.next_index >= #entities && self.free_list.is_empty() {
                         return ::core::option::Option::None;
                     }
-                    ::core::option::Option::Some(::tiny_ecs::entity::Entity::new(self.alloc_entity()))
+                    ::core::option::Option::Some(self.alloc_entity())
                 }
 
                 fn insert_resource<R: 'static>(&mut self, resource: R)
@@ -427,11 +1023,37 @@ impl WorldInput {
                     unsafe { <Self as ::tiny_ecs::system::ResourceInsRef<R>>::insert_resource(world_ptr, resource); }
                 }
 
-                fn despawn(&mut self, entity: ::tiny_ecs::entity::Entity) {
+                fn remove_resource<R: 'static>(&mut self) -> ::core::option::Option<R>
+                where
+                    Self: ::tiny_ecs::system::ResourceInsRef<R>,
+                {
+                    let world_ptr: *mut Self = self as *mut Self;
+                    // SAFETY: `world_ptr` is valid; `&mut self` rules out live borrows.
+                    unsafe { <Self as ::tiny_ecs::system::ResourceInsRef<R>>::remove_resource(world_ptr) }
+                }
+
+                fn despawn(&mut self, entity: ::tiny_ecs::entity::Entity) -> bool {
+                    // Stale handles must not touch the slot's new occupant.
+                    // An `OnRemove` hook or observer despawning an entity that
+                    // is already on its way out would otherwise recurse.
+                    if !self.is_alive(entity) || self.despawning.contains(entity.index() as usize) {
+                        return false;
+                    }
                     let idx = entity.index();
+                    self.despawning.insert(idx as usize);
                     #( #comp_despawns )*
+                    self.despawning.remove(idx as usize);
+                    #generation_bump
                     let _ = self.free_list.push(idx);
+                    self.live.remove(idx as usize);
                     self.alive = self.alive.saturating_sub(1);
+                    true
+                }
+
+                fn is_alive(&self, entity: ::tiny_ecs::entity::Entity) -> bool {
+                    // `live` only holds allocated slots, so check it before
+                    // reading the slot's generation.
+                    self.live.contains(entity.index() as usize) && self.entity_at(entity.index()) == entity
                 }
 
                 fn entity_count(&self) -> usize {
@@ -439,32 +1061,90 @@ impl WorldInput {
                 }
 
                 fn flush_commands(&mut self) {
-                    // First, move every pending spawn value into its column.
-                    #( #comp_flushes )*
-                    // Then apply deferred despawns. Pop each command in its own
-                    // statement so the `&mut self.commands` borrow ends before
-                    // `self.despawn` reborrows the whole world mutably.
+                    self.check_change_ticks();
+                    // Each pass applies every pending spawn, insert and remove,
+                    // then the deferred despawns. Required components, hooks
+                    // and observers may queue more work, so loop until a pass
+                    // finds nothing pending.
                     loop {
-                        let cmd = self.commands.drain().next();
-                        match cmd {
-                            ::core::option::Option::Some(
-                                ::tiny_ecs::commands_buffer::Command::Despawn(e),
-                            ) => self.despawn(e),
-                            ::core::option::Option::None => break,
+                        let mut pending = false;
+                        #( #comp_flushes )*
+                        #( #res_flushes )*
+                        // Pop each command in its own statement so the
+                        // `&mut self.commands` borrow ends before `self.despawn`
+                        // reborrows the whole world mutably.
+                        loop {
+                            let cmd = self.commands.drain().next();
+                            match cmd {
+                                ::core::option::Option::Some(
+                                    ::tiny_ecs::commands_buffer::Command::Despawn(e),
+                                ) => {
+                                    pending = true;
+                                    self.despawn(e);
+                                }
+                                ::core::option::Option::None => break,
+                            }
+                        }
+                        if !pending {
+                            break;
                         }
                     }
                 }
 
+                fn check_change_ticks(&mut self) {
+                    // Nothing stored is newer than the write tick.
+                    let this_run = self.ticks.write_tick();
+                    let since_check = this_run.get().wrapping_sub(self.last_check_tick.get());
+                    if since_check < ::tiny_ecs::change_detection::CHECK_TICK_THRESHOLD {
+                        return;
+                    }
+                    self.last_check_tick = this_run;
+                    #( self.#comp_field_names.check_change_ticks(this_run); )*
+                    #( self.#res_tick_names.check_ticks(this_run); )*
+                    #( self.#removed_names.check_change_ticks(this_run); )*
+                    // A running schedule is out of the map; its systems are
+                    // clamped by the next pass.
+                    for (_, sched) in self.schedules.iter_mut() {
+                        sched.check_change_ticks(this_run);
+                    }
+                }
+
+                fn update_events(&mut self) {
+                    #( #event_updates )*
+                }
+
+                fn add_observer<E: ::tiny_ecs::observer::ComponentEvent>(&mut self, system: ::tiny_ecs::system::System) {
+                    if self.observers.push((::core::any::TypeId::of::<E>(), system)).is_err() {
+                        ::core::panic!("more than {} observers registered", ::tiny_ecs::observer::MAX_OBSERVERS);
+                    }
+                }
+
                 fn commands_ptr(&mut self) -> *mut ::tiny_ecs::commands_buffer::CommandBuffer {
                     &mut self.commands as *mut _
                 }
+
+                fn set_skip_handler(&mut self, handler: fn(::tiny_ecs::system::SystemSkipped)) {
+                    self.skip_handler = ::core::option::Option::Some(handler);
+                }
+
+                fn set_error_handler(&mut self, handler: fn(&mut Self, ::tiny_ecs::error::SystemFailed<'_>)) {
+                    self.error_handler = ::core::option::Option::Some(handler);
+                }
             }
 
             #( #column_ref_impls )*
             #( #resource_ref_impls )*
             #( #resource_ins_impls )*
-            #( #spawn_ref_impls )*
+            #( #entity_commands_ref_impls )*
+            #( #removed_ref_impls )*
+            #( #events_ref_impls )*
+            #entities_ref_impl
+            #change_ticks_ref_impl
+            #diagnostics_ref_impl
+            #trigger_ref_impl
+            #locals_ref_impl
             #commands_ref_impl
+            #generation_check
         };
 
         Ok(expanded)